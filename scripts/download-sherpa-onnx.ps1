@@ -0,0 +1,67 @@
+# Download sherpa-onnx offline CLI binary for the Parakeet Tauri sidecar
+# Usage: pwsh scripts/download-sherpa-onnx.ps1 [-Version v1.12.14]
+
+param(
+    [string]$Version = "v1.12.14"
+)
+
+$ErrorActionPreference = "Stop"
+
+$repo = "k2-fsa/sherpa-onnx"
+$assetName = "sherpa-onnx-$Version-win-x64-shared.tar.bz2"
+$downloadUrl = "https://github.com/$repo/releases/download/$Version/$assetName"
+
+$binariesDir = Join-Path $PSScriptRoot ".." "src-tauri" "binaries"
+$targetBinary = Join-Path $binariesDir "sherpa-onnx-offline-x86_64-pc-windows-msvc.exe"
+
+# Check if binary already exists
+if (Test-Path $targetBinary) {
+    Write-Host "sherpa-onnx-offline sidecar already exists at $targetBinary"
+    Write-Host "Delete it first if you want to re-download."
+    exit 0
+}
+
+# Ensure binaries directory exists
+if (-not (Test-Path $binariesDir)) {
+    New-Item -ItemType Directory -Path $binariesDir -Force | Out-Null
+}
+
+$tempArchive = Join-Path $env:TEMP $assetName
+$tempExtract = Join-Path $env:TEMP "sherpa-onnx-win-x64"
+
+try {
+    Write-Host "Downloading sherpa-onnx $Version ($assetName)..."
+    Invoke-WebRequest -Uri $downloadUrl -OutFile $tempArchive -UseBasicParsing
+
+    Write-Host "Extracting..."
+    if (Test-Path $tempExtract) {
+        Remove-Item $tempExtract -Recurse -Force
+    }
+    New-Item -ItemType Directory -Path $tempExtract -Force | Out-Null
+    tar -xjf $tempArchive -C $tempExtract
+
+    $sourceBinary = Get-ChildItem -Path $tempExtract -Filter "sherpa-onnx-offline.exe" -Recurse -ErrorAction SilentlyContinue | Select-Object -First 1
+    if (-not $sourceBinary) {
+        Write-Host "Available files in archive:"
+        Get-ChildItem -Path $tempExtract -Recurse | ForEach-Object { Write-Host "  $($_.FullName)" }
+        throw "Could not find sherpa-onnx-offline.exe in the downloaded archive"
+    }
+
+    Copy-Item $sourceBinary.FullName $targetBinary
+    Write-Host "Installed sherpa-onnx-offline sidecar to: $targetBinary"
+
+    # Shared build: copy onnxruntime / sherpa-onnx DLLs next to the binary
+    $dllFiles = Get-ChildItem -Path $tempExtract -Filter "*.dll" -Recurse -ErrorAction SilentlyContinue
+    foreach ($dll in $dllFiles) {
+        $destDll = Join-Path $binariesDir $dll.Name
+        Copy-Item $dll.FullName $destDll
+        Write-Host "Copied dependency: $($dll.Name)"
+    }
+}
+finally {
+    # Clean up temp files
+    if (Test-Path $tempArchive) { Remove-Item $tempArchive -Force }
+    if (Test-Path $tempExtract) { Remove-Item $tempExtract -Recurse -Force }
+}
+
+Write-Host "Done! sherpa-onnx-offline sidecar is ready."
@@ -1,4 +1,4 @@
-# Download whisper.cpp CLI binary for Tauri sidecar
+# Download whisper.cpp CLI and server binaries for Tauri sidecars
 # Usage: pwsh scripts/download-whisper-cpp.ps1 [-Version v1.8.3]
 
 param(
@@ -13,10 +13,11 @@ $downloadUrl = "https://github.com/$repo/releases/download/$Version/$assetName"
 
 $binariesDir = Join-Path $PSScriptRoot ".." "src-tauri" "binaries"
 $targetBinary = Join-Path $binariesDir "whisper-cpp-x86_64-pc-windows-msvc.exe"
+$targetServer = Join-Path $binariesDir "whisper-server-x86_64-pc-windows-msvc.exe"
 
-# Check if binary already exists
-if (Test-Path $targetBinary) {
-    Write-Host "whisper-cpp sidecar already exists at $targetBinary"
+# Check if binaries already exist
+if ((Test-Path $targetBinary) -and (Test-Path $targetServer)) {
+    Write-Host "whisper-cpp sidecars already exist in $binariesDir"
     Write-Host "Delete it first if you want to re-download."
     exit 0
 }
@@ -61,6 +62,15 @@ try {
     Copy-Item $sourceBinary $targetBinary
     Write-Host "Installed whisper-cpp sidecar to: $targetBinary"
 
+    # HTTP server mode, used for warm local transcription
+    $serverBinary = Get-ChildItem -Path $tempExtract -Filter "whisper-server.exe" -Recurse -ErrorAction SilentlyContinue | Select-Object -First 1
+    if ($serverBinary) {
+        Copy-Item $serverBinary.FullName $targetServer
+        Write-Host "Installed whisper-server sidecar to: $targetServer"
+    } else {
+        Write-Host "whisper-server.exe not found in archive; server mode will be unavailable"
+    }
+
     # Also copy any DLLs that the binary might need
     $dllFiles = Get-ChildItem -Path (Split-Path $sourceBinary) -Filter "*.dll" -ErrorAction SilentlyContinue
     foreach ($dll in $dllFiles) {
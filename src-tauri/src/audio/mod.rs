@@ -1,3 +1,7 @@
 pub mod recorder;
+pub mod resampler;
 
-pub use recorder::{AudioDevice, AudioRecorder, RecordingState};
+pub use recorder::{
+    AudioDevice, AudioError, AudioRecorder, RecordingState, TARGET_SAMPLE_RATE, encode_wav, speech_bounds,
+    wav_duration_ms,
+};
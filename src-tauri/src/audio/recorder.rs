@@ -1,24 +1,50 @@
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{SampleFormat, SampleRate, StreamConfig};
 use hound::{WavSpec, WavWriter};
+use super::resampler::{ResampleQuality, StreamingResampler};
 use serde::Serialize;
 use std::io::Cursor;
 use std::panic;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use std::ops::Range;
 use std::thread::JoinHandle;
+use std::time::Instant;
 use thiserror::Error;
 
 /// Target sample rate for whisper.cpp input.
-const TARGET_SAMPLE_RATE: u32 = 16_000;
+pub const TARGET_SAMPLE_RATE: u32 = 16_000;
 
 /// Arc handles needed by the audio level emitter thread.
-pub type LevelEmitterHandles = (Arc<AtomicBool>, Arc<Mutex<f32>>, Arc<Mutex<Option<String>>>);
+pub type LevelEmitterHandles = (
+    Arc<AtomicBool>,
+    Arc<Mutex<f32>>,
+    Arc<Mutex<Option<String>>>,
+    Arc<Mutex<Option<Instant>>>,
+);
 
 /// Preferred sample rates to try when negotiating with the device.
 /// Ordered by preference: whisper-native first, then common rates.
 const PREFERRED_RATES: &[u32] = &[16_000, 44_100, 48_000];
 
+/// VAD analysis frame length.
+const VAD_FRAME_MS: u32 = 30;
+/// RMS below this is always silence (about -40 dBFS).
+const VAD_MIN_RMS: f32 = 0.01;
+/// A frame is speech when its RMS exceeds the noise floor by this factor.
+const VAD_NOISE_FACTOR: f32 = 3.0;
+/// Ceiling on the estimated noise floor (about -34 dBFS). A clip that is
+/// speech from start to end has no quiet frames, so without a cap its
+/// "floor" would be speech and nothing would clear the threshold.
+const VAD_MAX_NOISE_FLOOR: f32 = 0.02;
+/// Frames crossing zero more often than this are hiss/noise, not voice.
+const VAD_MAX_ZCR: f32 = 0.4;
+/// Shortest run of speech frames that counts as speech (filters clicks).
+const VAD_MIN_SPEECH_MS: u32 = 120;
+/// Audio kept around detected speech when trimming, so word onsets and
+/// trailing consonants aren't clipped.
+const VAD_PAD_MS: u32 = 250;
+
 #[derive(Error, Debug)]
 pub enum AudioError {
     #[error("No audio input device available")]
@@ -39,6 +65,8 @@ pub enum AudioError {
     ThreadPanic,
     #[error("Device disconnected during recording")]
     DeviceDisconnected,
+    #[error("No speech detected")]
+    NoSpeech,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -50,14 +78,18 @@ pub struct AudioDevice {
 
 /// Shared recording state that is Send + Sync (no cpal Stream stored here).
 pub struct RecordingState {
+    /// Captured audio, already mono at [`TARGET_SAMPLE_RATE`].
     samples: Arc<Mutex<Vec<f32>>>,
     is_recording: Arc<AtomicBool>,
     peak_level: Arc<Mutex<f32>>,
-    sample_rate: Mutex<u32>,
     /// Set by the stream error callback when the device disconnects or errors.
     recording_error: Arc<Mutex<Option<String>>>,
     /// Handle to the recording thread for panic detection.
     thread_handle: Mutex<Option<JoinHandle<()>>>,
+    /// When the stream callback last saw speech; `None` until the first word.
+    last_speech: Arc<Mutex<Option<Instant>>>,
+    /// Name of the device the current or last recording used.
+    device_name: Mutex<Option<String>>,
 }
 
 /// The AudioRecorder manages recording lifecycle.
@@ -70,9 +102,10 @@ impl RecordingState {
             samples: Arc::new(Mutex::new(Vec::new())),
             is_recording: Arc::new(AtomicBool::new(false)),
             peak_level: Arc::new(Mutex::new(0.0)),
-            sample_rate: Mutex::new(TARGET_SAMPLE_RATE),
             recording_error: Arc::new(Mutex::new(None)),
             thread_handle: Mutex::new(None),
+            last_speech: Arc::new(Mutex::new(None)),
+            device_name: Mutex::new(None),
         }
     }
 
@@ -80,11 +113,23 @@ impl RecordingState {
         *self.peak_level.lock().unwrap()
     }
 
-    #[allow(dead_code)]
     pub fn is_recording(&self) -> bool {
         self.is_recording.load(Ordering::SeqCst)
     }
 
+    /// Audio captured so far from `start` (an offset in 16 kHz samples) to the
+    /// end of the buffer. Lets live transcription read rolling chunks while the
+    /// recording thread keeps appending.
+    pub fn chunk_16k(&self, start: usize) -> Vec<f32> {
+        let samples = self.samples.lock().unwrap();
+        samples.get(start..).map(<[f32]>::to_vec).unwrap_or_default()
+    }
+
+    /// Name of the input device the current or last recording used.
+    pub fn device_name(&self) -> Option<String> {
+        self.device_name.lock().unwrap().clone()
+    }
+
     /// Returns the error message if the recording thread hit a device error.
     pub fn get_error(&self) -> Option<String> {
         self.recording_error.lock().unwrap().clone()
@@ -96,6 +141,7 @@ impl RecordingState {
             Arc::clone(&self.is_recording),
             Arc::clone(&self.peak_level),
             Arc::clone(&self.recording_error),
+            Arc::clone(&self.last_speech),
         )
     }
 }
@@ -131,7 +177,12 @@ impl AudioRecorder {
 
     /// Start recording on a background thread. The cpal Stream lives
     /// on that thread and is dropped when stop() flips is_recording to false.
-    pub fn start(state: &RecordingState, device_id: Option<String>) -> Result<(), AudioError> {
+    /// Audio is resampled to 16 kHz with the given filter `quality` as it arrives.
+    pub fn start(
+        state: &RecordingState,
+        device_id: Option<String>,
+        quality: ResampleQuality,
+    ) -> Result<(), AudioError> {
         if state.is_recording.load(Ordering::SeqCst) {
             return Err(AudioError::AlreadyRecording);
         }
@@ -149,6 +200,7 @@ impl AudioRecorder {
             state.samples.lock().unwrap().clear();
             *state.recording_error.lock().unwrap() = None;
             *state.peak_level.lock().unwrap() = 0.0;
+            *state.last_speech.lock().unwrap() = None;
         }
 
         let host = cpal::default_host();
@@ -160,15 +212,18 @@ impl AudioRecorder {
                 .ok_or_else(|| AudioError::DeviceNotFound(id.clone()))?,
             None => host.default_input_device().ok_or(AudioError::NoDevice)?,
         };
+        *state.device_name.lock().unwrap() = device.name().ok();
 
         // Negotiate sample rate: try preferred rates, fall back to device default
         let (config, sample_format) = negotiate_config(&device)?;
-        let sr = config.sample_rate.0;
-        *state.sample_rate.lock().unwrap() = sr;
+        log::info!("Recording at {} Hz, resampling to {} Hz", config.sample_rate.0, TARGET_SAMPLE_RATE);
         let channels = config.channels as usize;
 
-        let samples = Arc::clone(&state.samples);
-        let peak_level = Arc::clone(&state.peak_level);
+        let sink = CaptureSink {
+            samples: Arc::clone(&state.samples),
+            peak_level: Arc::clone(&state.peak_level),
+            last_speech: Arc::clone(&state.last_speech),
+        };
         let is_recording = Arc::clone(&state.is_recording);
         let recording_error = Arc::clone(&state.recording_error);
 
@@ -189,8 +244,8 @@ impl AudioRecorder {
                             config,
                             sample_format,
                             channels,
-                            samples,
-                            peak_level,
+                            quality,
+                            sink,
                             is_recording: Arc::clone(&is_recording_thread),
                             recording_error: Arc::clone(&recording_error_thread),
                         },
@@ -215,10 +270,10 @@ impl AudioRecorder {
         if !state.is_recording.load(Ordering::SeqCst) {
             // Check if the thread panicked
             let mut handle = state.thread_handle.lock().unwrap();
-            if let Some(h) = handle.take() {
-                if h.join().is_err() {
-                    return Err(AudioError::ThreadPanic);
-                }
+            if let Some(h) = handle.take()
+                && h.join().is_err()
+            {
+                return Err(AudioError::ThreadPanic);
             }
             // Check if a device error was stored
             if let Some(err_msg) = state.get_error() {
@@ -257,16 +312,25 @@ impl AudioRecorder {
             return Err(AudioError::NotRecording);
         }
 
-        let sr = *state.sample_rate.lock().unwrap();
-
-        // Resample to 16kHz mono if needed
-        let mono_16k = if sr != TARGET_SAMPLE_RATE {
-            resample(&samples, sr, TARGET_SAMPLE_RATE)
-        } else {
-            samples
+        // Drop leading/trailing silence. Only a clip that never gets above the
+        // silence level is refused; any other VAD miss keeps the whole clip.
+        let speech = match speech_bounds(&samples, TARGET_SAMPLE_RATE) {
+            Some(speech) => {
+                log::info!(
+                    "Trimmed silence: kept {} of {} samples",
+                    speech.len(),
+                    samples.len()
+                );
+                speech
+            }
+            None if is_silent(&samples, TARGET_SAMPLE_RATE) => return Err(AudioError::NoSpeech),
+            None => {
+                log::warn!("No speech run detected, keeping the untrimmed clip");
+                0..samples.len()
+            }
         };
 
-        encode_wav(&mono_16k, TARGET_SAMPLE_RATE)
+        encode_wav(&samples[speech], TARGET_SAMPLE_RATE)
     }
 }
 
@@ -310,12 +374,20 @@ struct RecordingThreadParams {
     config: StreamConfig,
     sample_format: SampleFormat,
     channels: usize,
-    samples: Arc<Mutex<Vec<f32>>>,
-    peak_level: Arc<Mutex<f32>>,
+    quality: ResampleQuality,
+    sink: CaptureSink,
     is_recording: Arc<AtomicBool>,
     recording_error: Arc<Mutex<Option<String>>>,
 }
 
+/// Shared state the capture callback writes into.
+#[derive(Clone)]
+struct CaptureSink {
+    samples: Arc<Mutex<Vec<f32>>>,
+    peak_level: Arc<Mutex<f32>>,
+    last_speech: Arc<Mutex<Option<Instant>>>,
+}
+
 /// Store an error message in the shared recording error state.
 fn set_recording_error(recording_error: &Mutex<Option<String>>, msg: String) {
     if let Ok(mut err) = recording_error.lock() {
@@ -329,8 +401,8 @@ fn run_recording_thread(device: &cpal::Device, params: RecordingThreadParams) {
         config,
         sample_format,
         channels,
-        samples,
-        peak_level,
+        quality,
+        sink,
         is_recording,
         recording_error,
     } = params;
@@ -343,36 +415,43 @@ fn run_recording_thread(device: &cpal::Device, params: RecordingThreadParams) {
         is_rec_err.store(false, Ordering::SeqCst);
     };
 
+    // Shared with the callback so the filter tail can be flushed after the stream stops
+    let resampler = Arc::new(Mutex::new(StreamingResampler::new(
+        config.sample_rate.0,
+        TARGET_SAMPLE_RATE,
+        quality,
+    )));
+
     let stream_result = match sample_format {
         SampleFormat::F32 => build_stream::<f32>(
-            device, &config, Arc::clone(&samples), Arc::clone(&peak_level), channels, err_callback,
+            device, &config, sink.clone(), Arc::clone(&resampler), channels, err_callback,
         ),
         SampleFormat::I16 => build_stream::<i16>(
-            device, &config, Arc::clone(&samples), Arc::clone(&peak_level), channels, err_callback,
+            device, &config, sink.clone(), Arc::clone(&resampler), channels, err_callback,
         ),
         SampleFormat::U16 => build_stream::<u16>(
-            device, &config, Arc::clone(&samples), Arc::clone(&peak_level), channels, err_callback,
+            device, &config, sink.clone(), Arc::clone(&resampler), channels, err_callback,
         ),
         SampleFormat::I8 => build_stream::<i8>(
-            device, &config, Arc::clone(&samples), Arc::clone(&peak_level), channels, err_callback,
+            device, &config, sink.clone(), Arc::clone(&resampler), channels, err_callback,
         ),
         SampleFormat::U8 => build_stream::<u8>(
-            device, &config, Arc::clone(&samples), Arc::clone(&peak_level), channels, err_callback,
+            device, &config, sink.clone(), Arc::clone(&resampler), channels, err_callback,
         ),
         SampleFormat::I32 => build_stream::<i32>(
-            device, &config, Arc::clone(&samples), Arc::clone(&peak_level), channels, err_callback,
+            device, &config, sink.clone(), Arc::clone(&resampler), channels, err_callback,
         ),
         SampleFormat::U32 => build_stream::<u32>(
-            device, &config, Arc::clone(&samples), Arc::clone(&peak_level), channels, err_callback,
+            device, &config, sink.clone(), Arc::clone(&resampler), channels, err_callback,
         ),
         SampleFormat::I64 => build_stream::<i64>(
-            device, &config, Arc::clone(&samples), Arc::clone(&peak_level), channels, err_callback,
+            device, &config, sink.clone(), Arc::clone(&resampler), channels, err_callback,
         ),
         SampleFormat::U64 => build_stream::<u64>(
-            device, &config, Arc::clone(&samples), Arc::clone(&peak_level), channels, err_callback,
+            device, &config, sink.clone(), Arc::clone(&resampler), channels, err_callback,
         ),
         SampleFormat::F64 => build_stream::<f64>(
-            device, &config, Arc::clone(&samples), Arc::clone(&peak_level), channels, err_callback,
+            device, &config, sink.clone(), Arc::clone(&resampler), channels, err_callback,
         ),
         _ => {
             set_recording_error(&recording_error, format!("Unsupported sample format: {:?}", sample_format));
@@ -402,19 +481,23 @@ fn run_recording_thread(device: &cpal::Device, params: RecordingThreadParams) {
 
     // Stream is dropped here, stopping recording
     drop(stream);
+
+    let tail = resampler.lock().unwrap().flush();
+    sink.samples.lock().unwrap().extend(tail);
 }
 
 fn build_stream<T: cpal::Sample + cpal::SizedSample + Send + 'static>(
     device: &cpal::Device,
     config: &StreamConfig,
-    samples: Arc<Mutex<Vec<f32>>>,
-    peak_level: Arc<Mutex<f32>>,
+    sink: CaptureSink,
+    resampler: Arc<Mutex<StreamingResampler>>,
     channels: usize,
     err_callback: impl FnMut(cpal::StreamError) + Send + 'static,
 ) -> Result<cpal::Stream, AudioError>
 where
     f32: cpal::FromSample<T>,
 {
+    let mut vad = VoiceActivity::new(config.sample_rate.0);
     let stream = device
         .build_input_stream(
             config,
@@ -432,11 +515,20 @@ where
                     peak = peak.max(sample.abs());
                 }
 
-                if let Ok(mut level) = peak_level.lock() {
+                if let Ok(mut level) = sink.peak_level.lock() {
                     *level = peak;
                 }
-                if let Ok(mut buf) = samples.lock() {
-                    buf.extend_from_slice(&mono_samples);
+                if vad.push(&mono_samples)
+                    && let Ok(mut last) = sink.last_speech.lock()
+                {
+                    *last = Some(Instant::now());
+                }
+                let resampled = match resampler.lock() {
+                    Ok(mut resampler) => resampler.process(&mono_samples),
+                    Err(_) => return,
+                };
+                if let Ok(mut buf) = sink.samples.lock() {
+                    buf.extend_from_slice(&resampled);
                 }
             },
             err_callback,
@@ -447,37 +539,128 @@ where
     Ok(stream)
 }
 
-/// Simple linear resampling from source rate to target rate
-fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
-    if from_rate == to_rate {
-        return samples.to_vec();
+/// RMS energy and zero-crossing rate (crossings per sample) of one frame.
+fn frame_stats(frame: &[f32]) -> (f32, f32) {
+    if frame.is_empty() {
+        return (0.0, 0.0);
     }
+    let energy = frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32;
+    let crossings = frame
+        .windows(2)
+        .filter(|w| (w[0] >= 0.0) != (w[1] >= 0.0))
+        .count();
+    (energy.sqrt(), crossings as f32 / frame.len() as f32)
+}
 
-    let ratio = from_rate as f64 / to_rate as f64;
-    let output_len = (samples.len() as f64 / ratio) as usize;
-    let mut output = Vec::with_capacity(output_len);
+/// Energy + zero-crossing speech decision for one frame against a noise floor.
+fn is_speech_frame(rms: f32, zcr: f32, noise_floor: f32) -> bool {
+    rms >= VAD_MIN_RMS.max(noise_floor * VAD_NOISE_FACTOR) && zcr <= VAD_MAX_ZCR
+}
 
-    for i in 0..output_len {
-        let src_idx = i as f64 * ratio;
-        let idx = src_idx as usize;
-        let frac = src_idx - idx as f64;
+fn vad_frame_len(sample_rate: u32) -> usize {
+    (sample_rate * VAD_FRAME_MS / 1000).max(1) as usize
+}
 
-        let sample = if idx + 1 < samples.len() {
-            samples[idx] as f64 * (1.0 - frac) + samples[idx + 1] as f64 * frac
-        } else if idx < samples.len() {
-            samples[idx] as f64
+/// Sample range of `samples` that contains speech, padded by [`VAD_PAD_MS`]
+/// on both sides. Returns `None` when no run of speech frames at least
+/// [`VAD_MIN_SPEECH_MS`] long is found.
+///
+/// The noise floor is the 10th percentile of frame energies, so steady
+/// background noise (fans, hum) raises the threshold instead of counting as
+/// speech. It is capped at [`VAD_MAX_NOISE_FLOOR`] so clips without any pause
+/// still register.
+pub fn speech_bounds(samples: &[f32], sample_rate: u32) -> Option<Range<usize>> {
+    let frame_len = vad_frame_len(sample_rate);
+    let stats: Vec<(f32, f32)> = samples.chunks(frame_len).map(frame_stats).collect();
+    if stats.is_empty() {
+        return None;
+    }
+
+    let mut energies: Vec<f32> = stats.iter().map(|&(rms, _)| rms).collect();
+    energies.sort_by(f32::total_cmp);
+    let noise_floor = energies[energies.len() / 10].min(VAD_MAX_NOISE_FLOOR);
+
+    let min_run = (VAD_MIN_SPEECH_MS / VAD_FRAME_MS).max(1) as usize;
+    let mut first = None;
+    let mut last = None;
+    let mut run = 0;
+    for (i, &(rms, zcr)) in stats.iter().enumerate() {
+        if is_speech_frame(rms, zcr, noise_floor) {
+            run += 1;
+            if run >= min_run {
+                first.get_or_insert(i + 1 - run);
+                last = Some(i);
+            }
         } else {
-            0.0
-        };
+            run = 0;
+        }
+    }
+
+    let pad = (sample_rate * VAD_PAD_MS / 1000) as usize;
+    let start = (first? * frame_len).saturating_sub(pad);
+    let end = ((last? + 1) * frame_len + pad).min(samples.len());
+    Some(start..end)
+}
+
+/// Whether no frame of `samples` reaches the absolute silence level.
+fn is_silent(samples: &[f32], sample_rate: u32) -> bool {
+    samples
+        .chunks(vad_frame_len(sample_rate))
+        .all(|frame| frame_stats(frame).0 < VAD_MIN_RMS)
+}
+
+/// Streaming counterpart of [`speech_bounds`] used while recording, where the
+/// whole clip isn't available: the noise floor follows quiet frames quickly
+/// and loud frames slowly.
+struct VoiceActivity {
+    frame_len: usize,
+    frame: Vec<f32>,
+    noise_floor: f32,
+    run: usize,
+    min_run: usize,
+}
 
-        output.push(sample as f32);
+impl VoiceActivity {
+    fn new(sample_rate: u32) -> Self {
+        let frame_len = vad_frame_len(sample_rate);
+        Self {
+            frame_len,
+            frame: Vec::with_capacity(frame_len),
+            noise_floor: VAD_MIN_RMS / VAD_NOISE_FACTOR,
+            run: 0,
+            min_run: (VAD_MIN_SPEECH_MS / VAD_FRAME_MS).max(1) as usize,
+        }
     }
 
-    output
+    /// Feed mono samples; returns whether speech was heard in them.
+    fn push(&mut self, samples: &[f32]) -> bool {
+        let mut heard = false;
+        for &sample in samples {
+            self.frame.push(sample);
+            if self.frame.len() < self.frame_len {
+                continue;
+            }
+
+            let (rms, zcr) = frame_stats(&self.frame);
+            self.frame.clear();
+
+            if is_speech_frame(rms, zcr, self.noise_floor) {
+                self.run += 1;
+                heard |= self.run >= self.min_run;
+            } else {
+                self.run = 0;
+            }
+
+            let rate = if rms < self.noise_floor { 0.2 } else { 0.005 };
+            self.noise_floor += (rms - self.noise_floor) * rate;
+            self.noise_floor = self.noise_floor.min(VAD_MAX_NOISE_FLOOR);
+        }
+        heard
+    }
 }
 
 /// Encode f32 samples as a WAV byte buffer (16-bit PCM, 16kHz, mono)
-fn encode_wav(samples: &[f32], sample_rate: u32) -> Result<Vec<u8>, AudioError> {
+pub fn encode_wav(samples: &[f32], sample_rate: u32) -> Result<Vec<u8>, AudioError> {
     let spec = WavSpec {
         channels: 1,
         sample_rate,
@@ -506,31 +689,97 @@ fn encode_wav(samples: &[f32], sample_rate: u32) -> Result<Vec<u8>, AudioError>
     Ok(cursor.into_inner())
 }
 
+/// Length of a WAV clip in milliseconds, `None` if it isn't one.
+pub fn wav_duration_ms(wav: &[u8]) -> Option<i64> {
+    let reader = hound::WavReader::new(Cursor::new(wav)).ok()?;
+    let sample_rate = reader.spec().sample_rate as i64;
+    (sample_rate > 0).then(|| reader.duration() as i64 * 1000 / sample_rate)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn test_resample_same_rate() {
-        let input = vec![0.0, 0.5, 1.0, -1.0];
-        let output = resample(&input, 16000, 16000);
-        assert_eq!(input, output);
+    fn test_encode_wav_produces_valid_header() {
+        let samples = vec![0.0f32; 16000];
+        let wav = encode_wav(&samples, 16000).unwrap();
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(&wav[8..12], b"WAVE");
     }
 
     #[test]
-    fn test_resample_downsample() {
-        // 48kHz to 16kHz should produce ~1/3 the samples
-        let input: Vec<f32> = (0..4800).map(|i| (i as f32 / 4800.0).sin()).collect();
-        let output = resample(&input, 48000, 16000);
-        assert_eq!(output.len(), 1600);
+    fn test_wav_duration_ms() {
+        let wav = encode_wav(&vec![0.0f32; 16000], 16000).unwrap();
+        assert_eq!(wav_duration_ms(&wav), Some(1000));
+        assert_eq!(wav_duration_ms(b"RIFF"), None);
+    }
+
+    /// 1 s of a 220 Hz tone between `lead` and `tail` seconds of low noise.
+    fn speech_clip(lead: f32, tail: f32) -> Vec<f32> {
+        let sr = 16000.0;
+        let noise = |i: usize| ((i * 7919 % 200) as f32 / 200.0 - 0.5) * 0.004;
+        let lead_len = (lead * sr) as usize;
+        let tone_len = sr as usize;
+        let tail_len = (tail * sr) as usize;
+        (0..lead_len + tone_len + tail_len)
+            .map(|i| {
+                let t = i as f32 / sr;
+                if i >= lead_len && i < lead_len + tone_len {
+                    0.3 * (2.0 * std::f32::consts::PI * 220.0 * t).sin()
+                } else {
+                    noise(i)
+                }
+            })
+            .collect()
     }
 
     #[test]
-    fn test_encode_wav_produces_valid_header() {
-        let samples = vec![0.0f32; 16000];
-        let wav = encode_wav(&samples, 16000).unwrap();
-        assert_eq!(&wav[0..4], b"RIFF");
-        assert_eq!(&wav[8..12], b"WAVE");
+    fn test_speech_bounds_trims_leading_and_trailing_silence() {
+        let clip = speech_clip(2.0, 3.0);
+        let bounds = speech_bounds(&clip, 16000).unwrap();
+        let pad = (16000 * VAD_PAD_MS / 1000) as usize;
+        let frame = vad_frame_len(16000);
+
+        assert!(bounds.start.abs_diff(32000 - pad) <= frame);
+        assert!(bounds.end.abs_diff(48000 + pad) <= frame);
+    }
+
+    #[test]
+    fn test_speech_bounds_rejects_silence_and_clicks() {
+        assert!(speech_bounds(&[], 16000).is_none());
+        assert!(speech_bounds(&vec![0.0; 16000], 16000).is_none());
+
+        // A single loud 30 ms click is shorter than the minimum speech run
+        let mut clip = vec![0.0f32; 16000];
+        for (i, s) in clip[8000..8480].iter_mut().enumerate() {
+            *s = if i % 40 < 20 { 0.5 } else { -0.5 };
+        }
+        assert!(speech_bounds(&clip, 16000).is_none());
+    }
+
+    #[test]
+    fn test_speech_bounds_keeps_clip_that_is_all_speech() {
+        let clip = speech_clip(0.0, 0.0);
+        assert_eq!(speech_bounds(&clip, 16000), Some(0..clip.len()));
+        assert!(!is_silent(&clip, 16000));
+    }
+
+    #[test]
+    fn test_is_silent_only_below_the_silence_level() {
+        assert!(is_silent(&speech_clip(1.0, 1.0)[..16000], 16000));
+        assert!(!is_silent(&speech_clip(1.0, 1.0), 16000));
+    }
+
+    #[test]
+    fn test_voice_activity_detects_speech_while_streaming() {
+        let clip = speech_clip(1.0, 1.0);
+        let mut vad = VoiceActivity::new(16000);
+
+        let heard: Vec<bool> = clip.chunks(1600).map(|c| vad.push(c)).collect();
+        assert!(heard[..10].iter().all(|&h| !h));
+        assert!(heard[10..20].iter().any(|&h| h));
+        assert!(heard[21..].iter().all(|&h| !h));
     }
 
     #[test]
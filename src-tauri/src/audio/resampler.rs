@@ -0,0 +1,341 @@
+//! Band-limited sample-rate conversion.
+//!
+//! A polyphase windowed-sinc (Kaiser) resampler for rational ratios. The
+//! low-pass cutoff sits just below the Nyquist frequency of the lower of the
+//! two rates, so 44.1/48 kHz input doesn't alias into the 0-8 kHz band whisper
+//! listens to at 16 kHz.
+//!
+//! [`StreamingResampler`] converts chunk by chunk inside the capture callback;
+//! splitting the input differently never changes the output.
+
+use serde::Deserialize;
+
+/// Filter quality presets, trading CPU for stop-band attenuation and a
+/// narrower transition band.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResampleQuality {
+    /// 8 zero crossings per side, ~60 dB stop-band.
+    Fast,
+    /// 16 zero crossings per side, ~85 dB stop-band.
+    #[default]
+    Balanced,
+    /// 32 zero crossings per side, ~100 dB stop-band.
+    High,
+}
+
+impl ResampleQuality {
+    /// (zero crossings per side, Kaiser beta, cutoff as a fraction of Nyquist)
+    fn params(self) -> (usize, f64, f64) {
+        match self {
+            ResampleQuality::Fast => (8, 6.0, 0.85),
+            ResampleQuality::Balanced => (16, 8.6, 0.90),
+            ResampleQuality::High => (32, 10.0, 0.94),
+        }
+    }
+}
+
+/// Chunk-by-chunk resampler. Feed input with [`process`](Self::process) and
+/// call [`flush`](Self::flush) once at the end of the stream to get the tail
+/// still held back by the filter.
+pub struct StreamingResampler {
+    /// Upsampling factor L of the reduced ratio L/M.
+    up: u64,
+    /// Downsampling factor M of the reduced ratio L/M.
+    down: u64,
+    /// Taps on each side of the output instant.
+    half: usize,
+    /// `up` phases of `2 * half` taps each, flattened.
+    table: Vec<f32>,
+    /// Input not yet consumed, starting at absolute input index `buffer_start`.
+    buffer: Vec<f32>,
+    buffer_start: u64,
+    /// Total input samples received.
+    consumed: u64,
+    /// Index of the next output sample.
+    next_output: u64,
+    passthrough: bool,
+}
+
+impl StreamingResampler {
+    pub fn new(from_rate: u32, to_rate: u32, quality: ResampleQuality) -> Self {
+        let divisor = gcd(from_rate as u64, to_rate as u64).max(1);
+        let up = to_rate as u64 / divisor;
+        let down = from_rate as u64 / divisor;
+
+        let (zero_crossings, beta, rolloff) = quality.params();
+        // Cutoff relative to the input Nyquist frequency
+        let cutoff = (to_rate as f64 / from_rate as f64).min(1.0) * rolloff;
+        let half = (zero_crossings as f64 / cutoff).ceil() as usize;
+        let table = build_table(up as usize, half, cutoff, beta);
+
+        Self {
+            up,
+            down,
+            half,
+            table,
+            buffer: Vec::new(),
+            buffer_start: 0,
+            consumed: 0,
+            next_output: 0,
+            passthrough: from_rate == to_rate,
+        }
+    }
+
+    /// Feed the next chunk of input and return every output sample whose
+    /// filter window is now complete.
+    pub fn process(&mut self, input: &[f32]) -> Vec<f32> {
+        if self.passthrough {
+            return input.to_vec();
+        }
+        self.buffer.extend_from_slice(input);
+        self.consumed += input.len() as u64;
+        self.drain(self.consumed)
+    }
+
+    /// Emit the remaining output, treating the input after the end as silence.
+    /// The total output length is `floor(input_len * to_rate / from_rate)`.
+    pub fn flush(&mut self) -> Vec<f32> {
+        if self.passthrough {
+            return Vec::new();
+        }
+        let total = self.consumed * self.up / self.down;
+        let mut output = Vec::new();
+        while self.next_output < total {
+            output.push(self.compute(self.next_output));
+            self.next_output += 1;
+        }
+        output
+    }
+
+    /// Compute outputs whose last tap is below `available`.
+    fn drain(&mut self, available: u64) -> Vec<f32> {
+        let mut output = Vec::new();
+        loop {
+            let base = self.next_output * self.down / self.up;
+            if base + self.half as u64 >= available {
+                break;
+            }
+            output.push(self.compute(self.next_output));
+            self.next_output += 1;
+        }
+
+        // Drop input no longer reachable by any future output's window
+        let base = self.next_output * self.down / self.up;
+        let keep_from = (base + 1).saturating_sub(self.half as u64);
+        if keep_from > self.buffer_start {
+            let drop = ((keep_from - self.buffer_start) as usize).min(self.buffer.len());
+            self.buffer.drain(..drop);
+            self.buffer_start += drop as u64;
+        }
+        output
+    }
+
+    fn compute(&self, k: u64) -> f32 {
+        let position = k * self.down;
+        let base = (position / self.up) as i64;
+        let phase = (position % self.up) as usize;
+        let taps = 2 * self.half;
+        let coefficients = &self.table[phase * taps..(phase + 1) * taps];
+
+        let first = base - self.half as i64 + 1;
+        let mut acc = 0.0f32;
+        for (i, &c) in coefficients.iter().enumerate() {
+            let index = first + i as i64 - self.buffer_start as i64;
+            if index >= 0
+                && let Some(&sample) = self.buffer.get(index as usize)
+            {
+                acc += sample * c;
+            }
+        }
+        acc
+    }
+}
+
+/// Kaiser-windowed sinc taps for every phase, each phase normalised to unit DC gain.
+fn build_table(phases: usize, half: usize, cutoff: f64, beta: f64) -> Vec<f32> {
+    let taps = 2 * half;
+    let i0_beta = bessel_i0(beta);
+    let mut table = Vec::with_capacity(phases * taps);
+
+    for phase in 0..phases {
+        let frac = phase as f64 / phases as f64;
+        let row: Vec<f64> = (0..taps)
+            .map(|i| {
+                // Distance from the output instant to input tap i
+                let t = frac + (half as f64 - 1.0 - i as f64);
+                let x = t / half as f64;
+                if x.abs() >= 1.0 {
+                    return 0.0;
+                }
+                let window = bessel_i0(beta * (1.0 - x * x).sqrt()) / i0_beta;
+                cutoff * sinc(cutoff * t) * window
+            })
+            .collect();
+        let sum: f64 = row.iter().sum();
+        table.extend(row.iter().map(|&c| (c / sum) as f32));
+    }
+    table
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-12 {
+        1.0
+    } else {
+        let px = std::f64::consts::PI * x;
+        px.sin() / px
+    }
+}
+
+/// Zeroth-order modified Bessel function of the first kind (power series).
+fn bessel_i0(x: f64) -> f64 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let half_x = x / 2.0;
+    for k in 1..64 {
+        term *= (half_x / k as f64).powi(2);
+        sum += term;
+        if term < sum * 1e-12 {
+            break;
+        }
+    }
+    sum
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    fn tone(freq: f64, rate: u32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| (2.0 * PI * freq * i as f64 / rate as f64).sin() as f32 * 0.5)
+            .collect()
+    }
+
+    /// Power of `freq` in `samples` (Goertzel), skipping the filter warm-up.
+    fn power_at(samples: &[f32], rate: u32, freq: f64) -> f64 {
+        let samples = &samples[samples.len() / 10..samples.len() * 9 / 10];
+        let coeff = 2.0 * (2.0 * PI * freq / rate as f64).cos();
+        let (mut s1, mut s2) = (0.0f64, 0.0f64);
+        for &x in samples {
+            let s0 = x as f64 + coeff * s1 - s2;
+            s2 = s1;
+            s1 = s0;
+        }
+        (s1 * s1 + s2 * s2 - coeff * s1 * s2) / (samples.len() as f64).powi(2)
+    }
+
+    /// Resample a whole clip in one go.
+    fn resample(samples: &[f32], from_rate: u32, to_rate: u32, quality: ResampleQuality) -> Vec<f32> {
+        let mut resampler = StreamingResampler::new(from_rate, to_rate, quality);
+        let mut output = resampler.process(samples);
+        output.extend(resampler.flush());
+        output
+    }
+
+    fn energy(samples: &[f32]) -> f64 {
+        let inner = &samples[samples.len() / 10..samples.len() * 9 / 10];
+        inner.iter().map(|&x| (x as f64).powi(2)).sum::<f64>() / inner.len() as f64
+    }
+
+    #[test]
+    fn test_resample_same_rate() {
+        let input = vec![0.0, 0.5, 1.0, -1.0];
+        let output = resample(&input, 16000, 16000, ResampleQuality::default());
+        assert_eq!(input, output);
+    }
+
+    #[test]
+    fn test_resample_downsample() {
+        // 48kHz to 16kHz should produce ~1/3 the samples
+        let input: Vec<f32> = (0..4800).map(|i| (i as f32 / 4800.0).sin()).collect();
+        let output = resample(&input, 48000, 16000, ResampleQuality::default());
+        assert_eq!(output.len(), 1600);
+
+        let output = resample(&vec![0.0; 44100], 44100, 16000, ResampleQuality::default());
+        assert_eq!(output.len(), 16000);
+    }
+
+    #[test]
+    fn test_passband_tone_is_preserved() {
+        for quality in [ResampleQuality::Fast, ResampleQuality::Balanced, ResampleQuality::High] {
+            let input = tone(1000.0, 48000, 48000);
+            let output = resample(&input, 48000, 16000, quality);
+            let ratio = power_at(&output, 16000, 1000.0) / power_at(&input, 48000, 1000.0);
+            assert!((ratio - 1.0).abs() < 0.02, "{:?}: passband gain {}", quality, ratio);
+        }
+    }
+
+    #[test]
+    fn test_energy_above_8khz_does_not_alias() {
+        // Content only above the 16 kHz Nyquist: 9-20 kHz at 48k and 44.1k input
+        for rate in [48000, 44100] {
+            let mut input = vec![0.0f32; rate as usize];
+            for freq in [9000.0, 12000.0, 15000.0, 20000.0] {
+                for (x, t) in input.iter_mut().zip(tone(freq, rate, rate as usize)) {
+                    *x += t;
+                }
+            }
+            let before = energy(&input);
+
+            let linear = resample_linear(&input, rate, 16000);
+            let filtered = resample(&input, rate, 16000, ResampleQuality::Balanced);
+
+            // Linear interpolation folds most of it back into the speech band
+            assert!(energy(&linear) > before * 0.05);
+            // The windowed-sinc filter removes it (< -60 dB)
+            assert!(energy(&filtered) < before * 1e-6, "{} Hz: {}", rate, energy(&filtered) / before);
+        }
+    }
+
+    #[test]
+    fn test_aliased_tone_is_attenuated_per_preset() {
+        // 12 kHz at 48 kHz would alias to 4 kHz at 16 kHz
+        let input = tone(12000.0, 48000, 48000);
+        let reference = power_at(&tone(4000.0, 16000, 16000), 16000, 4000.0);
+        for (quality, max_db) in [
+            (ResampleQuality::Fast, -55.0),
+            (ResampleQuality::Balanced, -80.0),
+            (ResampleQuality::High, -95.0),
+        ] {
+            let output = resample(&input, 48000, 16000, quality);
+            let db = 10.0 * (power_at(&output, 16000, 4000.0) / reference).log10();
+            assert!(db < max_db, "{:?}: alias at {:.1} dB", quality, db);
+        }
+    }
+
+    #[test]
+    fn test_streaming_matches_one_shot() {
+        let input: Vec<f32> = (0..10000).map(|i| ((i * 37 % 101) as f32 / 101.0) - 0.5).collect();
+        let expected = resample(&input, 44100, 16000, ResampleQuality::High);
+
+        let mut resampler = StreamingResampler::new(44100, 16000, ResampleQuality::High);
+        let mut output = Vec::new();
+        for chunk in input.chunks(441).flat_map(|c| c.chunks(97)) {
+            output.extend(resampler.process(chunk));
+        }
+        output.extend(resampler.flush());
+
+        assert_eq!(output, expected);
+    }
+
+    /// The previous linear-interpolation resampler, kept as the aliasing baseline.
+    fn resample_linear(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+        let ratio = from_rate as f64 / to_rate as f64;
+        let output_len = (samples.len() as f64 / ratio) as usize;
+        (0..output_len)
+            .map(|i| {
+                let src = i as f64 * ratio;
+                let idx = src as usize;
+                let frac = src - idx as f64;
+                let next = samples.get(idx + 1).copied().unwrap_or(samples[idx]);
+                (samples[idx] as f64 * (1.0 - frac) + next as f64 * frac) as f32
+            })
+            .collect()
+    }
+}
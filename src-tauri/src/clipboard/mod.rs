@@ -20,13 +20,50 @@ pub fn paste_text(text: &str) -> Result<()> {
         Ok(())
     }
 
-    #[cfg(not(target_os = "windows"))]
+    #[cfg(target_os = "linux")]
+    {
+        linux_clipboard::set_text(text)?;
+        std::thread::sleep(std::time::Duration::from_millis(50));
+
+        let is_terminal = linux_terminal::is_foreground_terminal();
+        if is_terminal {
+            linux_paste::send_ctrl_shift_v()?;
+        } else {
+            linux_paste::send_ctrl_v()?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
     {
         let _ = text;
         anyhow::bail!("Clipboard paste not yet implemented for this platform");
     }
 }
 
+/// Type text into the focused application with synthetic key events. Unlike
+/// [`paste_text`] this leaves the clipboard alone, so it can be called for each
+/// piece of a streamed response without racing the paste or clobbering what
+/// the user copied.
+pub fn type_text(text: &str) -> Result<()> {
+    #[cfg(target_os = "windows")]
+    {
+        windows_paste::type_text(text)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        linux_paste::type_text(text)
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        let _ = text;
+        anyhow::bail!("Typing not yet implemented for this platform");
+    }
+}
+
 /// Read the current clipboard text.
 pub fn read_clipboard() -> Result<String> {
     #[cfg(target_os = "windows")]
@@ -34,12 +71,36 @@ pub fn read_clipboard() -> Result<String> {
         windows_clipboard::get_text()
     }
 
-    #[cfg(not(target_os = "windows"))]
+    #[cfg(target_os = "linux")]
+    {
+        linux_clipboard::get_text()
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
     {
         anyhow::bail!("Clipboard read not yet implemented for this platform");
     }
 }
 
+/// Name of the application with keyboard focus (`Code`, `firefox`, ...),
+/// where the platform lets us see it.
+pub fn foreground_app() -> Option<String> {
+    #[cfg(target_os = "windows")]
+    {
+        windows_terminal::foreground_app()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        linux_terminal::foreground_app()
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        None
+    }
+}
+
 #[cfg(target_os = "windows")]
 mod windows_clipboard {
     use anyhow::Result;
@@ -114,7 +175,13 @@ mod windows_clipboard {
 
 #[cfg(target_os = "windows")]
 mod windows_terminal {
-    use windows::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+    use std::path::Path;
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{
+        OpenProcess, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION, QueryFullProcessImageNameW,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+    use windows::core::PWSTR;
 
     /// Known terminal window class names on Windows.
     const TERMINAL_CLASSES: &[&str] = &[
@@ -152,6 +219,32 @@ mod windows_terminal {
                 .any(|tc| class_str.eq_ignore_ascii_case(tc))
         }
     }
+
+    /// Executable name, without extension, of the foreground window's process.
+    pub fn foreground_app() -> Option<String> {
+        unsafe {
+            let hwnd = GetForegroundWindow();
+            if hwnd.is_invalid() {
+                return None;
+            }
+
+            let mut pid = 0u32;
+            GetWindowThreadProcessId(hwnd, Some(&mut pid));
+            if pid == 0 {
+                return None;
+            }
+
+            let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+            let mut path = [0u16; 1024];
+            let mut len = path.len() as u32;
+            let queried = QueryFullProcessImageNameW(process, PROCESS_NAME_WIN32, PWSTR(path.as_mut_ptr()), &mut len);
+            let _ = CloseHandle(process);
+            queried.ok()?;
+
+            let path = String::from_utf16_lossy(&path[..len as usize]);
+            Path::new(&path).file_stem().map(|stem| stem.to_string_lossy().into_owned())
+        }
+    }
 }
 
 #[cfg(target_os = "windows")]
@@ -159,9 +252,10 @@ mod windows_paste {
     use anyhow::Result;
     use windows::Win32::UI::Input::KeyboardAndMouse::{
         SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS, KEYEVENTF_KEYUP,
-        VIRTUAL_KEY,
+        KEYEVENTF_UNICODE, VIRTUAL_KEY,
     };
 
+    const VK_RETURN: u16 = 0x0D;
     const VK_CONTROL: u16 = 0x11;
     const VK_SHIFT: u16 = 0x10;
     const VK_V: u16 = 0x56;
@@ -188,6 +282,28 @@ mod windows_paste {
         send_inputs(&inputs)
     }
 
+    /// Send each UTF-16 unit as a unicode key event; newlines press Enter,
+    /// which apps handle more reliably than a typed line feed.
+    pub fn type_text(text: &str) -> Result<()> {
+        let mut inputs = Vec::new();
+        for c in text.chars().filter(|&c| c != '\r') {
+            if c == '\n' {
+                inputs.push(make_key_input(VK_RETURN, false));
+                inputs.push(make_key_input(VK_RETURN, true));
+                continue;
+            }
+            let mut units = [0u16; 2];
+            for &unit in c.encode_utf16(&mut units).iter() {
+                inputs.push(make_unicode_input(unit, false));
+                inputs.push(make_unicode_input(unit, true));
+            }
+        }
+        if inputs.is_empty() {
+            return Ok(());
+        }
+        send_inputs(&inputs)
+    }
+
     fn send_inputs(inputs: &[INPUT]) -> Result<()> {
         unsafe {
             let sent = SendInput(inputs, std::mem::size_of::<INPUT>() as i32);
@@ -218,4 +334,431 @@ mod windows_paste {
             },
         }
     }
+
+    fn make_unicode_input(unit: u16, key_up: bool) -> INPUT {
+        let flags = if key_up {
+            KEYEVENTF_UNICODE | KEYEVENTF_KEYUP
+        } else {
+            KEYEVENTF_UNICODE
+        };
+
+        INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: INPUT_0 {
+                ki: KEYBDINPUT {
+                    wVk: VIRTUAL_KEY(0),
+                    wScan: unit,
+                    dwFlags: flags,
+                    time: 0,
+                    dwExtraInfo: 0,
+                },
+            },
+        }
+    }
+}
+
+/// Whether the session is running under a Wayland compositor (as opposed to X11).
+#[cfg(target_os = "linux")]
+fn is_wayland() -> bool {
+    std::env::var_os("WAYLAND_DISPLAY").is_some()
+        || std::env::var("XDG_SESSION_TYPE").is_ok_and(|t| t.eq_ignore_ascii_case("wayland"))
+}
+
+#[cfg(target_os = "linux")]
+mod linux_clipboard {
+    use anyhow::Result;
+    use std::io::{ErrorKind, Write};
+    use std::process::{Command, Stdio};
+
+    // Owning an X11/Wayland selection requires a process that stays alive to serve
+    // paste requests, so we delegate to the standard clipboard utilities, which fork
+    // into the background and keep serving until another client takes the selection.
+    type Tool = (&'static str, &'static [&'static str]);
+
+    const WAYLAND_COPY: &[Tool] = &[("wl-copy", &["--type", "text/plain;charset=utf-8"])];
+    const X11_COPY: &[Tool] = &[
+        ("xclip", &["-selection", "clipboard", "-in"]),
+        ("xsel", &["--clipboard", "--input"]),
+    ];
+
+    const WAYLAND_PASTE: &[Tool] = &[("wl-paste", &["--no-newline"])];
+    const X11_PASTE: &[Tool] = &[
+        ("xclip", &["-selection", "clipboard", "-out"]),
+        ("xsel", &["--clipboard", "--output"]),
+    ];
+
+    /// Tools to try in order. XWayland tools are kept as a fallback under Wayland.
+    fn tools(wayland: &[Tool], x11: &[Tool]) -> Vec<Tool> {
+        if super::is_wayland() {
+            wayland.iter().chain(x11).copied().collect()
+        } else {
+            x11.to_vec()
+        }
+    }
+
+    pub fn set_text(text: &str) -> Result<()> {
+        for (program, args) in tools(WAYLAND_COPY, X11_COPY) {
+            // stdout/stderr are not piped: the forked server inherits them and
+            // would otherwise keep the pipe open until the selection is replaced.
+            let child = Command::new(program)
+                .args(args)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn();
+
+            let mut child = match child {
+                Ok(c) => c,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => anyhow::bail!("Failed to run {}: {}", program, e),
+            };
+
+            if let Some(mut stdin) = child.stdin.take() {
+                stdin.write_all(text.as_bytes())?;
+            }
+
+            let status = child.wait()?;
+            if status.success() {
+                return Ok(());
+            }
+            log::warn!("[Whisperi] {} exited with {}, trying next clipboard tool", program, status);
+        }
+
+        anyhow::bail!(
+            "No working clipboard tool found. Install wl-clipboard (Wayland) or xclip/xsel (X11)"
+        );
+    }
+
+    pub fn get_text() -> Result<String> {
+        for (program, args) in tools(WAYLAND_PASTE, X11_PASTE) {
+            let output = match Command::new(program).args(args).stdin(Stdio::null()).output() {
+                Ok(o) => o,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => anyhow::bail!("Failed to run {}: {}", program, e),
+            };
+
+            // A non-zero exit with a working tool means the clipboard holds no text.
+            if !output.status.success() {
+                return Ok(String::new());
+            }
+            return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+        }
+
+        anyhow::bail!(
+            "No working clipboard tool found. Install wl-clipboard (Wayland) or xclip/xsel (X11)"
+        );
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux_terminal {
+    use std::process::Command;
+    use x11rb::connection::Connection;
+    use x11rb::properties::WmClass;
+    use x11rb::protocol::xproto::{AtomEnum, ConnectionExt as _};
+
+    /// Known terminal WM_CLASS / Wayland app_id values on Linux.
+    const TERMINAL_CLASSES: &[&str] = &[
+        "xterm",                        // xterm
+        "uxterm",                       // xterm (UTF-8 wrapper)
+        "urxvt",                        // rxvt-unicode
+        "rxvt",                         // rxvt
+        "gnome-terminal-server",        // GNOME Terminal
+        "org.gnome.terminal",           // GNOME Terminal (Wayland app_id)
+        "org.gnome.console",            // GNOME Console
+        "kgx",                          // GNOME Console (older builds)
+        "org.gnome.ptyxis",             // Ptyxis
+        "konsole",                      // Konsole
+        "org.kde.konsole",              // Konsole (Wayland app_id)
+        "yakuake",                      // Yakuake
+        "kitty",                        // kitty
+        "alacritty",                    // Alacritty
+        "org.wezfurlong.wezterm",       // WezTerm
+        "foot",                         // foot
+        "footclient",                   // foot (client mode)
+        "com.mitchellh.ghostty",        // Ghostty
+        "tilix",                        // Tilix
+        "com.gexperts.tilix",           // Tilix (Wayland app_id)
+        "terminator",                   // Terminator
+        "xfce4-terminal",               // Xfce Terminal
+        "mate-terminal",                // MATE Terminal
+        "lxterminal",                   // LXTerminal
+        "qterminal",                    // QTerminal
+        "terminology",                  // Terminology
+        "st-256color",                  // suckless st
+        "sakura",                       // Sakura
+        "guake",                        // Guake
+        "tilda",                        // Tilda
+        "io.elementary.terminal",       // elementary Terminal
+        "com.raggesilver.blackbox",     // Black Box
+        "cool-retro-term",              // cool-retro-term
+        "hyper",                        // Hyper
+        "wezterm",                      // WezTerm (older class name)
+        "warp",                         // Warp
+    ];
+
+    pub fn is_foreground_terminal() -> bool {
+        let classes = if super::is_wayland() {
+            wayland_focused_app_id()
+                .map(|id| vec![id])
+                .or_else(x11_active_window_classes)
+        } else {
+            x11_active_window_classes()
+        };
+
+        classes.unwrap_or_default().iter().any(|class| is_terminal_class(class))
+    }
+
+    /// Whether a WM_CLASS or app id names a known terminal, ignoring case.
+    fn is_terminal_class(class: &str) -> bool {
+        TERMINAL_CLASSES.iter().any(|tc| class.eq_ignore_ascii_case(tc))
+    }
+
+    /// Wayland app id or X11 WM_CLASS class of the focused window.
+    pub fn foreground_app() -> Option<String> {
+        if super::is_wayland()
+            && let Some(app_id) = wayland_focused_app_id()
+        {
+            return Some(app_id);
+        }
+        x11_active_window_classes()?.pop().filter(|class| !class.is_empty())
+    }
+
+    /// Query WM_CLASS (instance and class) of the window named by `_NET_ACTIVE_WINDOW`.
+    /// Under Wayland this only sees XWayland clients.
+    fn x11_active_window_classes() -> Option<Vec<String>> {
+        let (conn, screen_num) = x11rb::connect(None).ok()?;
+        let root = conn.setup().roots[screen_num].root;
+
+        let active_atom = conn
+            .intern_atom(true, b"_NET_ACTIVE_WINDOW")
+            .ok()?
+            .reply()
+            .ok()?
+            .atom;
+        if active_atom == x11rb::NONE {
+            return None;
+        }
+
+        let window = conn
+            .get_property(false, root, active_atom, AtomEnum::WINDOW, 0, 1)
+            .ok()?
+            .reply()
+            .ok()?
+            .value32()?
+            .next()?;
+        if window == x11rb::NONE {
+            return None;
+        }
+
+        let wm_class = WmClass::get(&conn, window).ok()?.reply().ok()??;
+        Some(vec![
+            String::from_utf8_lossy(wm_class.instance()).into_owned(),
+            String::from_utf8_lossy(wm_class.class()).into_owned(),
+        ])
+    }
+
+    /// Wayland has no portable way to inspect the focused window, so ask the
+    /// compositors that expose it over IPC (Hyprland, Sway).
+    fn wayland_focused_app_id() -> Option<String> {
+        if std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() {
+            let json = command_json("hyprctl", &["activewindow", "-j"])?;
+            return json["class"].as_str().map(str::to_string);
+        }
+
+        if std::env::var_os("SWAYSOCK").is_some() {
+            let tree = command_json("swaymsg", &["-t", "get_tree", "-r"])?;
+            let node = find_focused_node(&tree)?;
+            return node["app_id"]
+                .as_str()
+                .or_else(|| node["window_properties"]["class"].as_str())
+                .map(str::to_string);
+        }
+
+        None
+    }
+
+    fn command_json(program: &str, args: &[&str]) -> Option<serde_json::Value> {
+        let output = Command::new(program).args(args).output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        serde_json::from_slice(&output.stdout).ok()
+    }
+
+    fn find_focused_node(node: &serde_json::Value) -> Option<&serde_json::Value> {
+        if node["focused"].as_bool() == Some(true) {
+            return Some(node);
+        }
+        ["nodes", "floating_nodes"]
+            .iter()
+            .filter_map(|key| node[*key].as_array())
+            .flatten()
+            .find_map(find_focused_node)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_terminal_classes_match_ignoring_case() {
+            // WM_CLASS class names are capitalized, Wayland app ids are not
+            assert!(is_terminal_class("Alacritty") && is_terminal_class("org.gnome.Ptyxis"));
+            assert!(is_terminal_class("org.wezfurlong.wezterm") && is_terminal_class("XTerm"));
+            assert!(!is_terminal_class("firefox") && !is_terminal_class("Code"));
+            // Whole names only: an editor named after a terminal isn't one
+            assert!(!is_terminal_class("kitty-themes") && !is_terminal_class(""));
+        }
+
+        #[test]
+        fn test_find_focused_node_walks_sway_tree() {
+            let tree = serde_json::json!({
+                "nodes": [{ "nodes": [{ "focused": false, "app_id": "firefox" }] }],
+                "floating_nodes": [{ "focused": true, "app_id": "foot" }]
+            });
+            assert_eq!(find_focused_node(&tree).unwrap()["app_id"], "foot");
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux_paste {
+    use anyhow::{Context, Result};
+    use std::io::ErrorKind;
+    use std::process::Command;
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{ConnectionExt as _, KEY_PRESS_EVENT, KEY_RELEASE_EVENT};
+    use x11rb::protocol::xtest::ConnectionExt as _;
+    use x11rb::wrapper::ConnectionExt as _;
+
+    // X11 keysyms
+    const XK_CONTROL_L: u32 = 0xffe3;
+    const XK_SHIFT_L: u32 = 0xffe1;
+    const XK_V: u32 = 0x0076;
+
+    // Linux evdev key codes (for ydotool)
+    const KEY_LEFTCTRL: u16 = 29;
+    const KEY_LEFTSHIFT: u16 = 42;
+    const KEY_V: u16 = 47;
+
+    pub fn send_ctrl_v() -> Result<()> {
+        send_paste_combo(false)
+    }
+
+    pub fn send_ctrl_shift_v() -> Result<()> {
+        send_paste_combo(true)
+    }
+
+    /// Type text with wtype or ydotool under Wayland, or xdotool, which also
+    /// reaches XWayland clients. XTEST alone can only press keys that are
+    /// already mapped, so arbitrary characters need one of these tools.
+    pub fn type_text(text: &str) -> Result<()> {
+        if text.is_empty() {
+            return Ok(());
+        }
+        let wayland: &[(&str, &[&str])] = &[("wtype", &["--"]), ("ydotool", &["type", "--"])];
+        let x11: &[(&str, &[&str])] = &[("xdotool", &["type", "--clearmodifiers", "--"])];
+        let tools = if super::is_wayland() { [wayland, x11].concat() } else { x11.to_vec() };
+
+        for (program, args) in tools {
+            match Command::new(program).args(args).arg(text).output() {
+                Ok(output) if output.status.success() => return Ok(()),
+                Ok(output) => log::warn!(
+                    "[Whisperi] {} failed: {}",
+                    program,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => log::warn!("[Whisperi] Failed to run {}: {}", program, e),
+            }
+        }
+
+        anyhow::bail!("No working typing tool found. Install xdotool, or wtype or ydotool on Wayland")
+    }
+
+    fn send_paste_combo(shift: bool) -> Result<()> {
+        if super::is_wayland() {
+            match send_wayland(shift) {
+                Ok(()) => return Ok(()),
+                // Fall through to XTEST, which still reaches XWayland clients
+                Err(e) => log::warn!("[Whisperi] Wayland key injection failed: {}", e),
+            }
+        }
+        send_xtest(shift)
+    }
+
+    /// Wayland forbids synthetic input from regular clients, so use wtype
+    /// (virtual-keyboard protocol) or ydotool (uinput) when installed.
+    fn send_wayland(shift: bool) -> Result<()> {
+        let mut wtype_args = vec!["-M", "ctrl"];
+        if shift {
+            wtype_args.extend(["-M", "shift", "v", "-m", "shift"]);
+        } else {
+            wtype_args.push("v");
+        }
+        wtype_args.extend(["-m", "ctrl"]);
+
+        let mut keys = vec![KEY_LEFTCTRL];
+        if shift {
+            keys.push(KEY_LEFTSHIFT);
+        }
+        keys.push(KEY_V);
+        let mut ydotool_args = vec!["key".to_string()];
+        ydotool_args.extend(keys.iter().map(|k| format!("{}:1", k)));
+        ydotool_args.extend(keys.iter().rev().map(|k| format!("{}:0", k)));
+        let ydotool_args: Vec<&str> = ydotool_args.iter().map(String::as_str).collect();
+
+        for (program, args) in [("wtype", wtype_args), ("ydotool", ydotool_args)] {
+            match Command::new(program).args(&args).output() {
+                Ok(output) if output.status.success() => return Ok(()),
+                Ok(output) => log::warn!(
+                    "[Whisperi] {} failed: {}",
+                    program,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => log::warn!("[Whisperi] Failed to run {}: {}", program, e),
+            }
+        }
+
+        anyhow::bail!("No working key injection tool found. Install wtype or ydotool")
+    }
+
+    fn send_xtest(shift: bool) -> Result<()> {
+        let (conn, screen_num) = x11rb::connect(None).context("Failed to connect to X server")?;
+        let root = conn.setup().roots[screen_num].root;
+
+        let min_keycode = conn.setup().min_keycode;
+        let max_keycode = conn.setup().max_keycode;
+        let mapping = conn
+            .get_keyboard_mapping(min_keycode, max_keycode - min_keycode + 1)?
+            .reply()?;
+        let per_keycode = (mapping.keysyms_per_keycode as usize).max(1);
+
+        let keycode_for = |keysym: u32| -> Result<u8> {
+            mapping
+                .keysyms
+                .chunks(per_keycode)
+                .position(|syms| syms.contains(&keysym))
+                .map(|i| min_keycode + i as u8)
+                .with_context(|| format!("No keycode mapped for keysym {:#x}", keysym))
+        };
+
+        let mut keycodes = vec![keycode_for(XK_CONTROL_L)?];
+        if shift {
+            keycodes.push(keycode_for(XK_SHIFT_L)?);
+        }
+        keycodes.push(keycode_for(XK_V)?);
+
+        for &keycode in &keycodes {
+            conn.xtest_fake_input(KEY_PRESS_EVENT, keycode, x11rb::CURRENT_TIME, root, 0, 0, 0)?;
+        }
+        for &keycode in keycodes.iter().rev() {
+            conn.xtest_fake_input(KEY_RELEASE_EVENT, keycode, x11rb::CURRENT_TIME, root, 0, 0, 0)?;
+        }
+        conn.sync()?;
+
+        Ok(())
+    }
 }
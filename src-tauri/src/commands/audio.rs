@@ -1,7 +1,9 @@
 use super::ResultExt;
 use crate::audio::{AudioDevice, AudioRecorder, RecordingState};
+use crate::pipeline::{self, DictationSettings};
 use serde::Serialize;
 use std::sync::atomic::Ordering;
+use std::time::Duration;
 use tauri::{AppHandle, Emitter, State};
 
 #[derive(Clone, Serialize)]
@@ -25,15 +27,30 @@ pub fn start_recording(
     state: State<'_, RecordingState>,
     device_id: Option<String>,
 ) -> Result<(), String> {
-    AudioRecorder::start(&state, device_id).str_err()?;
+    let settings = DictationSettings::load(&app).unwrap_or_else(|e| {
+        log::warn!("[Whisperi] Failed to load settings for recording: {}", e);
+        DictationSettings::default()
+    });
+
+    AudioRecorder::start(&state, device_id, settings.resample_quality).str_err()?;
+    pipeline::emit_recording(&app);
+
+    let silence_timeout = match settings.silence_timeout {
+        0 => None,
+        secs => Some(Duration::from_secs(secs.into())),
+    };
+    if settings.streaming_transcription {
+        pipeline::streaming::start(&app, settings);
+    }
 
     // Clone the Arc handles we need for the level emitter
-    let (is_recording, peak_level, recording_error) = state.level_emitter_handles();
+    let (is_recording, peak_level, recording_error, last_speech) = state.level_emitter_handles();
 
     // Spawn a thread to emit audio level events while recording
     std::thread::Builder::new()
         .name("whisperi-audio-level".to_string())
         .spawn(move || {
+            let mut auto_stop_sent = false;
             while is_recording.load(Ordering::SeqCst) {
                 let level = *peak_level.lock().unwrap();
                 let _ = app.emit("audio-level", AudioLevelPayload { level });
@@ -44,7 +61,17 @@ pub fn start_recording(
                     break;
                 }
 
-                std::thread::sleep(std::time::Duration::from_millis(50));
+                // Ask the frontend to stop once the speaker has gone quiet
+                if let Some(timeout) = silence_timeout
+                    && !auto_stop_sent
+                    && last_speech.lock().unwrap().is_some_and(|t| t.elapsed() >= timeout)
+                {
+                    log::info!("[Whisperi] {}s of silence, auto-stopping recording", timeout.as_secs());
+                    let _ = app.emit("recording-auto-stop", ());
+                    auto_stop_sent = true;
+                }
+
+                std::thread::sleep(Duration::from_millis(50));
             }
 
             // Emit a final zero level when recording stops
@@ -56,7 +83,8 @@ pub fn start_recording(
 }
 
 #[tauri::command]
-pub fn stop_recording(state: State<'_, RecordingState>) -> Result<Vec<u8>, String> {
+pub fn stop_recording(app: AppHandle, state: State<'_, RecordingState>) -> Result<Vec<u8>, String> {
+    pipeline::streaming::cancel(&app);
     AudioRecorder::stop(&state).str_err()
 }
 
@@ -1,5 +1,7 @@
 use super::ResultExt;
-use crate::database::{Database, Transcription};
+use crate::database::search::{SearchFilters, SearchHit};
+use crate::database::{Database, NewTranscription, Transcription};
+use crate::recordings::Recordings;
 use tauri::State;
 
 #[tauri::command]
@@ -11,13 +13,14 @@ pub fn save_transcription(
     agent_name: Option<String>,
     error: Option<String>,
 ) -> Result<i64, String> {
-    db.save_transcription(
-        &original_text,
-        processed_text.as_deref(),
-        &processing_method,
-        agent_name.as_deref(),
-        error.as_deref(),
-    )
+    db.save_transcription(&NewTranscription {
+        original_text,
+        processed_text,
+        processing_method,
+        agent_name,
+        error,
+        ..Default::default()
+    })
     .str_err()
 }
 
@@ -31,12 +34,32 @@ pub fn get_transcriptions(
         .str_err()
 }
 
+/// Dictation `id` followed by its re-transcriptions and re-enhancements, oldest first.
 #[tauri::command]
-pub fn delete_transcription(db: State<'_, Database>, id: i64) -> Result<(), String> {
+pub fn get_revisions(db: State<'_, Database>, id: i64) -> Result<Vec<Transcription>, String> {
+    db.get_revisions(id).str_err()
+}
+
+/// History rows containing every word of `query`, best match first, with a
+/// highlighted snippet. An empty query lists what `filters` allow, newest first.
+#[tauri::command]
+pub fn search_transcriptions(
+    db: State<'_, Database>,
+    query: String,
+    filters: Option<SearchFilters>,
+) -> Result<Vec<SearchHit>, String> {
+    db.search_transcriptions(&query, &filters.unwrap_or_default()).str_err()
+}
+
+/// Delete a history row along with its revisions and kept recording.
+#[tauri::command]
+pub fn delete_transcription(db: State<'_, Database>, recordings: State<'_, Recordings>, id: i64) -> Result<(), String> {
+    recordings.delete(id).str_err()?;
     db.delete_transcription(id).str_err()
 }
 
 #[tauri::command]
-pub fn clear_transcriptions(db: State<'_, Database>) -> Result<(), String> {
+pub fn clear_transcriptions(db: State<'_, Database>, recordings: State<'_, Recordings>) -> Result<(), String> {
+    recordings.clear().str_err()?;
     db.clear_transcriptions().str_err()
 }
@@ -3,7 +3,10 @@ pub mod audio;
 pub mod clipboard;
 pub mod database;
 pub mod models;
+pub mod pipeline;
 pub mod reasoning;
+pub mod recordings;
+pub mod secrets;
 pub mod settings;
 pub mod transcription;
 
@@ -1,14 +1,55 @@
-use serde_json::Value;
+use super::ResultExt;
+use crate::database::Database;
+use crate::models::catalog::{self, CatalogRequest, ProviderModels};
+use crate::models::registry::{self, ModelRegistry};
+use crate::pipeline::DictationSettings;
+use crate::provider::{ProviderSummary, Purpose};
+use crate::{reasoning, transcription};
+use serde::Serialize;
+use tauri::{AppHandle, State};
 
+#[derive(Debug, Serialize)]
+pub struct ProviderList {
+    pub transcription: Vec<ProviderSummary>,
+    pub reasoning: Vec<ProviderSummary>,
+}
+
+/// The model registry (bundled data plus the user override) for the frontend.
+#[tauri::command]
+pub fn get_model_registry() -> Result<ModelRegistry, String> {
+    Ok(registry::registry().clone())
+}
+
+/// Providers the backend has clients for, with their capabilities.
+#[tauri::command]
+pub fn list_providers(app: AppHandle) -> Result<ProviderList, String> {
+    let settings = DictationSettings::load(&app).str_err()?;
+    Ok(ProviderList {
+        transcription: transcription::summaries(settings.transcription_config()),
+        reasoning: reasoning::summaries(settings.reasoning_config()),
+    })
+}
+
+/// Models a provider currently offers for `purpose`, fetched with the stored
+/// key and cached in the database for a day unless `refresh` is set.
 #[tauri::command]
-pub fn get_model_registry() -> Result<Value, String> {
-    // Load the model registry JSON that's embedded from the frontend
-    // This will be populated with the actual registry data in Phase 6
-    let registry = serde_json::json!({
-        "whisperModels": {},
-        "cloudProviders": [],
-        "transcriptionProviders": []
-    });
-
-    Ok(registry)
+pub async fn list_provider_models(
+    app: AppHandle,
+    db: State<'_, Database>,
+    provider: String,
+    purpose: Purpose,
+    refresh: Option<bool>,
+) -> Result<ProviderModels, String> {
+    let settings = DictationSettings::load(&app).str_err()?;
+    let (config, api_key) = match purpose {
+        Purpose::Transcription => (settings.transcription_config(), settings.transcription_key(&provider)),
+        Purpose::Reasoning => (settings.reasoning_config(), settings.reasoning_key(&provider)),
+    };
+    let request = CatalogRequest {
+        purpose,
+        provider: &provider,
+        config,
+        api_key,
+    };
+    catalog::list(&db, &request, refresh.unwrap_or(false)).await.str_err()
 }
@@ -0,0 +1,41 @@
+use super::ResultExt;
+use crate::database::Transcription;
+use crate::pipeline::revisions::{self, Engine, Enhancement};
+use crate::pipeline::{self, DictationOutcome};
+use tauri::AppHandle;
+
+/// Stop the active recording and run it through the full dictation pipeline.
+#[tauri::command]
+pub async fn dictate(app: AppHandle) -> Result<DictationOutcome, String> {
+    pipeline::dictate(&app).await.str_err()
+}
+
+/// Transcribe the kept recording of history row `id` again with another
+/// engine, saving the result as a revision of the dictation.
+#[tauri::command]
+pub async fn retranscribe(
+    app: AppHandle,
+    id: i64,
+    provider: String,
+    model: Option<String>,
+    language: Option<String>,
+) -> Result<Transcription, String> {
+    let engine = Engine {
+        provider,
+        model,
+        language,
+    };
+    revisions::retranscribe(&app, id, &engine).await.str_err()
+}
+
+/// Enhance the text of history row `id` again, optionally with another system
+/// prompt or agent name, saving the result as a revision of the dictation.
+#[tauri::command]
+pub async fn reenhance(
+    app: AppHandle,
+    id: i64,
+    prompt: Option<String>,
+    agent_name: Option<String>,
+) -> Result<Transcription, String> {
+    revisions::reenhance(&app, id, &Enhancement { prompt, agent_name }).await.str_err()
+}
@@ -1,28 +1,34 @@
+use super::ResultExt;
+use crate::pipeline::DictationSettings;
+use crate::reasoning::local::LocalApi;
 use crate::reasoning::{self, ReasoningRequest};
+use tauri::AppHandle;
 
+/// Run text through a reasoning provider by id, using the key from secure
+/// storage. The `custom` endpoint (and its model) and the `local` server come
+/// from settings.
 #[tauri::command]
 pub async fn process_reasoning(
+    app: AppHandle,
     text: String,
     model: String,
     provider: String,
     system_prompt: String,
-    api_key: String,
     max_tokens: Option<u32>,
 ) -> Result<String, String> {
-    let key_preview = if api_key.len() > 8 {
-        format!("{}...{}", &api_key[..4], &api_key[api_key.len()-4..])
-    } else {
-        "(too short)".to_string()
-    };
-    log::info!("[Whisperi] Enhancing: provider={}, model={}, key={}", provider, model, key_preview);
+    log::info!("[Whisperi] Enhancing: provider={}, model={}", provider, model);
+
+    let settings = DictationSettings::load(&app).str_err()?;
 
     let req = ReasoningRequest {
         text,
         model,
+        api_key: settings.reasoning_key(&provider).unwrap_or_default().to_string(),
         provider,
         system_prompt,
-        api_key,
         max_tokens,
+        endpoint: Some(settings.custom_reasoning),
+        local: Some(settings.local_reasoning),
     };
 
     match reasoning::process(&req).await {
@@ -36,3 +42,21 @@ pub async fn process_reasoning(
         }
     }
 }
+
+/// Models installed on a local Ollama / llama.cpp server. Arguments left out
+/// fall back to the saved `localReasoning` settings.
+#[tauri::command]
+pub async fn list_local_models(
+    app: AppHandle,
+    base_url: Option<String>,
+    api: Option<LocalApi>,
+) -> Result<Vec<String>, String> {
+    let mut server = DictationSettings::load(&app).str_err()?.local_reasoning;
+    if let Some(base_url) = base_url.filter(|u| !u.trim().is_empty()) {
+        server.base_url = base_url;
+    }
+    if let Some(api) = api {
+        server.api = api;
+    }
+    server.list_models().await.str_err()
+}
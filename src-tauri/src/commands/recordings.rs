@@ -0,0 +1,44 @@
+use super::ResultExt;
+use crate::database::Database;
+use crate::pipeline::DictationSettings;
+use crate::recordings::{self, Recordings};
+use std::path::PathBuf;
+use tauri::ipc::Response;
+use tauri::{AppHandle, State};
+
+/// The WAV clip kept for history row `id`, as raw bytes for playback.
+#[tauri::command]
+pub fn get_recording(recordings: State<'_, Recordings>, id: i64) -> Result<Response, String> {
+    recordings.load(id).map(Response::new).str_err()
+}
+
+/// Write the clip kept for history row `id` to `path` as a WAV file.
+#[tauri::command]
+pub fn export_recording(recordings: State<'_, Recordings>, id: i64, path: PathBuf) -> Result<(), String> {
+    recordings.export(id, &path).str_err()
+}
+
+#[tauri::command]
+pub fn delete_recording(db: State<'_, Database>, recordings: State<'_, Recordings>, id: i64) -> Result<(), String> {
+    recordings::delete(&db, &recordings, id).str_err()
+}
+
+/// Delete every kept recording, or with `all` unset only those the current
+/// retention settings no longer cover. Returns how many were deleted when
+/// applying the settings.
+#[tauri::command]
+pub fn purge_recordings(
+    app: AppHandle,
+    db: State<'_, Database>,
+    recordings: State<'_, Recordings>,
+    all: bool,
+) -> Result<usize, String> {
+    if all {
+        let count = db.get_stored_audio().str_err()?.len();
+        recordings.clear().str_err()?;
+        db.clear_audio().str_err()?;
+        return Ok(count);
+    }
+    let settings = DictationSettings::load(&app).str_err()?;
+    recordings::prune(&db, &recordings, &settings.audio_retention()).str_err()
+}
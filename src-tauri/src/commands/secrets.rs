@@ -0,0 +1,64 @@
+use super::ResultExt;
+use crate::credentials::{self, CheckRequest, CredentialCache, CredentialCheck};
+use crate::provider::Purpose;
+use crate::pipeline::DictationSettings;
+use crate::secrets::{Secrets, StoreKind};
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+#[derive(Debug, Serialize)]
+pub struct ApiKeyStatus {
+    pub stored: bool,
+    pub store: StoreKind,
+}
+
+/// Save the API key for a provider (or `customTranscription` /
+/// `customReasoning`) in secure storage. An empty key deletes it.
+#[tauri::command]
+pub fn set_api_key(secrets: State<'_, Secrets>, provider: String, api_key: String) -> Result<(), String> {
+    secrets.set(&provider, &api_key).str_err()
+}
+
+#[tauri::command]
+pub fn delete_api_key(secrets: State<'_, Secrets>, provider: String) -> Result<(), String> {
+    secrets.delete(&provider).str_err()
+}
+
+/// Whether a key is stored for `provider`, and where. The key itself never
+/// leaves the backend.
+#[tauri::command]
+pub fn test_api_key(secrets: State<'_, Secrets>, provider: String) -> ApiKeyStatus {
+    ApiKeyStatus {
+        stored: secrets.get(&provider).is_some(),
+        store: secrets.kind(),
+    }
+}
+
+/// Check the stored key for a provider with a cheap authenticated request
+/// (its model list), reporting latency and which of `models` the key can
+/// use. `models` defaults to the registry's models for the provider. Results
+/// are cached for a few minutes unless `refresh` is set.
+#[tauri::command]
+pub async fn test_provider_credentials(
+    app: AppHandle,
+    cache: State<'_, CredentialCache>,
+    provider: String,
+    purpose: Purpose,
+    models: Option<Vec<String>>,
+    refresh: Option<bool>,
+) -> Result<CredentialCheck, String> {
+    let settings = DictationSettings::load(&app).str_err()?;
+    let (config, api_key) = match purpose {
+        Purpose::Transcription => (settings.transcription_config(), settings.transcription_key(&provider)),
+        Purpose::Reasoning => (settings.reasoning_config(), settings.reasoning_key(&provider)),
+    };
+    let models = models.unwrap_or_else(|| credentials::default_models(purpose, &provider, config));
+    let request = CheckRequest {
+        purpose,
+        provider: &provider,
+        config,
+        api_key,
+        models: &models,
+    };
+    cache.check(&request, refresh.unwrap_or(false)).await.str_err()
+}
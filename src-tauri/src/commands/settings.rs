@@ -3,7 +3,7 @@ use serde_json::Value;
 use tauri::AppHandle;
 use tauri_plugin_store::StoreExt;
 
-const STORE_FILE: &str = "settings.json";
+pub(crate) const STORE_FILE: &str = "settings.json";
 
 #[tauri::command]
 pub fn get_setting(app: AppHandle, key: String) -> Result<Option<Value>, String> {
@@ -13,6 +13,9 @@ pub fn get_setting(app: AppHandle, key: String) -> Result<Option<Value>, String>
 
 #[tauri::command]
 pub fn set_setting(app: AppHandle, key: String, value: Value) -> Result<(), String> {
+    if key.ends_with("ApiKey") {
+        return Err("API keys are kept in secure storage; use set_api_key".to_string());
+    }
     let store = app.store(STORE_FILE).str_err()?;
     store.set(&key, value);
     store.save().str_err()?;
@@ -1,10 +1,15 @@
 use super::ResultExt;
+use crate::models::Downloads;
+use crate::models::registry::registry;
+use crate::pipeline::DictationSettings;
+use crate::provider::TranscriptionRequest;
 use crate::transcription;
 use serde::Serialize;
-use tauri::{AppHandle, Manager};
+use tauri::{AppHandle, Manager, State};
 
+/// A local model of either engine and whether it has been downloaded.
 #[derive(Debug, Serialize)]
-pub struct WhisperModelStatus {
+pub struct ModelStatus {
     pub id: String,
     pub name: String,
     pub description: String,
@@ -14,6 +19,8 @@ pub struct WhisperModelStatus {
     pub recommended: bool,
 }
 
+/// Transcribe with a local engine: `"whisper"` (default, whisper.cpp) or
+/// `"parakeet"` (sherpa-onnx). `model` is the engine's model id.
 #[tauri::command]
 pub async fn transcribe_local(
     app: AppHandle,
@@ -21,35 +28,43 @@ pub async fn transcribe_local(
     model: String,
     language: Option<String>,
     dictionary: Vec<String>,
+    engine: Option<String>,
 ) -> Result<String, String> {
-    let file_name = format!("ggml-{}.bin", model);
-
-    transcription::whisper::transcribe(
-        &app,
-        &audio_data,
-        &file_name,
-        language.as_deref(),
-        &dictionary,
-    )
-    .await
-    .str_err()
+    match engine.as_deref().unwrap_or("whisper") {
+        "whisper" => {
+            let model = registry().whisper_model(&model).str_err()?;
+            transcription::whisper::transcribe(
+                &app,
+                &audio_data,
+                &model.file_name,
+                language.as_deref(),
+                &dictionary,
+                false,
+            )
+            .await
+            .map(|transcript| transcript.text)
+            .str_err()
+        }
+        "parakeet" => transcription::parakeet::transcribe(&app, &audio_data, &model, language.as_deref())
+            .await
+            .str_err(),
+        other => Err(format!("Unknown local engine: {}", other)),
+    }
 }
 
+/// Transcribe with a cloud provider by id, using the key from secure storage.
+/// For `custom`, the endpoint configured in settings is used and `model` is
+/// ignored.
 #[tauri::command]
 pub async fn transcribe_cloud(
+    app: AppHandle,
     audio_data: Vec<u8>,
     provider: String,
-    api_key: String,
     model: String,
     language: Option<String>,
     dictionary: Vec<String>,
 ) -> Result<String, String> {
-    let key_preview = if api_key.len() > 8 {
-        format!("{}...{}", &api_key[..4], &api_key[api_key.len()-4..])
-    } else {
-        "(too short)".to_string()
-    };
-    log::info!("[Whisperi] Transcribing: provider={}, model={}, key={}", provider, model, key_preview);
+    log::info!("[Whisperi] Transcribing: provider={}, model={}", provider, model);
 
     let prompt = if dictionary.is_empty() {
         None
@@ -57,115 +72,37 @@ pub async fn transcribe_cloud(
         Some(dictionary.join(" "))
     };
 
-    match provider.as_str() {
-        "openai" => transcription::cloud::transcribe_openai(
-            audio_data,
-            &api_key,
-            &model,
-            language.as_deref(),
-            prompt.as_deref(),
-            None,
-        )
-        .await
-        .str_err(),
-
-        "groq" => transcription::cloud::transcribe_groq(
-            audio_data,
-            &api_key,
-            &model,
-            language.as_deref(),
-            prompt.as_deref(),
-        )
-        .await
-        .str_err(),
-
-        "qwen" => transcription::cloud::transcribe_qwen(
-            audio_data,
-            &api_key,
-            &model,
-        )
-        .await
-        .str_err(),
-
-        "mistral" => transcription::cloud::transcribe_mistral(
-            audio_data,
-            &api_key,
-            &model,
-            language.as_deref(),
-            prompt.as_deref(),
-        )
-        .await
-        .str_err(),
-
-        "openrouter" => transcription::cloud::transcribe_openrouter(
-            audio_data,
-            &api_key,
-            &model,
-            language.as_deref(),
-            prompt.as_deref(),
-        )
+    // Keys and provider-specific configuration (the custom endpoint) live in settings
+    let settings = DictationSettings::load(&app).str_err()?;
+    let request = TranscriptionRequest {
+        audio: audio_data,
+        api_key: settings.transcription_key(&provider),
+        model: &model,
+        language: language.as_deref(),
+        prompt: prompt.as_deref(),
+        timestamps: false,
+    };
+    transcription::transcribe_cloud(&provider, settings.transcription_config(), request)
         .await
-        .str_err(),
-
-        other => Err(format!("Unknown transcription provider: {}", other)),
-    }
+        .map(|transcript| transcript.text)
+        .str_err()
 }
 
 #[tauri::command]
-pub fn list_whisper_models() -> Result<Vec<WhisperModelStatus>, String> {
+pub fn list_whisper_models() -> Result<Vec<ModelStatus>, String> {
     let models_dir = transcription::whisper::models_dir().str_err()?;
 
-    let models = vec![
-        ("tiny", "Tiny", "Fastest, lower quality", "75MB", 75, false),
-        (
-            "base",
-            "Base",
-            "Good balance of speed and quality",
-            "142MB",
-            142,
-            true,
-        ),
-        (
-            "small",
-            "Small",
-            "Better quality, slower",
-            "466MB",
-            466,
-            false,
-        ),
-        ("medium", "Medium", "High quality", "1.5GB", 1500, false),
-        (
-            "large",
-            "Large",
-            "Best quality, slowest",
-            "3GB",
-            3000,
-            false,
-        ),
-        (
-            "turbo",
-            "Turbo",
-            "Fast with good quality",
-            "1.6GB",
-            1600,
-            false,
-        ),
-    ];
-
-    Ok(models
-        .into_iter()
-        .map(|(id, name, desc, size, size_mb, recommended)| {
-            let file_name = format!("ggml-{}.bin", id);
-            let downloaded = models_dir.join(&file_name).exists();
-            WhisperModelStatus {
-                id: id.to_string(),
-                name: name.to_string(),
-                description: desc.to_string(),
-                size: size.to_string(),
-                size_mb,
-                downloaded,
-                recommended,
-            }
+    Ok(registry()
+        .whisper_models
+        .iter()
+        .map(|(id, model)| ModelStatus {
+            id: id.clone(),
+            name: model.name.clone(),
+            description: model.description.clone(),
+            size: model.size.clone(),
+            size_mb: model.size_mb,
+            downloaded: models_dir.join(&model.file_name).exists(),
+            recommended: model.recommended,
         })
         .collect())
 }
@@ -179,57 +116,120 @@ struct ModelDownloadProgress {
 }
 
 #[tauri::command]
-pub async fn download_whisper_model(app: AppHandle, model_id: String) -> Result<(), String> {
-    let file_name = format!("ggml-{}.bin", model_id);
-    let url = format!(
-        "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/{}",
-        file_name
-    );
+pub async fn download_whisper_model(
+    app: AppHandle,
+    downloads: State<'_, Downloads>,
+    model_id: String,
+) -> Result<(), String> {
+    let model = registry().whisper_model(&model_id).str_err()?;
     let dest = transcription::whisper::models_dir()
         .str_err()?
-        .join(&file_name);
+        .join(&model.file_name);
 
     // Skip if already downloaded
     if dest.exists() {
-        let _ = tauri::Emitter::emit(
-            &app,
-            "model-download-progress",
-            ModelDownloadProgress {
-                model_id,
-                downloaded: 0,
-                total: 0,
-                percentage: 100,
-            },
-        );
+        emit_download_complete(&app, &model_id);
         return Ok(());
     }
 
-    let model_id_clone = model_id.clone();
-    crate::models::download_file(&url, &dest, move |downloaded, total| {
-        let percentage = if total > 0 {
-            ((downloaded as f64 / total as f64) * 100.0).min(100.0) as u8
-        } else {
-            0
-        };
-        let _ = tauri::Emitter::emit(
-            &app,
-            "model-download-progress",
-            ModelDownloadProgress {
-                model_id: model_id_clone.clone(),
-                downloaded,
-                total,
-                percentage,
-            },
-        );
-    })
+    let download = downloads.begin(&model_id).str_err()?;
+    crate::models::download_file(
+        &model.download_url,
+        &dest,
+        model.sha256.as_deref(),
+        &download.token,
+        |downloaded, total| emit_download_progress(&app, &model_id, downloaded, total),
+    )
     .await
     .str_err()
 }
 
+/// Cancel a running whisper or Parakeet model download. The partial file is
+/// kept, so downloading the model again resumes where it stopped.
+#[tauri::command]
+pub fn cancel_model_download(downloads: State<'_, Downloads>, model_id: String) -> bool {
+    downloads.cancel(&model_id)
+}
+
+/// Report an already-downloaded model as complete.
+fn emit_download_complete(app: &AppHandle, model_id: &str) {
+    let _ = tauri::Emitter::emit(
+        app,
+        "model-download-progress",
+        ModelDownloadProgress {
+            model_id: model_id.to_string(),
+            downloaded: 0,
+            total: 0,
+            percentage: 100,
+        },
+    );
+}
+
+fn emit_download_progress(app: &AppHandle, model_id: &str, downloaded: u64, total: u64) {
+    let percentage = if total > 0 {
+        ((downloaded as f64 / total as f64) * 100.0).min(100.0) as u8
+    } else {
+        0
+    };
+    let _ = tauri::Emitter::emit(
+        app,
+        "model-download-progress",
+        ModelDownloadProgress {
+            model_id: model_id.to_string(),
+            downloaded,
+            total,
+            percentage,
+        },
+    );
+}
+
 #[tauri::command]
 pub fn delete_whisper_model(model_id: String) -> Result<(), String> {
-    let file_name = format!("ggml-{}.bin", model_id);
-    transcription::whisper::delete_model(&file_name).str_err()
+    let model = registry().whisper_model(&model_id).str_err()?;
+    transcription::whisper::delete_model(&model.file_name).str_err()
+}
+
+#[tauri::command]
+pub fn list_parakeet_models() -> Result<Vec<ModelStatus>, String> {
+    transcription::parakeet::models()
+        .iter()
+        .map(|(id, model)| {
+            Ok(ModelStatus {
+                id: id.clone(),
+                name: model.name.clone(),
+                description: model.description.clone(),
+                size: model.size.clone(),
+                size_mb: model.size_mb,
+                downloaded: transcription::parakeet::is_downloaded(id).str_err()?,
+                recommended: model.recommended,
+            })
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub async fn download_parakeet_model(
+    app: AppHandle,
+    downloads: State<'_, Downloads>,
+    model_id: String,
+) -> Result<(), String> {
+    // Skip if already downloaded
+    if transcription::parakeet::is_downloaded(&model_id).str_err()? {
+        emit_download_complete(&app, &model_id);
+        return Ok(());
+    }
+
+    let download = downloads.begin(&model_id).str_err()?;
+    transcription::parakeet::download_model(&model_id, &download.token, |downloaded, total| {
+        emit_download_progress(&app, &model_id, downloaded, total)
+    })
+    .await
+    .str_err()
+}
+
+#[tauri::command]
+pub fn delete_parakeet_model(model_id: String) -> Result<(), String> {
+    transcription::parakeet::delete_model(&model_id).str_err()
 }
 
 /// Get the sidecar binary filename for the current platform.
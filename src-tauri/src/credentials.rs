@@ -0,0 +1,330 @@
+//! Credential checks. Each check makes the provider's cheapest authenticated
+//! request (listing its models), so a bad key shows up in Settings rather
+//! than in a failed dictation. Results are cached per provider, key and
+//! configuration; the key itself is only kept as part of a hash.
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::endpoint::CUSTOM_PROVIDER;
+use crate::models::registry::registry;
+use crate::provider::{ErrorInfo, ErrorKind, ListedModel, ProviderConfig, ProviderError, Purpose, require_key};
+use crate::{reasoning, transcription};
+
+/// How long a check result is reused.
+const CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialCheck {
+    pub provider: String,
+    pub purpose: Purpose,
+    /// Whether the provider accepted the key.
+    pub ok: bool,
+    pub latency_ms: u64,
+    /// Requested models the key can use.
+    pub accessible: Vec<String>,
+    /// Requested models the provider doesn't list for this key.
+    pub unavailable: Vec<String>,
+    /// Every model the provider listed.
+    pub model_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorInfo>,
+    /// Unix time of the request, in seconds.
+    pub checked_at: u64,
+    /// Whether this result came from the cache.
+    pub cached: bool,
+}
+
+pub struct CheckRequest<'a> {
+    pub purpose: Purpose,
+    pub provider: &'a str,
+    pub config: ProviderConfig<'a>,
+    pub api_key: Option<&'a str>,
+    /// Models to look for in the provider's list.
+    pub models: &'a [String],
+}
+
+/// Outcome of one request, kept so different model lists can be checked
+/// against it without asking again.
+#[derive(Debug, Clone)]
+struct Probe {
+    models: Result<Vec<String>, ErrorInfo>,
+    latency: Duration,
+    checked_at: u64,
+}
+
+/// Managed state holding recent check results.
+#[derive(Default)]
+pub struct CredentialCache {
+    entries: Mutex<HashMap<String, (Instant, Probe)>>,
+}
+
+impl CredentialCache {
+    /// Check the key for `request.provider`, reusing a result younger than
+    /// [`CACHE_TTL`] unless `refresh` is set. Fails only when the provider
+    /// can't be built (unknown id, no custom endpoint); a rejected key or an
+    /// unreachable provider is reported in the result.
+    pub async fn check(&self, request: &CheckRequest<'_>, refresh: bool) -> Result<CredentialCheck, ProviderError> {
+        let fingerprint = fingerprint(request.purpose, request.provider, request.config, request.api_key);
+        let cached = if refresh { None } else { self.get(&fingerprint) };
+        let is_cached = cached.is_some();
+        let probe = match cached {
+            Some(probe) => probe,
+            None => {
+                let probe = probe(request).await?;
+                // Transient failures and unreachable servers say nothing about
+                // the key, so the next check asks again
+                let inconclusive = matches!(
+                    &probe.models,
+                    Err(e) if e.retryable || matches!(e.kind, ErrorKind::Network | ErrorKind::Timeout)
+                );
+                if !inconclusive {
+                    self.entries
+                        .lock()
+                        .unwrap()
+                        .insert(fingerprint, (Instant::now(), probe.clone()));
+                }
+                probe
+            }
+        };
+        Ok(report(request, probe, is_cached))
+    }
+
+    fn get(&self, fingerprint: &str) -> Option<Probe> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, (at, _)| at.elapsed() < CACHE_TTL);
+        entries.get(fingerprint).map(|(_, probe)| probe.clone())
+    }
+}
+
+/// The models worth checking when the caller names none: the registry's
+/// suggestions, or the custom endpoint's configured model.
+pub fn default_models(purpose: Purpose, provider: &str, config: ProviderConfig<'_>) -> Vec<String> {
+    let info = match purpose {
+        Purpose::Transcription => registry().transcription_provider(provider),
+        Purpose::Reasoning => registry().reasoning_provider(provider),
+    };
+    if let Ok(info) = info {
+        return info.models.iter().map(|m| m.id.clone()).collect();
+    }
+    match config.endpoint {
+        Some(endpoint) if provider == CUSTOM_PROVIDER && !endpoint.model.is_empty() => vec![endpoint.model.clone()],
+        _ => Vec::new(),
+    }
+}
+
+/// List `provider`'s models with `api_key`. The outer error means the
+/// provider couldn't be built; the inner one is the provider's answer.
+pub async fn list_models(
+    purpose: Purpose,
+    provider: &str,
+    config: ProviderConfig<'_>,
+    api_key: Option<&str>,
+) -> Result<Result<Vec<ListedModel>, ProviderError>, ProviderError> {
+    Ok(match purpose {
+        Purpose::Transcription => {
+            let client = transcription::providers().get(provider, config)?;
+            match require_key(provider, client.requires_api_key(), api_key) {
+                Ok(api_key) => client.list_models(api_key).await,
+                Err(e) => Err(e),
+            }
+        }
+        Purpose::Reasoning => {
+            let client = reasoning::providers().get(provider, config)?;
+            match require_key(provider, client.requires_api_key(), api_key) {
+                Ok(api_key) => client.list_models(api_key).await,
+                Err(e) => Err(e),
+            }
+        }
+    })
+}
+
+async fn probe(request: &CheckRequest<'_>) -> Result<Probe, ProviderError> {
+    let started = Instant::now();
+    let models = list_models(request.purpose, request.provider, request.config, request.api_key).await?;
+    let models = models.map(|list| list.into_iter().map(|m| m.id).collect::<Vec<_>>());
+    let latency = started.elapsed();
+    match &models {
+        Ok(list) => log::info!(
+            "[Whisperi] {} key check passed in {} ms ({} models)",
+            request.provider,
+            latency.as_millis(),
+            list.len()
+        ),
+        Err(e) => log::warn!("[Whisperi] {} key check failed: {}", request.provider, e),
+    }
+    Ok(Probe {
+        models: models.map_err(|e| e.info()),
+        latency,
+        checked_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    })
+}
+
+fn report(request: &CheckRequest<'_>, probe: Probe, cached: bool) -> CredentialCheck {
+    let (accessible, unavailable) = match &probe.models {
+        Ok(listed) => request.models.iter().cloned().partition(|model| listed.contains(model)),
+        // Nothing is known about model access when the check failed
+        Err(_) => (Vec::new(), Vec::new()),
+    };
+    CredentialCheck {
+        provider: request.provider.to_string(),
+        purpose: request.purpose,
+        ok: probe.models.is_ok(),
+        latency_ms: probe.latency.as_millis() as u64,
+        accessible,
+        unavailable,
+        model_count: probe.models.as_ref().map_or(0, Vec::len),
+        error: probe.models.err(),
+        checked_at: probe.checked_at,
+        cached,
+    }
+}
+
+/// Cache key covering everything that changes a provider's answer, as hex
+/// SHA-256. It is stored in the database, so it must not change between builds.
+pub fn fingerprint(purpose: Purpose, provider: &str, config: ProviderConfig<'_>, api_key: Option<&str>) -> String {
+    let endpoint = serde_json::to_string(&config.endpoint).unwrap_or_default();
+    let local = serde_json::to_string(&config.local).unwrap_or_default();
+    let mut hasher = Sha256::new();
+    for part in [purpose.as_str(), provider, api_key.unwrap_or_default(), &endpoint, &local] {
+        hasher.update(part.as_bytes());
+        // Separator, so moving bytes between fields changes the hash
+        hasher.update([0]);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endpoint::CustomEndpoint;
+    use crate::reasoning::local::{LOCAL_PROVIDER, LocalServer};
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn request<'a>(
+        provider: &'a str,
+        endpoint: &'a CustomEndpoint,
+        api_key: &'a str,
+        models: &'a [String],
+    ) -> CheckRequest<'a> {
+        CheckRequest {
+            purpose: Purpose::Transcription,
+            provider,
+            config: ProviderConfig {
+                endpoint: Some(endpoint),
+                ..Default::default()
+            },
+            api_key: Some(api_key),
+            models,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_reports_model_access_and_caches_per_key() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .and(header("authorization", "Bearer good"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "object": "list",
+                "data": [{ "id": "large-v3" }, { "id": "distil-large-v3" }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .and(header("authorization", "Bearer bad"))
+            .respond_with(ResponseTemplate::new(401))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let endpoint = CustomEndpoint {
+            base_url: format!("{}/v1", server.uri()),
+            model: "large-v3".to_string(),
+            ..Default::default()
+        };
+        let models = default_models(Purpose::Transcription, CUSTOM_PROVIDER, request("", &endpoint, "", &[]).config);
+        assert_eq!(models, ["large-v3"]);
+        let models = vec!["large-v3".to_string(), "tiny".to_string()];
+        let cache = CredentialCache::default();
+
+        let first = cache.check(&request(CUSTOM_PROVIDER, &endpoint, "good", &models), false).await.unwrap();
+        assert!(first.ok && !first.cached);
+        assert_eq!(first.accessible, ["large-v3"]);
+        assert_eq!(first.unavailable, ["tiny"]);
+        assert_eq!(first.model_count, 2);
+        let again = cache.check(&request(CUSTOM_PROVIDER, &endpoint, "good", &models), false).await.unwrap();
+        assert!(again.ok && again.cached);
+        assert_eq!(again.checked_at, first.checked_at);
+
+        let rejected = cache.check(&request(CUSTOM_PROVIDER, &endpoint, "bad", &models), false).await.unwrap();
+        assert!(!rejected.ok && rejected.accessible.is_empty());
+        assert_eq!(rejected.error.unwrap().kind, ErrorKind::Unauthorized);
+        assert!(cache.check(&request(CUSTOM_PROVIDER, &endpoint, "bad", &models), false).await.unwrap().cached);
+    }
+
+    #[tokio::test]
+    async fn test_check_without_key_or_provider() {
+        let cache = CredentialCache::default();
+        let endpoint = CustomEndpoint::default();
+        let missing = cache.check(&request("groq", &endpoint, "", &[]), false).await.unwrap();
+        assert!(!missing.ok);
+        assert_eq!(missing.error.unwrap().kind, ErrorKind::MissingApiKey);
+
+        let unknown = cache.check(&request("nope", &endpoint, "k", &[]), false).await.unwrap_err();
+        assert!(matches!(unknown, ProviderError::UnknownProvider(_)));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_local_server_is_a_network_failure_and_not_cached() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let server = LocalServer {
+            base_url: format!("http://{}", listener.local_addr().unwrap()),
+            ..Default::default()
+        };
+        drop(listener);
+        let request = CheckRequest {
+            purpose: Purpose::Reasoning,
+            provider: LOCAL_PROVIDER,
+            config: ProviderConfig {
+                local: Some(&server),
+                ..Default::default()
+            },
+            api_key: None,
+            models: &[],
+        };
+
+        let cache = CredentialCache::default();
+        let first = cache.check(&request, false).await.unwrap();
+        assert!(!first.ok);
+        let error = first.error.unwrap();
+        assert_eq!(error.kind, ErrorKind::Network);
+        assert!(error.retryable);
+        let again = cache.check(&request, false).await.unwrap();
+        assert!(!again.cached);
+    }
+
+    #[test]
+    fn test_fingerprint_is_stable() {
+        let config = ProviderConfig::default();
+        // Persisted in `provider_models`, so the value must never drift
+        assert_eq!(
+            fingerprint(Purpose::Transcription, "groq", config, Some("gsk-test")),
+            "d1c289c3705a7c023756951d6b438a2fffa847fcefbd0a0ea28e8cad3a4fa250"
+        );
+        assert_ne!(
+            fingerprint(Purpose::Transcription, "groq", config, Some("gsk-other")),
+            fingerprint(Purpose::Transcription, "groq", config, Some("gsk-test"))
+        );
+    }
+}
@@ -1,20 +1,204 @@
-use anyhow::Result;
+//! Numbered schema migrations. The schema version is kept in SQLite's
+//! `PRAGMA user_version`; each pending migration runs in its own transaction
+//! together with the version bump, so a failure leaves the database at the
+//! last version that applied cleanly.
+//!
+//! Databases from before versioning report version 0 and may already have
+//! some of the later columns, so every step tolerates work already done.
+
+use anyhow::{Context, Result};
 use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+
+struct Migration {
+    description: &'static str,
+    up: fn(&Connection) -> Result<()>,
+}
 
+/// Version N of the schema is reached by applying `MIGRATIONS[N - 1]`.
+/// Append only: released steps must never change.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        description: "transcriptions table",
+        up: |conn| {
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS transcriptions (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    timestamp DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    original_text TEXT NOT NULL,
+                    processed_text TEXT,
+                    is_processed BOOLEAN DEFAULT 0,
+                    processing_method TEXT DEFAULT 'none',
+                    agent_name TEXT,
+                    error TEXT
+                );",
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        description: "providers that produced each transcription",
+        up: |conn| {
+            add_column(conn, "transcriptions", "transcription_provider", "TEXT")?;
+            add_column(conn, "transcriptions", "reasoning_provider", "TEXT")
+        },
+    },
+    Migration {
+        description: "provider model list cache",
+        up: |conn| {
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS provider_models (
+                    provider TEXT NOT NULL,
+                    purpose TEXT NOT NULL,
+                    models TEXT NOT NULL,
+                    fingerprint TEXT NOT NULL,
+                    fetched_at INTEGER NOT NULL,
+                    PRIMARY KEY (provider, purpose)
+                );",
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        description: "detected language and timed segments",
+        up: |conn| {
+            add_column(conn, "transcriptions", "language", "TEXT")?;
+            // JSON array of `transcript::Segment`
+            add_column(conn, "transcriptions", "segments", "TEXT")
+        },
+    },
+    Migration {
+        description: "kept recordings",
+        // Bytes of the clip in `recordings/`, NULL when none is kept
+        up: |conn| add_column(conn, "transcriptions", "audio_size", "INTEGER"),
+    },
+    Migration {
+        description: "revisions",
+        // Original row of a re-transcription or re-enhancement
+        up: |conn| add_column(conn, "transcriptions", "parent_id", "INTEGER"),
+    },
+    Migration {
+        description: "full-text search",
+        // External-content index over both texts, kept in sync by triggers
+        up: |conn| {
+            conn.execute_batch(
+                "CREATE VIRTUAL TABLE IF NOT EXISTS transcriptions_fts USING fts5(
+                    original_text, processed_text,
+                    content = 'transcriptions', content_rowid = 'id',
+                    tokenize = 'unicode61 remove_diacritics 2'
+                );
+                CREATE TRIGGER IF NOT EXISTS transcriptions_fts_insert AFTER INSERT ON transcriptions BEGIN
+                    INSERT INTO transcriptions_fts (rowid, original_text, processed_text)
+                    VALUES (new.id, new.original_text, new.processed_text);
+                END;
+                CREATE TRIGGER IF NOT EXISTS transcriptions_fts_delete AFTER DELETE ON transcriptions BEGIN
+                    INSERT INTO transcriptions_fts (transcriptions_fts, rowid, original_text, processed_text)
+                    VALUES ('delete', old.id, old.original_text, old.processed_text);
+                END;
+                CREATE TRIGGER IF NOT EXISTS transcriptions_fts_update
+                AFTER UPDATE OF original_text, processed_text ON transcriptions BEGIN
+                    INSERT INTO transcriptions_fts (transcriptions_fts, rowid, original_text, processed_text)
+                    VALUES ('delete', old.id, old.original_text, old.processed_text);
+                    INSERT INTO transcriptions_fts (rowid, original_text, processed_text)
+                    VALUES (new.id, new.original_text, new.processed_text);
+                END;
+                INSERT INTO transcriptions_fts (transcriptions_fts) VALUES ('rebuild');",
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        description: "dictation metadata",
+        // See `DictationMetadata`; NULL where unknown, as for every older row
+        up: |conn| {
+            for (column, definition) in [
+                ("audio_duration_ms", "INTEGER"),
+                ("input_device", "TEXT"),
+                ("transcription_model", "TEXT"),
+                ("requested_language", "TEXT"),
+                ("reasoning_model", "TEXT"),
+                ("transcription_ms", "INTEGER"),
+                ("enhancement_ms", "INTEGER"),
+                ("paste_ms", "INTEGER"),
+                ("total_ms", "INTEGER"),
+                ("prompt_tokens", "INTEGER"),
+                ("completion_tokens", "INTEGER"),
+                ("target_app", "TEXT"),
+                ("paste_succeeded", "BOOLEAN"),
+            ] {
+                add_column(conn, "transcriptions", column, definition)?;
+            }
+            Ok(())
+        },
+    },
+];
+
+/// Schema version this build migrates to.
+pub fn latest() -> u32 {
+    MIGRATIONS.len() as u32
+}
+
+pub fn version(conn: &Connection) -> Result<u32> {
+    Ok(conn.query_row("PRAGMA user_version", [], |row| row.get(0))?)
+}
+
+/// Bring the schema up to [`latest`].
 pub fn run(conn: &Connection) -> Result<()> {
-    conn.execute_batch(
-        "CREATE TABLE IF NOT EXISTS transcriptions (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            timestamp DATETIME DEFAULT CURRENT_TIMESTAMP,
-            original_text TEXT NOT NULL,
-            processed_text TEXT,
-            is_processed BOOLEAN DEFAULT 0,
-            processing_method TEXT DEFAULT 'none',
-            agent_name TEXT,
-            error TEXT
-        );",
-    )?;
+    migrate(conn, MIGRATIONS)
+}
+
+fn migrate(conn: &Connection, migrations: &[Migration]) -> Result<()> {
+    let current = version(conn)?;
+    if current as usize > migrations.len() {
+        // Written by a newer build; its extra columns don't get in the way
+        log::warn!(
+            "[Whisperi] Database schema v{} is newer than this build's v{}",
+            current,
+            migrations.len()
+        );
+        return Ok(());
+    }
+    for (index, migration) in migrations.iter().enumerate().skip(current as usize) {
+        let target = index as u32 + 1;
+        let tx = conn.unchecked_transaction()?;
+        (migration.up)(&tx)
+            .and_then(|()| Ok(tx.pragma_update(None, "user_version", target)?))
+            .with_context(|| format!("Database migration to v{} ({}) failed", target, migration.description))?;
+        tx.commit()?;
+        log::info!("[Whisperi] Migrated database to v{}: {}", target, migration.description);
+    }
+    Ok(())
+}
 
+/// Copy the database at `db_path` next to it as `<name>.v<version>.bak`
+/// before migrations run, so an upgrade can be undone by hand. Returns the
+/// copy's path, or `None` when nothing is pending or the database is new.
+pub fn backup(conn: &Connection, db_path: &Path) -> Result<Option<PathBuf>> {
+    let current = version(conn)?;
+    let tables: i64 = conn.query_row("SELECT COUNT(*) FROM sqlite_master WHERE type = 'table'", [], |row| row.get(0))?;
+    if current >= latest() || tables == 0 {
+        return Ok(None);
+    }
+    let file_name = db_path.file_name().context("Database path has no file name")?;
+    let path = db_path.with_file_name(format!("{}.v{}.bak", file_name.to_string_lossy(), current));
+    // VACUUM INTO refuses to overwrite; a copy left by an earlier attempt is from the same version
+    if path.exists() {
+        std::fs::remove_file(&path).with_context(|| format!("Failed to replace {}", path.display()))?;
+    }
+    conn.execute("VACUUM INTO ?1", [path.to_string_lossy()])
+        .with_context(|| format!("Failed to back up the database to {}", path.display()))?;
+    log::info!("[Whisperi] Backed up database v{} to {}", current, path.display());
+    Ok(Some(path))
+}
+
+/// Add a column to a table created by an older version, if it is missing.
+fn add_column(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let exists = conn
+        .prepare(&format!("SELECT 1 FROM pragma_table_info('{}') WHERE name = ?1", table))?
+        .exists([column])?;
+    if !exists {
+        conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {};", table, column, definition))?;
+    }
     Ok(())
 }
 
@@ -39,4 +223,141 @@ mod tests {
         run(&conn).unwrap();
         run(&conn).unwrap(); // Should not fail on second run
     }
+
+    #[test]
+    fn test_migration_adds_provider_columns_to_old_table() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE transcriptions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp DATETIME DEFAULT CURRENT_TIMESTAMP,
+                original_text TEXT NOT NULL,
+                processed_text TEXT,
+                is_processed BOOLEAN DEFAULT 0,
+                processing_method TEXT DEFAULT 'none',
+                agent_name TEXT,
+                error TEXT
+            );
+            INSERT INTO transcriptions (original_text) VALUES ('hello');",
+        )
+        .unwrap();
+        run(&conn).unwrap();
+
+        let provider: Option<String> = conn
+            .query_row("SELECT transcription_provider FROM transcriptions", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(provider, None);
+    }
+
+    /// `whisperi.db` as the releases before versioning left it: the original
+    /// table, `user_version` 0.
+    const V0_FIXTURE: &str = "
+        CREATE TABLE transcriptions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp DATETIME DEFAULT CURRENT_TIMESTAMP,
+            original_text TEXT NOT NULL,
+            processed_text TEXT,
+            is_processed BOOLEAN DEFAULT 0,
+            processing_method TEXT DEFAULT 'none',
+            agent_name TEXT,
+            error TEXT
+        );
+        INSERT INTO transcriptions (timestamp, original_text, processed_text, is_processed, processing_method, agent_name)
+        VALUES ('2025-06-01 09:30:00', 'hello world', 'Hello, world.', 1, 'ai', 'Whisperi');
+        INSERT INTO transcriptions (original_text, error) VALUES ('raw only', 'rate limited');";
+
+    fn columns(conn: &Connection, table: &str) -> Vec<String> {
+        let mut stmt = conn.prepare(&format!("SELECT name FROM pragma_table_info('{}')", table)).unwrap();
+        stmt.query_map([], |row| row.get(0)).unwrap().map(Result::unwrap).collect()
+    }
+
+    #[test]
+    fn test_upgrades_v0_fixture_to_latest() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(V0_FIXTURE).unwrap();
+        assert_eq!(version(&conn).unwrap(), 0);
+
+        run(&conn).unwrap();
+
+        assert_eq!(version(&conn).unwrap(), latest());
+        let fresh = Connection::open_in_memory().unwrap();
+        run(&fresh).unwrap();
+        assert_eq!(columns(&conn, "transcriptions"), columns(&fresh, "transcriptions"));
+        assert_eq!(columns(&conn, "provider_models"), columns(&fresh, "provider_models"));
+
+        let (text, processed, timestamp): (String, String, String) = conn
+            .query_row(
+                "SELECT original_text, processed_text, timestamp FROM transcriptions WHERE id = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!((text.as_str(), processed.as_str()), ("hello world", "Hello, world."));
+        assert_eq!(timestamp, "2025-06-01 09:30:00");
+        let error: String = conn
+            .query_row("SELECT error FROM transcriptions WHERE id = 2", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(error, "rate limited");
+        let indexed: i64 = conn
+            .query_row("SELECT rowid FROM transcriptions_fts WHERE transcriptions_fts MATCH 'world'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(indexed, 1);
+    }
+
+    #[test]
+    fn test_failed_migration_rolls_back_its_step() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(V0_FIXTURE).unwrap();
+        let migrations = [
+            Migration {
+                description: "note column",
+                up: |conn| add_column(conn, "transcriptions", "note", "TEXT"),
+            },
+            Migration {
+                description: "half done",
+                up: |conn| {
+                    add_column(conn, "transcriptions", "half", "TEXT")?;
+                    conn.execute_batch("ALTER TABLE missing ADD COLUMN x TEXT;")?;
+                    Ok(())
+                },
+            },
+        ];
+
+        let error = migrate(&conn, &migrations).unwrap_err();
+
+        assert!(format!("{:#}", error).contains("v2 (half done)"));
+        assert_eq!(version(&conn).unwrap(), 1);
+        let columns = columns(&conn, "transcriptions");
+        assert!(columns.contains(&"note".to_string()));
+        assert!(!columns.contains(&"half".to_string()));
+    }
+
+    #[test]
+    fn test_backup_copies_database_before_upgrade() {
+        let dir = std::env::temp_dir().join(format!("whisperi-migrations-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("whisperi.db");
+        let _ = std::fs::remove_file(&db_path);
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute_batch(V0_FIXTURE).unwrap();
+
+        let copy = backup(&conn, &db_path).unwrap().unwrap();
+        run(&conn).unwrap();
+
+        assert_eq!(copy, dir.join("whisperi.db.v0.bak"));
+        let saved = Connection::open(&copy).unwrap();
+        assert_eq!(version(&saved).unwrap(), 0);
+        let count: i64 = saved
+            .query_row("SELECT COUNT(*) FROM transcriptions", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 2);
+        assert!(backup(&conn, &db_path).unwrap().is_none(), "nothing pending");
+
+        let empty = Connection::open_in_memory().unwrap();
+        assert!(backup(&empty, &dir.join("new.db")).unwrap().is_none(), "new database");
+        drop((conn, saved));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -1,13 +1,16 @@
 pub mod migrations;
+pub mod search;
 
 use anyhow::{Context, Result};
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::Mutex;
 use tauri::AppHandle;
 use tauri::Manager;
 
+use crate::transcription::transcript::Segment;
+
 pub struct Database {
     conn: Mutex<Connection>,
 }
@@ -22,14 +25,101 @@ pub struct Transcription {
     pub processing_method: String,
     pub agent_name: Option<String>,
     pub error: Option<String>,
+    /// Provider that produced `original_text`, after any fallbacks.
+    pub transcription_provider: Option<String>,
+    /// Provider that produced `processed_text`, after any fallbacks.
+    pub reasoning_provider: Option<String>,
+    /// Language the transcription engine detected.
+    pub language: Option<String>,
+    /// Timed segments of `original_text`, when timestamps were requested.
+    pub segments: Vec<Segment>,
+    /// Compressed size of the kept recording, `None` when there is none.
+    pub audio_size: Option<i64>,
+    /// The row this one revises, `None` for a dictation.
+    pub parent_id: Option<i64>,
+    #[serde(flatten)]
+    pub metadata: DictationMetadata,
+}
+
+/// How a row's text was produced, for analytics and debugging. Fields are
+/// `None` where they don't apply or weren't recorded, as in rows saved
+/// before they existed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DictationMetadata {
+    /// Length of the recording after silence trimming.
+    pub audio_duration_ms: Option<i64>,
+    pub input_device: Option<String>,
+    /// Model of `transcription_provider`.
+    pub transcription_model: Option<String>,
+    /// Language asked of the transcription engine, `"auto"` to detect;
+    /// `language` is the one it detected.
+    pub requested_language: Option<String>,
+    /// Model of `reasoning_provider`.
+    pub reasoning_model: Option<String>,
+    /// Time spent transcribing, fallbacks included.
+    pub transcription_ms: Option<i64>,
+    /// Time spent enhancing, fallbacks included.
+    pub enhancement_ms: Option<i64>,
+    pub paste_ms: Option<i64>,
+    /// From the end of the recording to the text being delivered.
+    pub total_ms: Option<i64>,
+    pub prompt_tokens: Option<i64>,
+    pub completion_tokens: Option<i64>,
+    /// Application that had focus when the text was delivered.
+    pub target_app: Option<String>,
+    /// Whether pasting worked, `None` when auto-paste is off.
+    pub paste_succeeded: Option<bool>,
+}
+
+/// Row to insert into the history table.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NewTranscription {
+    pub original_text: String,
+    pub processed_text: Option<String>,
+    pub processing_method: String,
+    pub agent_name: Option<String>,
+    pub error: Option<String>,
+    pub transcription_provider: Option<String>,
+    pub reasoning_provider: Option<String>,
+    pub language: Option<String>,
+    pub segments: Vec<Segment>,
+    pub parent_id: Option<i64>,
+    pub metadata: DictationMetadata,
 }
 
+/// A row's kept recording, as the retention policy sees it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredAudio {
+    pub id: i64,
+    /// Compressed size in bytes.
+    pub size: i64,
+    /// Whether transcription or enhancement failed.
+    pub failed: bool,
+    pub age_days: f64,
+}
+
+/// A provider's model list as last fetched, see `models::catalog`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedModels {
+    pub models: Vec<String>,
+    /// Hash of the key and configuration the list was fetched with.
+    pub fingerprint: String,
+    /// Unix time in seconds.
+    pub fetched_at: i64,
+}
+
+const TRANSCRIPTION_COLUMNS: &str = "id, timestamp, original_text, processed_text, is_processed, processing_method,
+    agent_name, error, transcription_provider, reasoning_provider, language, segments, audio_size, parent_id,
+    audio_duration_ms, input_device, transcription_model, requested_language, reasoning_model, transcription_ms,
+    enhancement_ms, paste_ms, total_ms, prompt_tokens, completion_tokens, target_app, paste_succeeded";
+
 /// Initialize the database and store it in Tauri's managed state
 pub fn init(app: &AppHandle) -> Result<()> {
     let db_path = get_db_path(app)?;
     let conn = Connection::open(&db_path)
         .with_context(|| format!("Failed to open database at {}", db_path.display()))?;
 
+    migrations::backup(&conn, &db_path)?;
     migrations::run(&conn)?;
 
     app.manage(Database {
@@ -49,56 +139,84 @@ fn get_db_path(app: &AppHandle) -> Result<PathBuf> {
 }
 
 impl Database {
-    pub fn save_transcription(
-        &self,
-        original_text: &str,
-        processed_text: Option<&str>,
-        processing_method: &str,
-        agent_name: Option<&str>,
-        error: Option<&str>,
-    ) -> Result<i64> {
+    pub fn save_transcription(&self, record: &NewTranscription) -> Result<i64> {
+        let segments = if record.segments.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_string(&record.segments)?)
+        };
+        let metadata = &record.metadata;
         let conn = self.conn.lock().unwrap();
         conn.execute(
-            "INSERT INTO transcriptions (original_text, processed_text, is_processed, processing_method, agent_name, error)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            "INSERT INTO transcriptions (original_text, processed_text, is_processed, processing_method, agent_name, error,
+                                         transcription_provider, reasoning_provider, language, segments, parent_id,
+                                         audio_duration_ms, input_device, transcription_model, requested_language,
+                                         reasoning_model, transcription_ms, enhancement_ms, paste_ms, total_ms,
+                                         prompt_tokens, completion_tokens, target_app, paste_succeeded)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22,
+                     ?23, ?24)",
             rusqlite::params![
-                original_text,
-                processed_text,
-                processed_text.is_some(),
-                processing_method,
-                agent_name,
-                error,
+                record.original_text,
+                record.processed_text,
+                record.processed_text.is_some(),
+                record.processing_method,
+                record.agent_name,
+                record.error,
+                record.transcription_provider,
+                record.reasoning_provider,
+                record.language,
+                segments,
+                record.parent_id,
+                metadata.audio_duration_ms,
+                metadata.input_device,
+                metadata.transcription_model,
+                metadata.requested_language,
+                metadata.reasoning_model,
+                metadata.transcription_ms,
+                metadata.enhancement_ms,
+                metadata.paste_ms,
+                metadata.total_ms,
+                metadata.prompt_tokens,
+                metadata.completion_tokens,
+                metadata.target_app,
+                metadata.paste_succeeded,
             ],
         )?;
         Ok(conn.last_insert_rowid())
     }
 
+    /// Dictations, newest first. Revisions are listed by [`Self::get_revisions`].
     pub fn get_transcriptions(&self, limit: u32, offset: u32) -> Result<Vec<Transcription>> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
-            "SELECT id, timestamp, original_text, processed_text, is_processed, processing_method, agent_name, error
-             FROM transcriptions ORDER BY id DESC LIMIT ?1 OFFSET ?2",
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM transcriptions WHERE parent_id IS NULL ORDER BY id DESC LIMIT ?1 OFFSET ?2",
+            TRANSCRIPTION_COLUMNS
+        ))?;
+        let rows = stmt.query_map(rusqlite::params![limit, offset], read_transcription)?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
 
-        let rows = stmt.query_map(rusqlite::params![limit, offset], |row| {
-            Ok(Transcription {
-                id: row.get(0)?,
-                timestamp: row.get(1)?,
-                original_text: row.get(2)?,
-                processed_text: row.get(3)?,
-                is_processed: row.get(4)?,
-                processing_method: row.get(5)?,
-                agent_name: row.get(6)?,
-                error: row.get(7)?,
-            })
-        })?;
+    pub fn get_transcription(&self, id: i64) -> Result<Option<Transcription>> {
+        let conn = self.conn.lock().unwrap();
+        let sql = format!("SELECT {} FROM transcriptions WHERE id = ?1", TRANSCRIPTION_COLUMNS);
+        Ok(conn.query_row(&sql, [id], read_transcription).optional()?)
+    }
 
+    /// Dictation `id` followed by its revisions, oldest first.
+    pub fn get_revisions(&self, id: i64) -> Result<Vec<Transcription>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM transcriptions WHERE id = ?1 OR parent_id = ?1 ORDER BY id",
+            TRANSCRIPTION_COLUMNS
+        ))?;
+        let rows = stmt.query_map([id], read_transcription)?;
         rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
     }
 
+    /// Delete a row along with its revisions.
     pub fn delete_transcription(&self, id: i64) -> Result<()> {
         let conn = self.conn.lock().unwrap();
-        conn.execute("DELETE FROM transcriptions WHERE id = ?1", [id])?;
+        conn.execute("DELETE FROM transcriptions WHERE id = ?1 OR parent_id = ?1", [id])?;
         Ok(())
     }
 
@@ -107,4 +225,237 @@ impl Database {
         conn.execute("DELETE FROM transcriptions", [])?;
         Ok(())
     }
+
+    /// Link (or with `None`, unlink) the kept recording of row `id`.
+    pub fn set_audio_size(&self, id: i64, size: Option<i64>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("UPDATE transcriptions SET audio_size = ?2 WHERE id = ?1", rusqlite::params![id, size])?;
+        Ok(())
+    }
+
+    pub fn clear_audio(&self) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("UPDATE transcriptions SET audio_size = NULL WHERE audio_size IS NOT NULL", [])?;
+        Ok(())
+    }
+
+    /// Rows with a kept recording, newest first.
+    pub fn get_stored_audio(&self) -> Result<Vec<StoredAudio>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, audio_size, error IS NOT NULL OR paste_succeeded IS 0, julianday('now') - julianday(timestamp)
+             FROM transcriptions WHERE audio_size IS NOT NULL ORDER BY id DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(StoredAudio {
+                id: row.get(0)?,
+                size: row.get(1)?,
+                failed: row.get(2)?,
+                age_days: row.get(3)?,
+            })
+        })?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    pub fn get_cached_models(&self, provider: &str, purpose: &str) -> Result<Option<CachedModels>> {
+        let conn = self.conn.lock().unwrap();
+        let row = conn
+            .query_row(
+                "SELECT models, fingerprint, fetched_at FROM provider_models WHERE provider = ?1 AND purpose = ?2",
+                [provider, purpose],
+                |row| Ok((row.get::<_, String>(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?;
+        let Some((models, fingerprint, fetched_at)) = row else {
+            return Ok(None);
+        };
+        Ok(Some(CachedModels {
+            models: serde_json::from_str(&models).context("Corrupt cached model list")?,
+            fingerprint,
+            fetched_at,
+        }))
+    }
+
+    pub fn save_cached_models(&self, provider: &str, purpose: &str, cached: &CachedModels) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO provider_models (provider, purpose, models, fingerprint, fetched_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![
+                provider,
+                purpose,
+                serde_json::to_string(&cached.models)?,
+                cached.fingerprint,
+                cached.fetched_at,
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+fn read_transcription(row: &rusqlite::Row) -> rusqlite::Result<Transcription> {
+    Ok(Transcription {
+        id: row.get(0)?,
+        timestamp: row.get(1)?,
+        original_text: row.get(2)?,
+        processed_text: row.get(3)?,
+        is_processed: row.get(4)?,
+        processing_method: row.get(5)?,
+        agent_name: row.get(6)?,
+        error: row.get(7)?,
+        transcription_provider: row.get(8)?,
+        reasoning_provider: row.get(9)?,
+        language: row.get(10)?,
+        // Segments are an extra; an unreadable list shouldn't hide the row
+        segments: row
+            .get::<_, Option<String>>(11)?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+        audio_size: row.get(12)?,
+        parent_id: row.get(13)?,
+        metadata: DictationMetadata {
+            audio_duration_ms: row.get(14)?,
+            input_device: row.get(15)?,
+            transcription_model: row.get(16)?,
+            requested_language: row.get(17)?,
+            reasoning_model: row.get(18)?,
+            transcription_ms: row.get(19)?,
+            enhancement_ms: row.get(20)?,
+            paste_ms: row.get(21)?,
+            total_ms: row.get(22)?,
+            prompt_tokens: row.get(23)?,
+            completion_tokens: row.get(24)?,
+            target_app: row.get(25)?,
+            paste_succeeded: row.get(26)?,
+        },
+    })
+}
+
+#[cfg(test)]
+impl Database {
+    pub fn open_in_memory() -> Result<Self> {
+        let conn = Connection::open_in_memory()?;
+        migrations::run(&conn)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transcription_round_trips_segments() {
+        let db = Database::open_in_memory().unwrap();
+        let segment = Segment {
+            start: 0.5,
+            end: 1.75,
+            text: "hello".to_string(),
+            confidence: Some(0.9),
+            avg_logprob: Some(-0.1),
+        };
+        db.save_transcription(&NewTranscription {
+            original_text: "hello".to_string(),
+            language: Some("en".to_string()),
+            segments: vec![segment.clone()],
+            ..Default::default()
+        })
+        .unwrap();
+        db.save_transcription(&NewTranscription {
+            original_text: "plain".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let rows = db.get_transcriptions(10, 0).unwrap();
+        assert!(rows[0].segments.is_empty() && rows[0].language.is_none());
+        assert_eq!(rows[1].segments, [segment]);
+        assert_eq!(rows[1].language.as_deref(), Some("en"));
+    }
+
+    #[test]
+    fn test_transcription_round_trips_metadata() {
+        let db = Database::open_in_memory().unwrap();
+        let metadata = DictationMetadata {
+            audio_duration_ms: Some(4_250),
+            input_device: Some("USB Microphone".to_string()),
+            transcription_model: Some("whisper-large-v3".to_string()),
+            requested_language: Some("auto".to_string()),
+            reasoning_model: Some("gpt-5-mini".to_string()),
+            transcription_ms: Some(820),
+            enhancement_ms: Some(1_340),
+            paste_ms: Some(60),
+            total_ms: Some(2_230),
+            prompt_tokens: Some(412),
+            completion_tokens: Some(38),
+            target_app: Some("Code".to_string()),
+            paste_succeeded: Some(false),
+        };
+        let id = db
+            .save_transcription(&NewTranscription {
+                original_text: "hello".to_string(),
+                metadata: metadata.clone(),
+                ..Default::default()
+            })
+            .unwrap();
+        let plain = db.save_transcription(&NewTranscription::default()).unwrap();
+
+        let row = db.get_transcription(id).unwrap().unwrap();
+        assert_eq!(row.metadata, metadata);
+        assert_eq!(db.get_transcription(plain).unwrap().unwrap().metadata, DictationMetadata::default());
+        // Flattened into the row for the frontend
+        let json = serde_json::to_value(&row).unwrap();
+        assert_eq!(json["target_app"], "Code");
+        assert_eq!(json["paste_succeeded"], false);
+    }
+
+    #[test]
+    fn test_revisions_link_to_their_dictation() {
+        let db = Database::open_in_memory().unwrap();
+        let original = db.save_transcription(&NewTranscription::default()).unwrap();
+        let other = db.save_transcription(&NewTranscription::default()).unwrap();
+        let revision = db
+            .save_transcription(&NewTranscription {
+                original_text: "again".to_string(),
+                parent_id: Some(original),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let history: Vec<_> = db.get_transcriptions(10, 0).unwrap().iter().map(|t| t.id).collect();
+        assert_eq!(history, [other, original]);
+        let versions = db.get_revisions(original).unwrap();
+        assert_eq!(versions.iter().map(|t| t.id).collect::<Vec<_>>(), [original, revision]);
+        assert_eq!(versions[1].parent_id, Some(original));
+
+        db.delete_transcription(original).unwrap();
+        assert!(db.get_transcription(revision).unwrap().is_none());
+        assert!(db.get_transcription(other).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_stored_audio_lists_linked_rows() {
+        let db = Database::open_in_memory().unwrap();
+        let ok = db.save_transcription(&NewTranscription::default()).unwrap();
+        let failed = db
+            .save_transcription(&NewTranscription {
+                error: Some("timed out".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        db.save_transcription(&NewTranscription::default()).unwrap();
+        db.set_audio_size(ok, Some(100)).unwrap();
+        db.set_audio_size(failed, Some(200)).unwrap();
+
+        let stored = db.get_stored_audio().unwrap();
+        let linked: Vec<_> = stored.iter().map(|a| (a.id, a.size, a.failed)).collect();
+        assert_eq!(linked, [(failed, 200, true), (ok, 100, false)]);
+        assert!(stored[0].age_days < 1.0);
+        assert_eq!(db.get_transcriptions(1, 2).unwrap()[0].audio_size, Some(100));
+
+        db.clear_audio().unwrap();
+        assert!(db.get_stored_audio().unwrap().is_empty());
+    }
 }
@@ -0,0 +1,262 @@
+//! Full-text search over the history, backed by the `transcriptions_fts`
+//! FTS5 index that triggers keep in sync with `transcriptions`.
+
+use anyhow::Result;
+use rusqlite::types::Value;
+use serde::{Deserialize, Serialize};
+
+use super::{Database, TRANSCRIPTION_COLUMNS, Transcription, read_transcription};
+
+/// Marks a matched term in the raw snippet; control characters can't clash with dictated text.
+const MATCH_START: &str = "\u{2}";
+const MATCH_END: &str = "\u{3}";
+/// Tokens of context in a snippet.
+const SNIPPET_TOKENS: u32 = 16;
+const DEFAULT_LIMIT: u32 = 50;
+
+/// Narrows a search; unset fields don't filter.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchFilters {
+    /// Earliest timestamp, UTC (`2025-06-01` or `2025-06-01 18:00:00`).
+    pub from: Option<String>,
+    /// Latest timestamp, UTC; a bare date includes the whole day.
+    pub to: Option<String>,
+    /// `"ai"` or `"none"`.
+    pub processing_method: Option<String>,
+    pub agent_name: Option<String>,
+    /// Only rows where transcription or enhancement failed.
+    pub errors_only: bool,
+    /// Transcription or enhancement provider id.
+    pub provider: Option<String>,
+    pub limit: Option<u32>,
+    pub offset: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub transcription: Transcription,
+    /// Best-matching passage with the matched terms marked; empty without a query.
+    pub snippet: Vec<SnippetPart>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SnippetPart {
+    pub text: String,
+    pub matched: bool,
+}
+
+impl Database {
+    /// Rows whose original or processed text contains every word of `query`,
+    /// the last word as a prefix, best match first. An empty query lists the
+    /// rows the filters allow, newest first. Revisions are left out, as in
+    /// [`Database::get_transcriptions`].
+    pub fn search_transcriptions(&self, query: &str, filters: &SearchFilters) -> Result<Vec<SearchHit>> {
+        let mut clauses = Vec::new();
+        let mut params = Vec::new();
+        let mut filter = |clause: &str, value: &Option<String>| {
+            if let Some(value) = value.as_ref().filter(|v| !v.is_empty()) {
+                clauses.push(clause.to_string());
+                params.push(Value::Text(value.clone()));
+            }
+        };
+        filter("timestamp >= datetime(?)", &filters.from);
+        let to = filters.to.as_ref().map(|to| match to.len() {
+            10 => format!("{} 23:59:59", to),
+            _ => to.clone(),
+        });
+        filter("timestamp <= datetime(?)", &to);
+        filter("processing_method = ?", &filters.processing_method);
+        filter("agent_name = ?", &filters.agent_name);
+        if let Some(provider) = filters.provider.as_ref().filter(|p| !p.is_empty()) {
+            clauses.push("(transcription_provider = ? OR reasoning_provider = ?)".to_string());
+            params.extend([Value::Text(provider.clone()), Value::Text(provider.clone())]);
+        }
+        if filters.errors_only {
+            clauses.push("error IS NOT NULL".to_string());
+        }
+        let conditions = clauses.iter().map(|c| format!(" AND {}", c)).collect::<String>();
+        params.push(Value::Integer(filters.limit.unwrap_or(DEFAULT_LIMIT) as i64));
+        params.push(Value::Integer(filters.offset as i64));
+
+        let sql = match match_expression(query) {
+            Some(expression) => {
+                params.insert(0, Value::Text(expression));
+                format!(
+                    "WITH hits AS (
+                        SELECT rowid, rank, snippet(transcriptions_fts, -1, '{}', '{}', '…', {}) AS snippet
+                        FROM transcriptions_fts WHERE transcriptions_fts MATCH ?
+                     )
+                     SELECT {}, hits.snippet FROM transcriptions JOIN hits ON hits.rowid = transcriptions.id
+                     WHERE parent_id IS NULL{} ORDER BY hits.rank LIMIT ? OFFSET ?",
+                    MATCH_START, MATCH_END, SNIPPET_TOKENS, TRANSCRIPTION_COLUMNS, conditions
+                )
+            }
+            None => format!(
+                "SELECT {}, NULL FROM transcriptions WHERE parent_id IS NULL{} ORDER BY id DESC LIMIT ? OFFSET ?",
+                TRANSCRIPTION_COLUMNS, conditions
+            ),
+        };
+
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(params), |row| {
+            // The snippet follows the transcription's columns
+            let snippet: Option<String> = row.get(row.as_ref().column_count() - 1)?;
+            Ok(SearchHit {
+                transcription: read_transcription(row)?,
+                snippet: snippet.as_deref().map(snippet_parts).unwrap_or_default(),
+            })
+        })?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+}
+
+/// FTS5 query matching every word of `query`, quoted so user input is never
+/// read as query syntax. The last word matches as a prefix, for search as you type.
+fn match_expression(query: &str) -> Option<String> {
+    let words: Vec<String> = query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect();
+    (!words.is_empty()).then(|| words.join(" ") + "*")
+}
+
+fn snippet_parts(snippet: &str) -> Vec<SnippetPart> {
+    let mut parts = Vec::new();
+    for (i, piece) in snippet.split(MATCH_START).enumerate() {
+        // Every piece after the first opens with a match
+        let (matched, rest) = match piece.split_once(MATCH_END) {
+            Some((matched, rest)) if i > 0 => (Some(matched), rest),
+            _ => (None, piece),
+        };
+        for (text, matched) in [(matched.unwrap_or_default(), true), (rest, false)] {
+            if !text.is_empty() {
+                parts.push(SnippetPart {
+                    text: text.to_string(),
+                    matched,
+                });
+            }
+        }
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::NewTranscription;
+
+    fn save(db: &Database, original: &str, processed: Option<&str>, provider: &str, error: Option<&str>) -> i64 {
+        db.save_transcription(&NewTranscription {
+            original_text: original.to_string(),
+            processed_text: processed.map(str::to_string),
+            processing_method: if processed.is_some() { "ai" } else { "none" }.to_string(),
+            transcription_provider: Some(provider.to_string()),
+            error: error.map(str::to_string),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn ids(hits: &[SearchHit]) -> Vec<i64> {
+        hits.iter().map(|h| h.transcription.id).collect()
+    }
+
+    #[test]
+    fn test_search_matches_both_texts_with_snippets() {
+        let db = Database::open_in_memory().unwrap();
+        let meeting = save(&db, "schedule the meeting for friday", Some("Schedule the meeting for Friday."), "groq", None);
+        let cafe = save(&db, "meet me at the café", None, "openai", Some("rate limited"));
+        let none = SearchFilters::default();
+
+        assert_eq!(ids(&db.search_transcriptions("friday", &none).unwrap()), [meeting]);
+        assert_eq!(ids(&db.search_transcriptions("mee", &none).unwrap()).len(), 2, "last word is a prefix");
+        assert_eq!(ids(&db.search_transcriptions("cafe", &none).unwrap()), [cafe], "diacritics ignored");
+        assert!(db.search_transcriptions("meeting cafe", &none).unwrap().is_empty());
+        assert!(db.search_transcriptions("\"AND (* -", &none).unwrap().is_empty());
+
+        let hit = db.search_transcriptions("friday", &none).unwrap().remove(0);
+        let matched: Vec<_> = hit.snippet.iter().filter(|p| p.matched).map(|p| p.text.as_str()).collect();
+        assert_eq!(matched.len(), 1);
+        assert!(matched[0].eq_ignore_ascii_case("friday"));
+
+        let listed = db.search_transcriptions("  ", &none).unwrap();
+        assert_eq!(ids(&listed), [cafe, meeting]);
+        assert!(listed[0].snippet.is_empty());
+    }
+
+    #[test]
+    fn test_search_filters_and_stays_in_sync() {
+        let db = Database::open_in_memory().unwrap();
+        let first = save(&db, "first note", Some("First note."), "groq", None);
+        let failed = save(&db, "second note", None, "openai", Some("timed out"));
+        let filtered = |filters: SearchFilters| ids(&db.search_transcriptions("note", &filters).unwrap());
+
+        let errors_only = SearchFilters {
+            errors_only: true,
+            ..Default::default()
+        };
+        assert_eq!(filtered(errors_only), [failed]);
+        let groq = SearchFilters {
+            provider: Some("groq".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(filtered(groq), [first]);
+        let ai = SearchFilters {
+            processing_method: Some("ai".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(filtered(ai), [first]);
+        let today = db.get_transcription(first).unwrap().unwrap().timestamp[..10].to_string();
+        let today_only = SearchFilters {
+            from: Some(today.clone()),
+            to: Some(today),
+            ..Default::default()
+        };
+        assert_eq!(filtered(today_only).len(), 2);
+        let future = SearchFilters {
+            from: Some("2999-01-01".to_string()),
+            ..Default::default()
+        };
+        assert!(filtered(future).is_empty());
+
+        db.delete_transcription(first).unwrap();
+        assert_eq!(filtered(SearchFilters::default()), [failed]);
+        db.clear_transcriptions().unwrap();
+        assert!(filtered(SearchFilters::default()).is_empty());
+    }
+
+    #[test]
+    fn test_search_lists_dictations_not_revisions() {
+        let db = Database::open_in_memory().unwrap();
+        let original = save(&db, "call the dentist", None, "groq", None);
+        db.save_transcription(&NewTranscription {
+            original_text: "call the dentist".to_string(),
+            processed_text: Some("Call the dentist.".to_string()),
+            processing_method: "ai".to_string(),
+            parent_id: Some(original),
+            ..Default::default()
+        })
+        .unwrap();
+        let none = SearchFilters::default();
+
+        assert_eq!(ids(&db.search_transcriptions("dentist", &none).unwrap()), [original]);
+        assert_eq!(ids(&db.search_transcriptions("", &none).unwrap()), [original]);
+    }
+
+    #[test]
+    fn test_snippet_parts_split_marked_terms() {
+        let parts = snippet_parts("…see \u{2}you\u{3} on \u{2}Friday\u{3}");
+        let plain = |text: &str| SnippetPart {
+            text: text.to_string(),
+            matched: false,
+        };
+        let marked = |text: &str| SnippetPart {
+            text: text.to_string(),
+            matched: true,
+        };
+        assert_eq!(parts, [plain("…see "), marked("you"), plain(" on "), marked("Friday")]);
+    }
+}
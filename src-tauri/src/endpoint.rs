@@ -0,0 +1,155 @@
+//! User-defined OpenAI-compatible endpoints (self-hosted faster-whisper,
+//! vLLM, LiteLLM, ...), selected with the `custom` provider id for either
+//! transcription or reasoning.
+
+use anyhow::{Context, Result};
+use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::http;
+use crate::provider::{ListedModel, ProviderError};
+
+/// Provider id that routes a request to the configured [`CustomEndpoint`].
+pub const CUSTOM_PROVIDER: &str = "custom";
+
+/// How the API key is sent to a custom endpoint.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthScheme {
+    /// `Authorization: Bearer <key>`
+    #[default]
+    Bearer,
+    /// `<auth_header>: <key>`, e.g. `api-key` or `X-API-Key`
+    Header,
+    /// No credentials
+    None,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CustomEndpoint {
+    /// API root including the version segment, e.g. `http://gpu-box:8000/v1`.
+    pub base_url: String,
+    pub auth_scheme: AuthScheme,
+    /// Header name used with [`AuthScheme::Header`].
+    pub auth_header: String,
+    /// Sent with every request.
+    pub extra_headers: BTreeMap<String, String>,
+    pub model: String,
+}
+
+impl CustomEndpoint {
+    /// `base_url` joined with an API path such as `chat/completions`.
+    pub fn url(&self, path: &str) -> Result<String> {
+        let base = self.base_url.trim().trim_end_matches('/');
+        if base.is_empty() {
+            anyhow::bail!("Custom endpoint has no base URL. Set it in Settings.");
+        }
+        let parsed = reqwest::Url::parse(base).with_context(|| format!("Invalid custom endpoint URL: {}", base))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            anyhow::bail!("Custom endpoint URL must be http or https: {}", base);
+        }
+        Ok(format!("{}/{}", base, path.trim_start_matches('/')))
+    }
+
+    /// The auth and extra headers to send. An empty `api_key` sends no
+    /// credentials, for servers that don't require any.
+    pub fn headers(&self, api_key: Option<&str>) -> Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.extra_headers {
+            let name = HeaderName::from_bytes(name.trim().as_bytes())
+                .with_context(|| format!("Invalid header name: {}", name))?;
+            let value = HeaderValue::from_str(value.trim())
+                .with_context(|| format!("Invalid value for header {}", name))?;
+            headers.insert(name, value);
+        }
+
+        let Some(api_key) = api_key.filter(|k| !k.is_empty()) else {
+            return Ok(headers);
+        };
+        let name = match self.auth_scheme {
+            AuthScheme::Bearer => AUTHORIZATION,
+            AuthScheme::Header => HeaderName::from_bytes(self.auth_header.trim().as_bytes())
+                .with_context(|| format!("Invalid auth header name: '{}'", self.auth_header))?,
+            AuthScheme::None => return Ok(headers),
+        };
+        let value = match self.auth_scheme {
+            AuthScheme::Bearer => format!("Bearer {}", api_key),
+            _ => api_key.to_string(),
+        };
+        let mut value = HeaderValue::from_str(&value).context("API key contains invalid characters")?;
+        value.set_sensitive(true);
+        headers.insert(name, value);
+        Ok(headers)
+    }
+
+    /// Models from the endpoint's `/models` list.
+    pub async fn list_models(&self, api_key: Option<&str>) -> Result<Vec<ListedModel>, ProviderError> {
+        let config_error = |e: anyhow::Error| ProviderError::config(CUSTOM_PROVIDER, format!("{:#}", e));
+        let url = self.url("models").map_err(config_error)?;
+        let headers = self.headers(api_key).map_err(config_error)?;
+        http::list_models("Custom endpoint", || crate::HTTP_CLIENT.get(&url).headers(headers.clone())).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(auth_scheme: AuthScheme) -> CustomEndpoint {
+        CustomEndpoint {
+            base_url: "http://gpu-box:8000/v1/".to_string(),
+            auth_scheme,
+            auth_header: "X-API-Key".to_string(),
+            extra_headers: BTreeMap::from([("X-Team".to_string(), "speech".to_string())]),
+            model: "large-v3".to_string(),
+        }
+    }
+
+    fn headers(endpoint: &CustomEndpoint, api_key: Option<&str>) -> HeaderMap {
+        endpoint.headers(api_key).unwrap()
+    }
+
+    #[test]
+    fn test_url_joins_paths_and_rejects_bad_bases() {
+        let custom = endpoint(AuthScheme::Bearer);
+        assert_eq!(custom.url("/audio/transcriptions").unwrap(), "http://gpu-box:8000/v1/audio/transcriptions");
+        assert!(CustomEndpoint::default().url("models").is_err());
+        let ftp = CustomEndpoint {
+            base_url: "ftp://host".to_string(),
+            ..Default::default()
+        };
+        assert!(ftp.url("models").is_err());
+    }
+
+    #[test]
+    fn test_headers_apply_auth_scheme_and_extra_headers() {
+        let bearer = headers(&endpoint(AuthScheme::Bearer), Some("sk-1"));
+        assert_eq!(bearer["authorization"], "Bearer sk-1");
+        assert_eq!(bearer["x-team"], "speech");
+
+        let header = headers(&endpoint(AuthScheme::Header), Some("sk-1"));
+        assert_eq!(header["x-api-key"], "sk-1");
+        assert!(header.get("authorization").is_none());
+
+        let none = headers(&endpoint(AuthScheme::None), Some("sk-1"));
+        assert!(none.get("authorization").is_none() && none.get("x-api-key").is_none());
+
+        let keyless = headers(&endpoint(AuthScheme::Bearer), Some(""));
+        assert!(keyless.get("authorization").is_none());
+    }
+
+    #[test]
+    fn test_deserializes_settings_shape() {
+        let custom: CustomEndpoint = serde_json::from_value(serde_json::json!({
+            "baseUrl": "https://llm.example.com/v1",
+            "authScheme": "header",
+            "authHeader": "api-key",
+            "model": "qwen3-32b"
+        }))
+        .unwrap();
+        assert_eq!(custom.auth_scheme, AuthScheme::Header);
+        assert!(custom.extra_headers.is_empty());
+    }
+}
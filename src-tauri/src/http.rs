@@ -0,0 +1,507 @@
+//! Shared HTTP layer for provider APIs. Requests go out over
+//! [`crate::HTTP_CLIENT`]; failures are classified into [`ProviderError`] and
+//! the ones that can't have been processed (rate limits, 5xx, failed
+//! connections) are retried with jittered exponential backoff, honouring
+//! `Retry-After`. Streaming responses are read as server-sent events.
+
+use futures_util::StreamExt;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+
+use crate::provider::{ListedModel, ProviderError};
+
+/// Event emitted before each retry so the UI can say what is happening.
+pub const RETRY_EVENT: &str = "provider-retry";
+
+/// Total attempts per request, including the first.
+const MAX_ATTEMPTS: u32 = 3;
+/// Backoff ceiling for the first retry; doubled after every failed attempt.
+const RETRY_BASE_DELAY: Duration = if cfg!(test) {
+    Duration::from_millis(10)
+} else {
+    Duration::from_millis(800)
+};
+/// Longest wait worth sitting through mid-dictation. A `Retry-After` beyond
+/// this fails immediately so a fallback provider can take over.
+const MAX_RETRY_DELAY: Duration = if cfg!(test) {
+    Duration::from_millis(200)
+} else {
+    Duration::from_secs(10)
+};
+/// Per-request timeout; long recordings on multimodal models can take a while.
+const REQUEST_TIMEOUT: Duration = if cfg!(test) {
+    Duration::from_millis(500)
+} else {
+    Duration::from_secs(120)
+};
+/// Longest silence on a streaming response, before its headers or between
+/// chunks. A stream has no total timeout since it runs as long as the model writes.
+const STREAM_IDLE_TIMEOUT: Duration = if cfg!(test) {
+    Duration::from_millis(500)
+} else {
+    Duration::from_secs(60)
+};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryNotice {
+    pub provider: String,
+    /// The attempt that just failed, starting at 1.
+    pub attempt: u32,
+    pub delay_ms: u64,
+    pub message: String,
+}
+
+type RetryListener = Box<dyn Fn(&RetryNotice) + Send + Sync>;
+
+static RETRY_LISTENER: OnceLock<RetryListener> = OnceLock::new();
+
+/// Register the callback told about every retry (set once at startup).
+pub fn set_retry_listener(listener: impl Fn(&RetryNotice) + Send + Sync + 'static) {
+    if RETRY_LISTENER.set(Box::new(listener)).is_err() {
+        log::warn!("[Whisperi] Retry listener already set");
+    }
+}
+
+/// Send the request produced by `build`, retrying transient failures. `build`
+/// is called once per attempt because multipart bodies can't be cloned.
+pub async fn send(provider: &str, build: impl FnMut() -> RequestBuilder) -> Result<Response, ProviderError> {
+    send_with(provider, build, false).await
+}
+
+/// [`send`], bounding either the whole exchange or, for a `streaming`
+/// response, only the wait for its headers.
+async fn send_with(
+    provider: &str,
+    mut build: impl FnMut() -> RequestBuilder,
+    streaming: bool,
+) -> Result<Response, ProviderError> {
+    let mut attempt = 1;
+    loop {
+        let sent = if streaming {
+            tokio::time::timeout(STREAM_IDLE_TIMEOUT, build().send()).await
+        } else {
+            Ok(build().timeout(REQUEST_TIMEOUT).send().await)
+        };
+        let error = match sent {
+            Ok(Ok(response)) if response.status().is_success() => return Ok(response),
+            Ok(Ok(response)) => from_response(provider, response).await,
+            Ok(Err(e)) => from_reqwest(provider, e),
+            Err(_) => ProviderError::Timeout {
+                provider: provider.to_string(),
+            },
+        };
+        let Some(delay) = retry_delay(&error, attempt) else {
+            return Err(error);
+        };
+
+        let notice = RetryNotice {
+            provider: provider.to_string(),
+            attempt,
+            delay_ms: delay.as_millis() as u64,
+            message: format!("{}, retrying in {}s", error, delay.as_secs_f32().ceil()),
+        };
+        log::warn!("[Whisperi] {} (attempt {}/{})", notice.message, attempt, MAX_ATTEMPTS);
+        if let Some(listener) = RETRY_LISTENER.get() {
+            listener(&notice);
+        }
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// [`send`] and decode the JSON success body.
+pub async fn send_json<T: DeserializeOwned>(
+    provider: &str,
+    build: impl FnMut() -> RequestBuilder,
+) -> Result<T, ProviderError> {
+    let response = send(provider, build).await?;
+    let body = response.bytes().await.map_err(|e| from_reqwest(provider, e))?;
+    serde_json::from_slice(&body).map_err(|e| ProviderError::InvalidResponse {
+        provider: provider.to_string(),
+        message: e.to_string(),
+    })
+}
+
+/// [`send`] a streaming request and pass the JSON `data` of each server-sent
+/// event to `on_event`, until OpenAI's `[DONE]` sentinel or the end of the
+/// body. Only the request itself is retried; a stream that breaks midway
+/// fails, since its events were already handed out.
+pub async fn send_sse<T: DeserializeOwned>(
+    provider: &str,
+    build: impl FnMut() -> RequestBuilder,
+    mut on_event: impl FnMut(T) -> Result<(), ProviderError>,
+) -> Result<(), ProviderError> {
+    let response = send_with(provider, build, true).await?;
+    let mut body = response.bytes_stream();
+    let mut parser = SseParser::default();
+    let mut handle = |data: String| -> Result<bool, ProviderError> {
+        if data == "[DONE]" {
+            return Ok(false);
+        }
+        let event = serde_json::from_str(&data).map_err(|e| ProviderError::InvalidResponse {
+            provider: provider.to_string(),
+            message: e.to_string(),
+        })?;
+        on_event(event)?;
+        Ok(true)
+    };
+
+    loop {
+        let next = tokio::time::timeout(STREAM_IDLE_TIMEOUT, body.next()).await;
+        let Ok(next) = next else {
+            return Err(ProviderError::Timeout {
+                provider: provider.to_string(),
+            });
+        };
+        let Some(chunk) = next else { break };
+        let chunk = chunk.map_err(|e| from_reqwest(provider, e))?;
+        for data in parser.push(&chunk) {
+            if !handle(data)? {
+                return Ok(());
+            }
+        }
+    }
+    if let Some(data) = parser.finish() {
+        handle(data)?;
+    }
+    Ok(())
+}
+
+/// Splits a server-sent event stream into the `data` of each event. Other
+/// fields (`event:`, `id:`, comments) are ignored; every API used here
+/// repeats the event type inside the JSON.
+#[derive(Default)]
+struct SseParser {
+    buffer: Vec<u8>,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        // Lines are decoded whole, so a character split across chunks survives
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+            if line.is_empty() {
+                events.extend(self.take());
+            } else if let Some(value) = line.strip_prefix("data:") {
+                self.data.push(value.strip_prefix(' ').unwrap_or(value).to_string());
+            }
+        }
+        events
+    }
+
+    /// The last event, when the body ended without a blank line.
+    fn finish(&mut self) -> Option<String> {
+        let rest = std::mem::take(&mut self.buffer);
+        let rest = String::from_utf8_lossy(&rest);
+        if let Some(value) = rest.trim_end().strip_prefix("data:") {
+            self.data.push(value.trim_start().to_string());
+        }
+        self.take()
+    }
+
+    fn take(&mut self) -> Option<String> {
+        (!self.data.is_empty()).then(|| std::mem::take(&mut self.data).join("\n"))
+    }
+}
+
+#[derive(Deserialize)]
+struct ModelList {
+    data: Vec<ModelEntry>,
+}
+
+#[derive(Deserialize)]
+struct ModelEntry {
+    id: String,
+    /// OpenRouter describes each model's modalities.
+    #[serde(default)]
+    architecture: Option<Architecture>,
+}
+
+#[derive(Deserialize)]
+struct Architecture {
+    #[serde(default)]
+    input_modalities: Vec<String>,
+}
+
+/// [`send`] a request for an OpenAI-style `/models` list.
+pub async fn list_models(
+    provider: &str,
+    build: impl FnMut() -> RequestBuilder,
+) -> Result<Vec<ListedModel>, ProviderError> {
+    let list: ModelList = send_json(provider, build).await?;
+    let models = list.data.into_iter().map(|m| ListedModel {
+        id: m.id,
+        inputs: m.architecture.map(|a| a.input_modalities).unwrap_or_default(),
+    });
+    Ok(models.collect())
+}
+
+/// Models at `{base_url}/models` for a bearer key. OpenRouter lists its
+/// models without authentication, so the key is checked at `/key` first.
+pub async fn list_bearer_models(
+    provider: &str,
+    base_url: &str,
+    api_key: &str,
+) -> Result<Vec<ListedModel>, ProviderError> {
+    if base_url.contains("openrouter.ai") {
+        let url = format!("{}/key", base_url);
+        send(provider, || crate::HTTP_CLIENT.get(&url).bearer_auth(api_key)).await?;
+    }
+    let url = format!("{}/models", base_url);
+    list_models(provider, || crate::HTTP_CLIENT.get(&url).bearer_auth(api_key)).await
+}
+
+/// Classify a non-success response, consuming its body for the log.
+pub async fn from_response(provider: &str, response: Response) -> ProviderError {
+    let status = response.status();
+    let retry_after = retry_after(response.headers());
+    let body = response.text().await.unwrap_or_default();
+    log::error!("[Whisperi] {} API error ({}): {}", provider, status, body);
+    classify(provider, status, retry_after, body)
+}
+
+pub fn classify(provider: &str, status: StatusCode, retry_after: Option<Duration>, body: String) -> ProviderError {
+    let provider = provider.to_string();
+    let lower = body.to_ascii_lowercase();
+    let quota = ["insufficient_quota", "billing", "credit"].iter().any(|k| lower.contains(k));
+    match status.as_u16() {
+        401 | 403 => ProviderError::Unauthorized { provider, status },
+        402 => ProviderError::QuotaExceeded { provider },
+        429 if quota => ProviderError::QuotaExceeded { provider },
+        429 => ProviderError::RateLimited { provider, retry_after },
+        408 | 504 => ProviderError::Timeout { provider },
+        // 529 is Anthropic's "overloaded"
+        500..=599 => ProviderError::Server {
+            provider,
+            status,
+            retry_after,
+        },
+        _ => ProviderError::Api { provider, status, body },
+    }
+}
+
+fn from_reqwest(provider: &str, source: reqwest::Error) -> ProviderError {
+    let provider = provider.to_string();
+    if source.is_timeout() {
+        ProviderError::Timeout { provider }
+    } else {
+        ProviderError::Network { provider, source }
+    }
+}
+
+fn retry_delay(error: &ProviderError, attempt: u32) -> Option<Duration> {
+    if attempt >= MAX_ATTEMPTS || !is_resendable(error) {
+        return None;
+    }
+    match error.retry_after() {
+        Some(wait) if wait > MAX_RETRY_DELAY => None,
+        Some(wait) => Some(wait),
+        None => Some(backoff(attempt)),
+    }
+}
+
+/// Whether sending the request again can't repeat work: the provider turned
+/// it away with a retryable status, or it never reached the provider. A
+/// timeout or a connection dropped mid-request may come after the provider
+/// processed (and billed) it, so those go to the fallback provider instead.
+fn is_resendable(error: &ProviderError) -> bool {
+    match error {
+        ProviderError::RateLimited { .. } | ProviderError::Server { .. } => true,
+        ProviderError::Network { source, .. } => source.is_connect(),
+        _ => false,
+    }
+}
+
+/// A random delay between half and all of a ceiling that doubles per attempt,
+/// so clients rate-limited together don't retry in lockstep.
+fn backoff(attempt: u32) -> Duration {
+    let ceiling = (RETRY_BASE_DELAY * 2u32.pow(attempt - 1)).min(MAX_RETRY_DELAY);
+    let half = ceiling / 2;
+    half + half.mul_f64(fastrand::f64())
+}
+
+/// `retry-after-ms` (OpenAI) or `Retry-After` in seconds or as an HTTP date.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name| headers.get(name).and_then(|v| v.to_str().ok()).map(str::trim);
+    if let Some(ms) = header("retry-after-ms").and_then(|v| v.parse::<u64>().ok()) {
+        return Some(Duration::from_millis(ms));
+    }
+    let value = header(RETRY_AFTER.as_str())?;
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(date.duration_since(SystemTime::now()).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_classify() {
+        let class = |status: u16, body: &str| classify("Groq", StatusCode::from_u16(status).unwrap(), None, body.into());
+
+        let auth = class(401, "");
+        assert_eq!(auth.to_string(), "Your Groq API key is invalid or not authorized (401 Unauthorized)");
+        assert!(!auth.is_retryable());
+        assert!(matches!(class(429, "{\"code\":\"insufficient_quota\"}"), ProviderError::QuotaExceeded { .. }));
+        assert!(class(429, "slow down").is_retryable());
+        assert!(class(503, "").is_retryable() && class(529, "").is_retryable());
+        assert!(matches!(class(504, ""), ProviderError::Timeout { .. }));
+        assert!(!class(400, "bad model").is_retryable());
+    }
+
+    #[test]
+    fn test_retry_after_header_forms() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("4"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(4)));
+
+        let later = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(30));
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(&later).unwrap());
+        let wait = retry_after(&headers).unwrap();
+        assert!(wait > Duration::from_secs(25) && wait <= Duration::from_secs(30));
+
+        headers.insert("retry-after-ms", HeaderValue::from_static("1500"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_millis(1500)));
+    }
+
+    #[test]
+    fn test_backoff_is_jittered_and_capped() {
+        for attempt in 1..=8 {
+            let ceiling = (RETRY_BASE_DELAY * 2u32.pow(attempt - 1)).min(MAX_RETRY_DELAY);
+            let delay = backoff(attempt);
+            assert!(delay >= ceiling / 2 && delay <= ceiling, "attempt {}: {:?}", attempt, delay);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_retries_transient_failures() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/flaky"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after-ms", "20"))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/flaky"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/flaky"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "text": "hi" })))
+            .mount(&server)
+            .await;
+
+        let url = format!("{}/flaky", server.uri());
+        let body: serde_json::Value = send_json("Groq", || crate::HTTP_CLIENT.post(&url)).await.unwrap();
+        assert_eq!(body["text"], "hi");
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_send_fails_fast_on_auth_and_long_retry_after() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/auth"))
+            .respond_with(ResponseTemplate::new(401).set_body_string("bad key"))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/busy"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "60"))
+            .mount(&server)
+            .await;
+
+        let auth = send("Groq", || crate::HTTP_CLIENT.post(format!("{}/auth", server.uri()))).await.unwrap_err();
+        assert!(matches!(auth, ProviderError::Unauthorized { .. }));
+        let busy = send("Groq", || crate::HTTP_CLIENT.post(format!("{}/busy", server.uri()))).await.unwrap_err();
+        assert_eq!(busy.retry_after(), Some(Duration::from_secs(60)));
+        assert_eq!(busy.to_string(), "Groq is rate limiting requests (retry after 60s)");
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_send_does_not_resend_timed_out_requests() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/slow"))
+            .respond_with(ResponseTemplate::new(200).set_delay(REQUEST_TIMEOUT * 4))
+            .mount(&server)
+            .await;
+
+        let err = send("Groq", || crate::HTTP_CLIENT.post(format!("{}/slow", server.uri()))).await.unwrap_err();
+        assert!(matches!(err, ProviderError::Timeout { .. }));
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_send_resends_when_connection_fails() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        drop(listener);
+
+        let mut attempts = 0;
+        let err = send("Groq", || {
+            attempts += 1;
+            crate::HTTP_CLIENT.post(&url)
+        })
+        .await
+        .unwrap_err();
+        assert!(matches!(err, ProviderError::Network { .. }));
+        assert_eq!(attempts, MAX_ATTEMPTS);
+    }
+
+    #[test]
+    fn test_sse_parser_splits_events_across_chunks() {
+        let mut parser = SseParser::default();
+        let stream = "event: delta\ndata: {\"t\":\"h\u{e9}\"}\r\n\r\n: ping\n\ndata: a\ndata: b\n\ndata: [DONE]";
+        let mut events = Vec::new();
+        // One byte at a time also splits the two-byte character
+        for byte in stream.as_bytes() {
+            events.extend(parser.push(std::slice::from_ref(byte)));
+        }
+        events.extend(parser.finish());
+        assert_eq!(events, ["{\"t\":\"h\u{e9}\"}", "a\nb", "[DONE]"]);
+    }
+
+    #[tokio::test]
+    async fn test_send_sse_stops_at_done() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/stream"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string("data: {\"n\":1}\n\ndata: {\"n\":2}\n\ndata: [DONE]\n\ndata: {\"n\":3}\n\n"),
+            )
+            .mount(&server)
+            .await;
+
+        let mut seen = Vec::new();
+        send_sse("Groq", || crate::HTTP_CLIENT.post(format!("{}/stream", server.uri())), |event: serde_json::Value| {
+            seen.push(event["n"].as_i64().unwrap());
+            Ok(())
+        })
+        .await
+        .unwrap();
+        assert_eq!(seen, [1, 2]);
+    }
+}
@@ -1,15 +1,23 @@
 mod audio;
 mod clipboard;
 mod commands;
+mod credentials;
 mod database;
+mod endpoint;
+mod http;
 mod models;
+mod pipeline;
+mod provider;
 mod reasoning;
+mod recordings;
+mod secrets;
 mod transcription;
 
 pub(crate) static HTTP_CLIENT: std::sync::LazyLock<reqwest::Client> =
     std::sync::LazyLock::new(|| {
         reqwest::Client::builder()
             .user_agent("Whisperi")
+            .connect_timeout(std::time::Duration::from_secs(10))
             .build()
             .expect("Failed to build HTTP client")
     });
@@ -105,11 +113,58 @@ pub fn run() {
         .setup(|app| {
             // Initialize audio recording state
             app.manage(audio::RecordingState::new());
+            app.manage(pipeline::streaming::LiveTranscription::default());
+            #[cfg(feature = "native-whisper")]
+            app.manage(transcription::native::NativeWhisper::default());
+            app.manage(transcription::server::WhisperServer::default());
+            app.manage(models::Downloads::default());
+            app.manage(credentials::CredentialCache::default());
+
+            // Tell the UI when a provider call is being retried
+            let retry_handle = app.handle().clone();
+            http::set_retry_listener(move |notice| {
+                if let Err(e) = tauri::Emitter::emit(&retry_handle, http::RETRY_EVENT, notice) {
+                    log::warn!("[Whisperi] Failed to emit {}: {}", http::RETRY_EVENT, e);
+                }
+            });
 
             // Initialize database
             let app_handle = app.handle().clone();
             database::init(&app_handle)?;
 
+            // API keys come from secure storage; move any left in settings.json there
+            let secrets = secrets::Secrets::open(app.path().app_data_dir()?);
+            if let Err(e) = secrets::migrate_settings(&app_handle, &secrets) {
+                log::error!("[Whisperi] Failed to migrate API keys: {:#}", e);
+            }
+            app.manage(secrets);
+            app.manage(recordings::Recordings::open(app.path().app_data_dir()?.join("recordings")));
+
+            match pipeline::DictationSettings::load(&app_handle) {
+                Ok(settings) => {
+                    // Recordings may have aged past the retention limit since the last run
+                    let retention = settings.audio_retention();
+                    let (db, kept) = (app.state::<database::Database>(), app.state::<recordings::Recordings>());
+                    if let Err(e) = recordings::prune(&db, &kept, &retention) {
+                        log::warn!("[Whisperi] Failed to prune recordings: {:#}", e);
+                    }
+                    // Warm up the local whisper-server so the first dictation doesn't pay the model load
+                    if settings.use_local_whisper && settings.whisper_server {
+                        let app_handle = app_handle.clone();
+                        tauri::async_runtime::spawn(async move {
+                            let warm = async {
+                                let model_file = settings.whisper_model_file()?;
+                                transcription::whisper::warm_server(&app_handle, &model_file).await
+                            };
+                            if let Err(e) = warm.await {
+                                log::warn!("[Whisperi] Failed to start whisper-server: {:#}", e);
+                            }
+                        });
+                    }
+                }
+                Err(e) => log::warn!("[Whisperi] Failed to load settings: {}", e),
+            }
+
             // Override Windows minimum window size for the overlay
             #[cfg(windows)]
             if let Some(main_window) = app.get_webview_window("main") {
@@ -180,22 +235,47 @@ pub fn run() {
             commands::transcription::transcribe_cloud,
             commands::transcription::list_whisper_models,
             commands::transcription::download_whisper_model,
+            commands::transcription::cancel_model_download,
             commands::transcription::delete_whisper_model,
             commands::transcription::get_whisper_status,
+            commands::transcription::list_parakeet_models,
+            commands::transcription::download_parakeet_model,
+            commands::transcription::delete_parakeet_model,
             commands::reasoning::process_reasoning,
+            commands::reasoning::list_local_models,
             commands::settings::get_setting,
             commands::settings::set_setting,
             commands::settings::get_all_settings,
+            commands::secrets::set_api_key,
+            commands::secrets::delete_api_key,
+            commands::secrets::test_api_key,
+            commands::secrets::test_provider_credentials,
             commands::models::get_model_registry,
+            commands::models::list_providers,
+            commands::models::list_provider_models,
+            commands::pipeline::dictate,
+            commands::pipeline::retranscribe,
+            commands::pipeline::reenhance,
             commands::clipboard::paste_text,
             commands::clipboard::read_clipboard,
             commands::database::save_transcription,
             commands::database::get_transcriptions,
             commands::database::delete_transcription,
             commands::database::clear_transcriptions,
+            commands::database::get_revisions,
+            commands::database::search_transcriptions,
+            commands::recordings::get_recording,
+            commands::recordings::export_recording,
+            commands::recordings::delete_recording,
+            commands::recordings::purge_recordings,
             commands::app::quit_app,
             commands::app::show_settings,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running whisperi");
+        .build(tauri::generate_context!())
+        .expect("error while building whisperi")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                app.state::<transcription::server::WhisperServer>().kill();
+            }
+        });
 }
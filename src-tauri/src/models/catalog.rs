@@ -0,0 +1,236 @@
+//! Live model lists from the providers' own APIs, narrowed to models that can
+//! serve the purpose and cached in the database, so the registry's
+//! hand-maintained lists don't have to keep up with every release.
+
+use serde::Serialize;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::credentials;
+use crate::database::{CachedModels, Database};
+use crate::endpoint::CUSTOM_PROVIDER;
+use crate::provider::{ListedModel, ProviderConfig, ProviderError, Purpose};
+use crate::reasoning::local::LOCAL_PROVIDER;
+
+/// How long a fetched list is used before the provider is asked again.
+const CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Id fragments of models that take audio in.
+const SPEECH_HINTS: &[&str] = &["whisper", "transcribe", "voxtral", "asr", "audio"];
+/// Id fragments of models that can't serve a chat completion.
+const NON_CHAT_HINTS: &[&str] = &[
+    "whisper",
+    "transcribe",
+    "asr",
+    "tts",
+    "embed",
+    "moderation",
+    "dall-e",
+    "image",
+    "realtime",
+    "guard",
+    "babbage",
+    "davinci",
+    "sora",
+];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderModels {
+    pub provider: String,
+    pub purpose: Purpose,
+    /// Model ids, sorted.
+    pub models: Vec<String>,
+    /// Unix time of the fetch, in seconds.
+    pub fetched_at: i64,
+    /// Whether the list came from the database rather than the provider.
+    pub cached: bool,
+    /// The provider couldn't be reached, so an expired list was returned.
+    pub stale: bool,
+}
+
+pub struct CatalogRequest<'a> {
+    pub purpose: Purpose,
+    pub provider: &'a str,
+    pub config: ProviderConfig<'a>,
+    pub api_key: Option<&'a str>,
+}
+
+/// The provider's models for `request.purpose`. A list cached with the same
+/// key and configuration is reused for [`CACHE_TTL`] unless `refresh` is set;
+/// when the provider fails, an expired list is better than none.
+pub async fn list(db: &Database, request: &CatalogRequest<'_>, refresh: bool) -> Result<ProviderModels, ProviderError> {
+    let purpose = request.purpose.as_str();
+    let fingerprint = credentials::fingerprint(request.purpose, request.provider, request.config, request.api_key);
+    let cached = db
+        .get_cached_models(request.provider, purpose)
+        .unwrap_or_else(|e| {
+            log::warn!("[Whisperi] Reading cached {} models failed: {:#}", request.provider, e);
+            None
+        })
+        .filter(|c| c.fingerprint == fingerprint);
+
+    let now = unix_now();
+    if let Some(cached) = &cached
+        && !refresh
+        && now - cached.fetched_at < CACHE_TTL.as_secs() as i64
+    {
+        return Ok(response(request, cached.clone(), true, false));
+    }
+
+    let fetched = credentials::list_models(request.purpose, request.provider, request.config, request.api_key).await?;
+    let listed = match fetched {
+        Ok(listed) => listed,
+        Err(e) => {
+            let Some(cached) = cached else {
+                return Err(e);
+            };
+            log::warn!("[Whisperi] {}; using the model list from {}", e, cached.fetched_at);
+            return Ok(response(request, cached, true, true));
+        }
+    };
+
+    // A custom or local server lists what the user set it up to serve, under
+    // ids (`large-v3`) the hints don't know
+    let user_configured = [CUSTOM_PROVIDER, LOCAL_PROVIDER].contains(&request.provider);
+    let mut models: Vec<String> = listed
+        .into_iter()
+        .filter(|m| user_configured || serves(request.purpose, m))
+        .map(|m| m.id)
+        .collect();
+    models.sort();
+    models.dedup();
+    log::info!("[Whisperi] {} lists {} {} models", request.provider, models.len(), purpose);
+
+    let fresh = CachedModels {
+        models,
+        fingerprint,
+        fetched_at: now,
+    };
+    if let Err(e) = db.save_cached_models(request.provider, purpose, &fresh) {
+        log::warn!("[Whisperi] Caching {} models failed: {:#}", request.provider, e);
+    }
+    Ok(response(request, fresh, false, false))
+}
+
+/// Whether a listed model can serve `purpose`. Modalities decide when the
+/// provider reports them (OpenRouter); otherwise the id does.
+fn serves(purpose: Purpose, model: &ListedModel) -> bool {
+    let id = model.id.to_ascii_lowercase();
+    let mentions = |hints: &[&str]| hints.iter().any(|h| id.contains(h));
+    match purpose {
+        Purpose::Transcription if !model.inputs.is_empty() => model.inputs.iter().any(|i| i == "audio"),
+        Purpose::Transcription => mentions(SPEECH_HINTS),
+        Purpose::Reasoning if !model.inputs.is_empty() => model.inputs.iter().any(|i| i == "text"),
+        Purpose::Reasoning => !mentions(NON_CHAT_HINTS),
+    }
+}
+
+fn response(request: &CatalogRequest<'_>, cached: CachedModels, from_cache: bool, stale: bool) -> ProviderModels {
+    ProviderModels {
+        provider: request.provider.to_string(),
+        purpose: request.purpose,
+        models: cached.models,
+        fetched_at: cached.fetched_at,
+        cached: from_cache,
+        stale,
+    }
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endpoint::{CUSTOM_PROVIDER, CustomEndpoint};
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn listed(id: &str, inputs: &[&str]) -> ListedModel {
+        ListedModel {
+            id: id.to_string(),
+            inputs: inputs.iter().map(|i| i.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_filters_by_purpose() {
+        let speech = |m: &ListedModel| serves(Purpose::Transcription, m);
+        let chat = |m: &ListedModel| serves(Purpose::Reasoning, m);
+
+        assert!(speech(&listed("whisper-large-v3-turbo", &[])) && !chat(&listed("whisper-large-v3-turbo", &[])));
+        assert!(speech(&listed("gpt-4o-mini-transcribe", &[])));
+        assert!(speech(&listed("qwen3-asr-flash", &[])) && !chat(&listed("qwen3-asr-flash", &[])));
+        assert!(!speech(&listed("llama-3.3-70b-versatile", &[])) && chat(&listed("llama-3.3-70b-versatile", &[])));
+        assert!(!chat(&listed("text-embedding-3-small", &[])) && !chat(&listed("gpt-4o-mini-tts", &[])));
+
+        // OpenRouter ids say nothing about audio; its modalities do
+        let multimodal = listed("google/gemini-2.5-flash", &["text", "image", "audio"]);
+        assert!(speech(&multimodal) && chat(&multimodal));
+        assert!(!speech(&listed("anthropic/claude-sonnet-4", &["text", "image"])));
+    }
+
+    #[tokio::test]
+    async fn test_list_caches_in_database_and_falls_back_to_stale() {
+        let server = MockServer::start().await;
+        let endpoint = CustomEndpoint {
+            base_url: format!("{}/v1", server.uri()),
+            ..Default::default()
+        };
+        let request = CatalogRequest {
+            purpose: Purpose::Transcription,
+            provider: CUSTOM_PROVIDER,
+            config: ProviderConfig {
+                endpoint: Some(&endpoint),
+                ..Default::default()
+            },
+            api_key: None,
+        };
+        let db = Database::open_in_memory().unwrap();
+
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [{ "id": "large-v3" }, { "id": "distil-whisper" }, { "id": "qwen3-32b" }]
+            })))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        // The custom endpoint's list is taken as is
+        let first = list(&db, &request, false).await.unwrap();
+        assert_eq!(first.models, ["distil-whisper", "large-v3", "qwen3-32b"]);
+        assert!(!first.cached && !first.stale);
+        let again = list(&db, &request, false).await.unwrap();
+        assert!(again.cached && !again.stale);
+        assert_eq!(again.models, first.models);
+
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+        let stale = list(&db, &request, true).await.unwrap();
+        assert!(stale.cached && stale.stale);
+        assert_eq!(stale.models, first.models);
+
+        // A different endpoint doesn't reuse the cached list
+        let other = CustomEndpoint {
+            base_url: format!("{}/other/v1", server.uri()),
+            ..Default::default()
+        };
+        let request = CatalogRequest {
+            config: ProviderConfig {
+                endpoint: Some(&other),
+                ..Default::default()
+            },
+            ..request
+        };
+        assert!(list(&db, &request, false).await.is_err());
+    }
+}
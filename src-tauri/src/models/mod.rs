@@ -1,48 +1,575 @@
-use anyhow::Result;
+pub mod catalog;
+pub mod registry;
+
+use anyhow::{Context, Result};
 use futures_util::StreamExt;
-use std::path::PathBuf;
+use reqwest::StatusCode;
+use reqwest::header::{CONTENT_RANGE, RANGE};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio_util::sync::CancellationToken;
+
+/// Attempts per download before a transient failure is reported.
+const MAX_ATTEMPTS: u32 = 5;
+/// First retry delay; doubled after every failed attempt.
+const RETRY_BASE_DELAY: Duration = if cfg!(test) {
+    Duration::from_millis(10)
+} else {
+    Duration::from_secs(1)
+};
+/// Free space to leave on the target disk on top of the download itself.
+const DISK_HEADROOM: u64 = 100 * 1024 * 1024;
+
+#[derive(Debug, thiserror::Error)]
+pub enum DownloadError {
+    #[error("Download cancelled")]
+    Cancelled,
+    #[error("Download failed with status: {0}")]
+    Status(StatusCode),
+    #[error("Server returned an unexpected byte range")]
+    BadRange,
+    #[error("Download ended early ({received} of {expected} bytes)")]
+    Incomplete { received: u64, expected: u64 },
+    #[error("Checksum mismatch: expected {expected}, got {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+    #[error("Not enough disk space: {needed} MB needed, {available} MB available")]
+    InsufficientSpace { needed: u64, available: u64 },
+    #[error("Network error: {0}")]
+    Network(#[from] reqwest::Error),
+    #[error("File error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl DownloadError {
+    /// Whether retrying (and resuming) the download may succeed.
+    fn is_transient(&self) -> bool {
+        match self {
+            Self::Status(status) => {
+                status.is_server_error()
+                    || *status == StatusCode::TOO_MANY_REQUESTS
+                    || *status == StatusCode::REQUEST_TIMEOUT
+                    // The stale `.part` has been removed, so the retry starts over
+                    || *status == StatusCode::RANGE_NOT_SATISFIABLE
+            }
+            Self::BadRange | Self::Incomplete { .. } | Self::Network(_) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Cancellation tokens for in-flight model downloads, keyed by model id.
+#[derive(Default)]
+pub struct Downloads {
+    active: Mutex<HashMap<String, CancellationToken>>,
+}
+
+/// Registration of one running download; unregisters itself when dropped.
+pub struct ActiveDownload<'a> {
+    downloads: &'a Downloads,
+    id: String,
+    pub token: CancellationToken,
+}
+
+impl Downloads {
+    /// Register a download for `id`, failing if one is already running.
+    pub fn begin(&self, id: &str) -> Result<ActiveDownload<'_>> {
+        let mut active = self.active.lock().unwrap();
+        if active.contains_key(id) {
+            anyhow::bail!("{} is already downloading", id);
+        }
+        let token = CancellationToken::new();
+        active.insert(id.to_string(), token.clone());
+        Ok(ActiveDownload {
+            downloads: self,
+            id: id.to_string(),
+            token,
+        })
+    }
+
+    /// Cancel the download for `id`. Returns false if none was running.
+    pub fn cancel(&self, id: &str) -> bool {
+        match self.active.lock().unwrap().get(id) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Drop for ActiveDownload<'_> {
+    fn drop(&mut self) {
+        self.downloads.active.lock().unwrap().remove(&self.id);
+    }
+}
 
 /// Download a file with streaming progress reporting.
 /// Calls `on_progress(downloaded_bytes, total_bytes)` periodically during download.
+///
+/// Data is written to `<dest>.part`, which is resumed with an HTTP Range
+/// request on retry or on a later call, and only renamed to `dest` once it is
+/// complete and matches `expected_sha256` (when given). Transient failures
+/// are retried with exponential backoff. A cancelled download keeps its
+/// `.part` so it can be resumed.
 pub async fn download_file(
     url: &str,
-    dest: &PathBuf,
+    dest: &Path,
+    expected_sha256: Option<&str>,
+    cancel: &CancellationToken,
     on_progress: impl Fn(u64, u64),
-) -> Result<()> {
-    let response = crate::HTTP_CLIENT.get(url).send().await?;
+) -> Result<(), DownloadError> {
+    let part = dest.with_extension("part");
 
-    if !response.status().is_success() {
-        anyhow::bail!("Download failed with status: {}", response.status());
+    let mut attempt = 1;
+    loop {
+        match fetch(url, &part, cancel, &on_progress).await {
+            Ok(()) => break,
+            Err(e) if e.is_transient() && attempt < MAX_ATTEMPTS => {
+                let delay = RETRY_BASE_DELAY * 2u32.pow(attempt - 1);
+                log::warn!(
+                    "[Whisperi] Download attempt {} failed ({}), retrying in {:?}",
+                    attempt,
+                    e,
+                    delay
+                );
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = cancel.cancelled() => return Err(DownloadError::Cancelled),
+                }
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
     }
 
-    let total_size = response.content_length().unwrap_or(0);
+    if let Some(expected) = expected_sha256 {
+        let actual = sha256_file(&part).await?;
+        if !actual.eq_ignore_ascii_case(expected) {
+            let _ = tokio::fs::remove_file(&part).await;
+            return Err(DownloadError::ChecksumMismatch {
+                expected: expected.to_string(),
+                actual,
+            });
+        }
+    }
 
-    // Download to a temp file first, then rename on success
-    let temp_dest = dest.with_extension("part");
-    let mut file = tokio::fs::File::create(&temp_dest).await?;
-    let mut downloaded: u64 = 0;
-    let mut last_progress = std::time::Instant::now();
+    // Rename .part to final destination
+    tokio::fs::rename(&part, dest).await?;
+    Ok(())
+}
+
+/// One download attempt, appending to `part` from its current length.
+async fn fetch(
+    url: &str,
+    part: &Path,
+    cancel: &CancellationToken,
+    on_progress: &impl Fn(u64, u64),
+) -> Result<(), DownloadError> {
+    let offset = tokio::fs::metadata(part).await.map(|m| m.len()).unwrap_or(0);
+
+    let mut request = crate::HTTP_CLIENT.get(url);
+    if offset > 0 {
+        log::info!("[Whisperi] Resuming download at {} bytes", offset);
+        request = request.header(RANGE, format!("bytes={}-", offset));
+    }
+    let response = tokio::select! {
+        response = request.send() => response?,
+        _ = cancel.cancelled() => return Err(DownloadError::Cancelled),
+    };
+
+    let status = response.status();
+    let remaining = response.content_length();
+    let (mut file, mut downloaded, total) = if status == StatusCode::PARTIAL_CONTENT {
+        let (start, total) = response
+            .headers()
+            .get(CONTENT_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_content_range)
+            .ok_or(DownloadError::BadRange)?;
+        if start != offset {
+            tokio::fs::remove_file(part).await?;
+            return Err(DownloadError::BadRange);
+        }
+        let total = total.or(remaining.map(|r| offset + r));
+        let file = tokio::fs::OpenOptions::new().append(true).open(part).await?;
+        (file, offset, total)
+    } else if status.is_success() {
+        // Fresh download, or a server that ignores Range: start over
+        (tokio::fs::File::create(part).await?, 0, remaining)
+    } else if status == StatusCode::RANGE_NOT_SATISFIABLE {
+        // The `.part` already holds the whole file, e.g. when the rename never
+        // ran: leave it for the caller to verify and rename
+        let total = response
+            .headers()
+            .get(CONTENT_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("bytes */"))
+            .and_then(|total| total.parse::<u64>().ok());
+        if offset > 0 && total.is_none_or(|total| total == offset) {
+            log::info!("[Whisperi] Partial download is already complete ({} bytes)", offset);
+            on_progress(offset, offset);
+            return Ok(());
+        }
+        tokio::fs::remove_file(part).await?;
+        return Err(DownloadError::Status(status));
+    } else {
+        return Err(DownloadError::Status(status));
+    };
+
+    if let (Some(total), Some(dir)) = (total, part.parent()) {
+        ensure_disk_space(dir, total.saturating_sub(downloaded))?;
+    }
 
+    let mut last_progress = std::time::Instant::now();
     let mut stream = response.bytes_stream();
-    while let Some(chunk) = stream.next().await {
-        let chunk = chunk?;
-        tokio::io::AsyncWriteExt::write_all(&mut file, &chunk).await?;
+    loop {
+        let chunk = tokio::select! {
+            chunk = stream.next() => chunk,
+            _ = cancel.cancelled() => {
+                file.flush().await?;
+                return Err(DownloadError::Cancelled);
+            }
+        };
+        let Some(chunk) = chunk else { break };
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                file.flush().await?;
+                return Err(e.into());
+            }
+        };
+        file.write_all(&chunk).await?;
         downloaded += chunk.len() as u64;
 
         // Throttle progress callbacks to ~100ms
         let now = std::time::Instant::now();
-        if now.duration_since(last_progress).as_millis() >= 100 || downloaded >= total_size {
-            on_progress(downloaded, total_size);
+        if now.duration_since(last_progress).as_millis() >= 100 || Some(downloaded) == total {
+            on_progress(downloaded, total.unwrap_or(0));
             last_progress = now;
         }
     }
 
     // Flush and close the file
-    tokio::io::AsyncWriteExt::flush(&mut file).await?;
+    file.flush().await?;
     drop(file);
 
-    // Rename .part to final destination
-    tokio::fs::rename(&temp_dest, dest).await?;
+    match total {
+        Some(expected) if downloaded < expected => Err(DownloadError::Incomplete {
+            received: downloaded,
+            expected,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Parse `bytes <start>-<end>/<total>` into the start offset and total size
+/// (`None` when the server sends `*`).
+fn parse_content_range(value: &str) -> Option<(u64, Option<u64>)> {
+    let (range, total) = value.strip_prefix("bytes ")?.split_once('/')?;
+    let start = range.split_once('-')?.0.parse().ok()?;
+    Some((start, total.parse().ok()))
+}
 
+/// Fail early when the disk holding `dir` can't fit `needed` more bytes.
+fn ensure_disk_space(dir: &Path, needed: u64) -> Result<(), DownloadError> {
+    let Some(available) = available_space(dir) else {
+        return Ok(());
+    };
+    if available < needed + DISK_HEADROOM {
+        return Err(DownloadError::InsufficientSpace {
+            needed: needed / (1024 * 1024),
+            available: available / (1024 * 1024),
+        });
+    }
     Ok(())
 }
+
+/// Free space on the disk whose mount point is the longest prefix of `dir`.
+fn available_space(dir: &Path) -> Option<u64> {
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|disk| dir.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+}
+
+/// Hex SHA-256 of a file, hashed off the async runtime.
+async fn sha256_file(path: &Path) -> std::io::Result<String> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let mut hasher = Sha256::new();
+        std::io::copy(&mut std::fs::File::open(path)?, &mut hasher)?;
+        Ok(format!("{:x}", hasher.finalize()))
+    })
+    .await?
+}
+
+/// Extract a `.tar.bz2` archive into `dest_dir`. Entries are unpacked into a
+/// staging directory first and moved into place only once extraction succeeds,
+/// so an interrupted extraction never leaves a half-populated model folder.
+pub async fn extract_tar_bz2(archive: &Path, dest_dir: &Path) -> Result<()> {
+    let archive = archive.to_path_buf();
+    let dest_dir = dest_dir.to_path_buf();
+
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        let staging = dest_dir.join(".extracting");
+        if staging.exists() {
+            std::fs::remove_dir_all(&staging)?;
+        }
+        std::fs::create_dir_all(&staging)?;
+
+        let file = std::fs::File::open(&archive)
+            .with_context(|| format!("Failed to open {}", archive.display()))?;
+        tar::Archive::new(bzip2::read::BzDecoder::new(file))
+            .unpack(&staging)
+            .with_context(|| format!("Failed to extract {}", archive.display()))?;
+
+        for entry in std::fs::read_dir(&staging)? {
+            let entry = entry?;
+            let target = dest_dir.join(entry.file_name());
+            if target.exists() {
+                std::fs::remove_dir_all(&target)?;
+            }
+            std::fs::rename(entry.path(), target)?;
+        }
+        std::fs::remove_dir_all(&staging)?;
+        Ok(())
+    })
+    .await?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::path::PathBuf;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const BODY: &[u8] = b"ggml model weights";
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("whisperi-download-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn sha256_hex(data: &[u8]) -> String {
+        format!("{:x}", Sha256::digest(data))
+    }
+
+    #[tokio::test]
+    async fn test_download_resumes_partial_file_with_range() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/model.bin"))
+            .and(header("range", "bytes=4-"))
+            .respond_with(
+                ResponseTemplate::new(206)
+                    .insert_header("content-range", format!("bytes 4-{}/{}", BODY.len() - 1, BODY.len()))
+                    .set_body_bytes(&BODY[4..]),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dir = test_dir("resume");
+        let dest = dir.join("model.bin");
+        std::fs::write(dest.with_extension("part"), &BODY[..4]).unwrap();
+
+        let checksum = sha256_hex(BODY);
+        let progress = Mutex::new(Vec::new());
+        download_file(
+            &format!("{}/model.bin", server.uri()),
+            &dest,
+            Some(&checksum),
+            &CancellationToken::new(),
+            |downloaded, total| progress.lock().unwrap().push((downloaded, total)),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(std::fs::read(&dest).unwrap(), BODY);
+        assert!(!dest.with_extension("part").exists());
+        let last = *progress.lock().unwrap().last().unwrap();
+        assert_eq!(last, (BODY.len() as u64, BODY.len() as u64));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_renames_complete_partial_file() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("range", format!("bytes={}-", BODY.len()).as_str()))
+            .respond_with(ResponseTemplate::new(416).insert_header("content-range", format!("bytes */{}", BODY.len())))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dir = test_dir("complete-part");
+        let dest = dir.join("model.bin");
+        std::fs::write(dest.with_extension("part"), BODY).unwrap();
+
+        download_file(&server.uri(), &dest, Some(&sha256_hex(BODY)), &CancellationToken::new(), |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(&dest).unwrap(), BODY);
+        assert!(!dest.with_extension("part").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_restarts_when_server_ignores_range() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(BODY))
+            .mount(&server)
+            .await;
+
+        let dir = test_dir("ignore-range");
+        let dest = dir.join("model.bin");
+        std::fs::write(dest.with_extension("part"), b"stale").unwrap();
+
+        download_file(&server.uri(), &dest, None, &CancellationToken::new(), |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(&dest).unwrap(), BODY);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_retries_transient_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(BODY))
+            .mount(&server)
+            .await;
+
+        let dir = test_dir("retry");
+        let dest = dir.join("model.bin");
+        download_file(&server.uri(), &dest, None, &CancellationToken::new(), |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(&dest).unwrap(), BODY);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_does_not_retry_client_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dir = test_dir("not-found");
+        let err = download_file(&server.uri(), &dir.join("model.bin"), None, &CancellationToken::new(), |_, _| {})
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DownloadError::Status(StatusCode::NOT_FOUND)));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_rejects_checksum_mismatch() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(BODY))
+            .mount(&server)
+            .await;
+
+        let dir = test_dir("checksum");
+        let dest = dir.join("model.bin");
+        let err = download_file(&server.uri(), &dest, Some(&sha256_hex(b"other")), &CancellationToken::new(), |_, _| {})
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DownloadError::ChecksumMismatch { .. }));
+        assert!(!dest.exists());
+        assert!(!dest.with_extension("part").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_download_keeps_partial_file() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(BODY).set_delay(Duration::from_secs(5)))
+            .mount(&server)
+            .await;
+
+        let downloads = Downloads::default();
+        let download = downloads.begin("base").unwrap();
+        assert!(downloads.begin("base").is_err());
+
+        let dir = test_dir("cancel");
+        let dest = dir.join("model.bin");
+        std::fs::write(dest.with_extension("part"), &BODY[..4]).unwrap();
+        let url = server.uri();
+        let (result, _) = tokio::join!(
+            download_file(&url, &dest, None, &download.token, |_, _| {}),
+            async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                assert!(downloads.cancel("base"));
+            }
+        );
+
+        assert!(matches!(result, Err(DownloadError::Cancelled)));
+        assert_eq!(std::fs::read(dest.with_extension("part")).unwrap(), &BODY[..4]);
+        drop(download);
+        assert!(!downloads.cancel("base"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_content_range() {
+        assert_eq!(parse_content_range("bytes 100-199/200"), Some((100, Some(200))));
+        assert_eq!(parse_content_range("bytes 0-9/*"), Some((0, None)));
+        assert_eq!(parse_content_range("items 0-9/10"), None);
+    }
+
+    #[tokio::test]
+    async fn test_extract_tar_bz2_unpacks_model_directory() {
+        let root = std::env::temp_dir().join(format!("whisperi-extract-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+
+        // Build model-dir/tokens.txt into a .tar.bz2
+        let archive = root.join("model.tar.bz2");
+        {
+            let encoder = bzip2::write::BzEncoder::new(
+                std::fs::File::create(&archive).unwrap(),
+                bzip2::Compression::fast(),
+            );
+            let mut builder = tar::Builder::new(encoder);
+            let data = b"<blk> 0\n";
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, "model-dir/tokens.txt", &data[..]).unwrap();
+            builder.into_inner().unwrap().finish().unwrap().flush().unwrap();
+        }
+
+        let dest = root.join("models");
+        std::fs::create_dir_all(&dest).unwrap();
+        extract_tar_bz2(&archive, &dest).await.unwrap();
+
+        assert_eq!(std::fs::read(dest.join("model-dir/tokens.txt")).unwrap(), b"<blk> 0\n");
+        assert!(!dest.join(".extracting").exists());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
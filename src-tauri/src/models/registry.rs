@@ -0,0 +1,314 @@
+//! Typed model and provider registry.
+//!
+//! The registry is bundled from `src/models/modelRegistryData.json` (shared with
+//! the frontend) and can be extended or patched by a user override file at
+//! `<config dir>/whisperi/model-registry.json` with the same shape. Objects are
+//! merged key by key and arrays of `{ "id": ... }` entries are merged by id, so
+//! an override only needs the fields it changes.
+
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+const BUNDLED: &str = include_str!("../../../src/models/modelRegistryData.json");
+const OVERRIDE_FILE: &str = "model-registry.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelRegistry {
+    pub whisper_models: IndexMap<String, WhisperModel>,
+    pub parakeet_models: IndexMap<String, ParakeetModel>,
+    pub transcription_providers: Vec<ProviderInfo>,
+    /// Reasoning (text enhancement) providers; `cloudProviders` in the JSON.
+    #[serde(rename = "cloudProviders")]
+    pub reasoning_providers: Vec<ProviderInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WhisperModel {
+    pub name: String,
+    pub description: String,
+    pub size: String,
+    pub size_mb: u64,
+    pub file_name: String,
+    pub download_url: String,
+    /// Hex SHA-256 the downloaded file is verified against, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    #[serde(default)]
+    pub recommended: bool,
+    /// Empty means every language whisper supports.
+    #[serde(default)]
+    pub supported_languages: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParakeetModel {
+    pub name: String,
+    pub description: String,
+    pub size: String,
+    pub size_mb: u64,
+    #[serde(default)]
+    pub supported_languages: Vec<String>,
+    #[serde(default)]
+    pub recommended: bool,
+    pub download_url: String,
+    /// Hex SHA-256 of the archive, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    pub extract_dir: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderInfo {
+    pub id: String,
+    pub name: String,
+    /// API root; `None` means the provider module's own default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// Suggested models. Empty means the provider accepts any model id.
+    #[serde(default)]
+    pub models: Vec<ProviderModel>,
+    #[serde(default)]
+    pub capabilities: Capabilities,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderModel {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<String>,
+}
+
+/// What a provider supports beyond the basic request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Capabilities {
+    /// Accepts a vocabulary prompt built from the custom dictionary.
+    pub prompt: bool,
+    /// Accepts a language hint.
+    pub language: bool,
+    /// Can stream its output incrementally.
+    pub streaming: bool,
+    /// Can return segment timestamps with a transcript.
+    pub timestamps: bool,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            prompt: true,
+            language: true,
+            streaming: false,
+            timestamps: false,
+        }
+    }
+}
+
+impl ProviderInfo {
+    /// First listed model, used when a fallback names no model of its own.
+    pub fn default_model(&self) -> Option<&str> {
+        self.models.first().map(|m| m.id.as_str())
+    }
+
+    /// Whether `model` is one of the listed models (always true for open-ended providers).
+    pub fn lists_model(&self, model: &str) -> bool {
+        self.models.is_empty() || self.models.iter().any(|m| m.id == model)
+    }
+
+    /// Log when a request uses a model the registry doesn't know. Providers
+    /// add models faster than releases ship, so this is not an error.
+    pub fn check_model(&self, model: &str) {
+        if !self.lists_model(model) {
+            log::warn!("[Whisperi] Model '{}' is not listed for provider {}", model, self.id);
+        }
+    }
+}
+
+impl ModelRegistry {
+    /// Parse the bundled registry JSON, merging `overlay` on top when given.
+    pub fn from_json(bundled: &str, overlay: Option<&str>) -> Result<Self> {
+        let mut value: Value = serde_json::from_str(bundled).context("Invalid bundled model registry")?;
+        if let Some(overlay) = overlay {
+            let overlay: Value = serde_json::from_str(overlay).context("Invalid model registry override")?;
+            merge(&mut value, overlay);
+        }
+        let registry: Self =
+            serde_json::from_value(value).context("Model registry does not match the expected schema")?;
+        registry.check_hashes()?;
+        Ok(registry)
+    }
+
+    /// A malformed `sha256` would fail every download of its model, so it
+    /// fails loading instead.
+    fn check_hashes(&self) -> Result<()> {
+        let whisper = self.whisper_models.iter().map(|(id, m)| (id, &m.sha256));
+        let parakeet = self.parakeet_models.iter().map(|(id, m)| (id, &m.sha256));
+        for (id, sha256) in whisper.chain(parakeet) {
+            if let Some(sha256) = sha256
+                && !(sha256.len() == 64 && sha256.chars().all(|c| c.is_ascii_hexdigit()))
+            {
+                anyhow::bail!("Model {} has an invalid sha256: {}", id, sha256);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn whisper_model(&self, id: &str) -> Result<&WhisperModel> {
+        self.whisper_models
+            .get(id)
+            .with_context(|| format!("Unknown whisper model: {}", id))
+    }
+
+    pub fn parakeet_model(&self, id: &str) -> Result<&ParakeetModel> {
+        self.parakeet_models
+            .get(id)
+            .with_context(|| format!("Unknown Parakeet model: {}", id))
+    }
+
+    pub fn transcription_provider(&self, id: &str) -> Result<&ProviderInfo> {
+        self.transcription_providers
+            .iter()
+            .find(|p| p.id == id)
+            .with_context(|| format!("Unknown transcription provider: {}", id))
+    }
+
+    pub fn reasoning_provider(&self, id: &str) -> Result<&ProviderInfo> {
+        self.reasoning_providers
+            .iter()
+            .find(|p| p.id == id)
+            .with_context(|| format!("Unknown reasoning provider: {}", id))
+    }
+}
+
+static REGISTRY: LazyLock<ModelRegistry> = LazyLock::new(load);
+
+/// The bundled registry with the user override applied.
+pub fn registry() -> &'static ModelRegistry {
+    &REGISTRY
+}
+
+/// Location of the optional user override file.
+pub fn override_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("whisperi").join(OVERRIDE_FILE))
+}
+
+fn load() -> ModelRegistry {
+    let overlay = override_path().and_then(|path| {
+        let text = std::fs::read_to_string(&path).ok()?;
+        log::info!("[Whisperi] Applying model registry override: {}", path.display());
+        Some(text)
+    });
+
+    if let Some(overlay) = overlay.as_deref() {
+        match ModelRegistry::from_json(BUNDLED, Some(overlay)) {
+            Ok(registry) => return registry,
+            Err(e) => log::error!("[Whisperi] Ignoring model registry override: {:#}", e),
+        }
+    }
+    ModelRegistry::from_json(BUNDLED, None).expect("bundled modelRegistryData.json is invalid")
+}
+
+/// Merge `overlay` into `base`: objects key by key, arrays of objects by their
+/// `id`, anything else replaced outright.
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (Value::Array(base), Value::Array(overlay)) if overlay.iter().all(|v| v.get("id").is_some()) => {
+            for item in overlay {
+                match base.iter_mut().find(|b| b.get("id") == item.get("id")) {
+                    Some(existing) => merge(existing, item),
+                    None => base.push(item),
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_registry_parses() {
+        let registry = ModelRegistry::from_json(BUNDLED, None).unwrap();
+
+        let ids: Vec<_> = registry.whisper_models.keys().map(String::as_str).collect();
+        assert_eq!(ids, ["tiny", "base", "small", "medium", "large", "turbo"]);
+        assert_eq!(registry.whisper_model("turbo").unwrap().file_name, "ggml-large-v3-turbo.bin");
+        assert!(registry.parakeet_model("parakeet-tdt-0.6b-v3").is_ok());
+
+        let qwen = registry.transcription_provider("qwen").unwrap();
+        assert!(!qwen.capabilities.prompt && !qwen.capabilities.language);
+        assert!(registry.transcription_provider("groq").unwrap().capabilities.prompt);
+        assert!(registry.reasoning_provider("openai").unwrap().base_url.is_none());
+        assert!(registry.reasoning_provider("groq").unwrap().base_url.is_some());
+        assert!(registry.reasoning_provider("nope").is_err());
+    }
+
+    #[test]
+    fn test_malformed_sha256_is_rejected() {
+        let valid = format!(r#"{{ "whisperModels": {{ "tiny": {{ "sha256": "{}" }} }} }}"#, "ab".repeat(32));
+        assert!(ModelRegistry::from_json(BUNDLED, Some(&valid)).is_ok());
+        let short = r#"{ "whisperModels": { "tiny": { "sha256": "abc123" } } }"#;
+        assert!(ModelRegistry::from_json(BUNDLED, Some(short)).is_err());
+        let not_hex = format!(r#"{{ "parakeetModels": {{ "parakeet-tdt-0.6b-v3": {{ "sha256": "{}" }} }} }}"#, "zz".repeat(32));
+        assert!(ModelRegistry::from_json(BUNDLED, Some(&not_hex)).is_err());
+    }
+
+    #[test]
+    fn test_override_merges_by_key_and_id() {
+        let overlay = r#"{
+            "whisperModels": {
+                "base": { "recommended": false },
+                "distil-en": {
+                    "name": "Distil EN", "description": "English only", "size": "166MB", "sizeMb": 166,
+                    "fileName": "ggml-distil-small.en.bin",
+                    "downloadUrl": "https://example.com/ggml-distil-small.en.bin",
+                    "supportedLanguages": ["en"]
+                }
+            },
+            "transcriptionProviders": [
+                { "id": "groq", "baseUrl": "https://groq.internal/v1", "models": [{ "id": "whisper-x", "name": "X" }] }
+            ]
+        }"#;
+        let registry = ModelRegistry::from_json(BUNDLED, Some(overlay)).unwrap();
+
+        assert!(!registry.whisper_model("base").unwrap().recommended);
+        assert_eq!(registry.whisper_model("base").unwrap().size_mb, 142);
+        assert_eq!(registry.whisper_models.keys().last().unwrap(), "distil-en");
+
+        let groq = registry.transcription_provider("groq").unwrap();
+        assert_eq!(groq.name, "Groq");
+        assert_eq!(groq.base_url.as_deref(), Some("https://groq.internal/v1"));
+        assert!(groq.lists_model("whisper-large-v3") && groq.lists_model("whisper-x"));
+        assert!(!groq.lists_model("gpt-4o-transcribe"));
+        assert!(registry.transcription_provider("openrouter").unwrap().lists_model("anything"));
+    }
+
+    #[test]
+    fn test_invalid_override_is_rejected() {
+        assert!(ModelRegistry::from_json(BUNDLED, Some("{ not json")).is_err());
+        assert!(ModelRegistry::from_json(BUNDLED, Some(r#"{ "whisperModels": { "x": { "name": "X" } } }"#)).is_err());
+    }
+}
@@ -0,0 +1,152 @@
+//! Enhancement output as it streams in. `<think>` blocks are filtered out
+//! chunk by chunk, and when the text is typed while streaming it is handed
+//! out a few words at a time, never ending mid-word, so what lands in the
+//! focused app is what the finished response would have pasted.
+
+const THINK_OPEN: &str = "<think>";
+const THINK_CLOSE: &str = "</think>";
+
+/// Removes `<think>...</think>` blocks from text that arrives in pieces,
+/// holding back anything that could be the start of a tag.
+#[derive(Default)]
+struct ThinkFilter {
+    pending: String,
+    in_think: bool,
+}
+
+impl ThinkFilter {
+    /// Feed a chunk, returning the visible text it completes.
+    fn push(&mut self, chunk: &str) -> String {
+        self.pending.push_str(chunk);
+        let mut visible = String::new();
+        loop {
+            let tag = if self.in_think { THINK_CLOSE } else { THINK_OPEN };
+            if let Some(at) = self.pending.find(tag) {
+                if !self.in_think {
+                    visible.push_str(&self.pending[..at]);
+                }
+                self.pending.drain(..at + tag.len());
+                self.in_think = !self.in_think;
+                continue;
+            }
+            let cut = self.pending.len() - partial_tag(&self.pending, tag);
+            if !self.in_think {
+                visible.push_str(&self.pending[..cut]);
+            }
+            self.pending.drain(..cut);
+            return visible;
+        }
+    }
+
+    /// Text held back at the end of the stream. An unclosed think block is dropped.
+    fn finish(&mut self) -> String {
+        let rest = std::mem::take(&mut self.pending);
+        if self.in_think { String::new() } else { rest }
+    }
+}
+
+/// Length of the longest suffix of `text` that is a proper prefix of `tag`.
+fn partial_tag(text: &str, tag: &str) -> usize {
+    (1..tag.len()).rev().find(|&n| text.ends_with(&tag[..n])).unwrap_or(0)
+}
+
+/// Visible text of one streamed response, and how much of it was typed.
+#[derive(Default)]
+pub struct StreamedText {
+    filter: ThinkFilter,
+    text: String,
+    typed: usize,
+}
+
+impl StreamedText {
+    /// Feed a delta from the provider, returning the text it made visible.
+    pub fn push(&mut self, delta: &str) -> String {
+        let visible = self.filter.push(delta);
+        self.append(visible)
+    }
+
+    /// Everything visible so far, without leading whitespace.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The text handed out for typing so far.
+    pub fn typed(&self) -> &str {
+        &self.text[..self.typed]
+    }
+
+    /// Untyped words up to the last whitespace. Trailing whitespace waits for
+    /// the next word, since the response may end there.
+    pub fn next_words(&mut self) -> Option<String> {
+        let rest = &self.text[self.typed..];
+        let end = rest.rfind(char::is_whitespace)?;
+        let words = rest[..end].trim_end();
+        if words.trim_start().is_empty() {
+            return None;
+        }
+        let words = words.to_string();
+        self.typed += words.len();
+        Some(words)
+    }
+
+    /// Hand back `words` from [`Self::next_words`] or [`Self::rest`] that
+    /// couldn't be typed, so they count as untyped again.
+    pub fn give_back(&mut self, words: &str) {
+        self.typed -= words.len();
+    }
+
+    /// Untyped text once the stream ended, trailing whitespace removed.
+    pub fn rest(&mut self) -> Option<String> {
+        let held = self.filter.finish();
+        self.append(held);
+        let rest = self.text[self.typed..].trim_end();
+        if rest.is_empty() {
+            return None;
+        }
+        let rest = rest.to_string();
+        self.typed += rest.len();
+        Some(rest)
+    }
+
+    fn append(&mut self, visible: String) -> String {
+        let visible = if self.text.is_empty() {
+            visible.trim_start().to_string()
+        } else {
+            visible
+        };
+        self.text.push_str(&visible);
+        visible
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_think_filter_handles_tags_split_across_chunks() {
+        let mut filter = ThinkFilter::default();
+        let chunks = ["Hi <th", "ink>plan", "ning</thi", "nk> there <", "b>"];
+        let visible: String = chunks.iter().map(|c| filter.push(c)).collect();
+        assert_eq!(visible + &filter.finish(), "Hi  there <b>");
+
+        let mut unclosed = ThinkFilter::default();
+        assert_eq!(unclosed.push("<think>still thinking"), "");
+        assert_eq!(unclosed.finish(), "");
+    }
+
+    #[test]
+    fn test_streamed_text_hands_out_whole_words() {
+        let mut streamed = StreamedText::default();
+        let mut typed = Vec::new();
+        for delta in ["<think>x</think>\n  Hel", "lo wor", "ld, how", " are", " you?  "] {
+            streamed.push(delta);
+            typed.extend(streamed.next_words());
+        }
+        typed.extend(streamed.rest());
+
+        assert_eq!(typed, ["Hello", " world,", " how", " are you?"]);
+        assert_eq!(typed.concat(), streamed.typed());
+        assert_eq!(streamed.text(), "Hello world, how are you?  ");
+    }
+}
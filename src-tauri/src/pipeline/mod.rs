@@ -0,0 +1,1348 @@
+pub mod delivery;
+pub mod prompts;
+pub mod revisions;
+pub mod settings;
+pub mod streaming;
+
+pub use settings::{DictationSettings, ProviderChoice};
+
+use crate::audio::{AudioError, AudioRecorder, RecordingState, wav_duration_ms};
+use crate::database::{Database, DictationMetadata, NewTranscription};
+use crate::endpoint::CUSTOM_PROVIDER;
+use crate::models::registry::registry;
+use crate::provider::{Completion, ErrorInfo, ProviderError, TranscriptionRequest};
+use crate::reasoning::local::LOCAL_PROVIDER;
+use crate::reasoning::{self, ReasoningRequest};
+use crate::recordings::{self, Recordings, Retention};
+use crate::transcription;
+use crate::transcription::transcript::Transcript;
+use anyhow::{Result, anyhow};
+use delivery::StreamedText;
+use serde::Serialize;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::mpsc;
+
+/// Event emitted on every pipeline stage transition.
+pub const STAGE_EVENT: &str = "dictation-stage";
+/// Event carrying enhancement output as it streams in.
+pub const DELTA_EVENT: &str = "enhancement-delta";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stage {
+    Recording,
+    Transcribing,
+    Enhancing,
+    Pasted,
+    Done,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StagePayload {
+    pub stage: Stage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Structured form of `error` when a provider call failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_error: Option<ErrorInfo>,
+}
+
+impl StagePayload {
+    fn new(stage: Stage) -> Self {
+        Self {
+            stage,
+            text: None,
+            error: None,
+            provider_error: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeltaPayload {
+    pub provider: String,
+    /// Newly visible text.
+    pub delta: String,
+    /// All visible text of this response so far, `<think>` blocks removed.
+    pub text: String,
+}
+
+/// Result of a completed dictation.
+#[derive(Debug, Clone, Serialize)]
+pub struct DictationOutcome {
+    /// History row id, `None` when the recording contained no speech.
+    pub transcription_id: Option<i64>,
+    pub raw_text: String,
+    /// Text that was delivered (enhanced, or with debug labels in debug mode).
+    pub text: String,
+    pub pasted: bool,
+}
+
+/// What the pipeline starts from: the recorded clip, or text that live
+/// transcription already produced for it along with the clip.
+pub enum Input {
+    Audio(Vec<u8>),
+    Transcript { text: String, audio: Vec<u8> },
+}
+
+/// Side-effecting stages of the pipeline. [`AppBackend`] wires them to the real
+/// transcription, reasoning, database and clipboard modules; tests substitute
+/// a fake so the flow can run without a webview.
+pub trait Backend {
+    /// Transcribe with one provider from [`DictationSettings::transcription_chain`].
+    async fn transcribe(
+        &self,
+        audio: Vec<u8>,
+        choice: &ProviderChoice,
+        settings: &DictationSettings,
+        dictionary: &[String],
+    ) -> Result<Transcript>;
+    /// Enhance with one provider, sending the output to `deltas` as it arrives.
+    async fn enhance(&self, request: ReasoningRequest, deltas: mpsc::UnboundedSender<String>) -> Result<Completion>;
+    fn save(&self, record: &NewTranscription) -> Result<i64>;
+    /// Store the recording of history row `id` and apply `retention`.
+    fn keep_audio(&self, id: i64, audio: &[u8], retention: &Retention) -> Result<()>;
+    fn paste(&self, text: &str) -> Result<()>;
+    /// Type text with key events, leaving the clipboard alone.
+    fn type_text(&self, text: &str) -> Result<()>;
+    fn emit(&self, payload: StagePayload);
+    fn emit_delta(&self, payload: DeltaPayload);
+    /// Name of the microphone the recording came from.
+    fn input_device(&self) -> Option<String>;
+    /// Application the text will be delivered to.
+    fn target_app(&self) -> Option<String>;
+}
+
+pub struct AppBackend {
+    app: AppHandle,
+}
+
+impl AppBackend {
+    pub fn new(app: AppHandle) -> Self {
+        Self { app }
+    }
+}
+
+impl Backend for AppBackend {
+    async fn transcribe(
+        &self,
+        audio: Vec<u8>,
+        choice: &ProviderChoice,
+        settings: &DictationSettings,
+        dictionary: &[String],
+    ) -> Result<Transcript> {
+        let language = Some(settings.preferred_language.as_str());
+        let provider = choice.provider.as_str();
+        let model = &choice.model;
+        let timestamps = settings.transcript_timestamps;
+
+        if provider == "parakeet" {
+            let text = transcription::parakeet::transcribe(&self.app, &audio, model, language).await?;
+            return Ok(Transcript::plain(text));
+        }
+        if provider == "whisper" {
+            let file_name = &registry().whisper_model(model)?.file_name;
+            let (app, audio) = (&self.app, audio.as_slice());
+            return if settings.whisper_server {
+                transcription::whisper::transcribe_server(app, audio, file_name, language, dictionary, timestamps).await
+            } else {
+                transcription::whisper::transcribe(app, audio, file_name, language, dictionary, timestamps).await
+            };
+        }
+
+        let prompt = if dictionary.is_empty() {
+            None
+        } else {
+            Some(dictionary.join(" "))
+        };
+
+        let request = TranscriptionRequest {
+            audio,
+            api_key: settings.transcription_key(provider),
+            model,
+            language,
+            prompt: prompt.as_deref(),
+            timestamps,
+        };
+        Ok(transcription::transcribe_cloud(provider, settings.transcription_config(), request).await?)
+    }
+
+    async fn enhance(&self, request: ReasoningRequest, deltas: mpsc::UnboundedSender<String>) -> Result<Completion> {
+        // The receiver only goes away once the pipeline stopped listening
+        let on_delta = |delta: &str| {
+            let _ = deltas.send(delta.to_string());
+        };
+        let response = reasoning::process_stream(&request, &on_delta).await?;
+        Ok(Completion::new(response.text, response.usage))
+    }
+
+    fn save(&self, record: &NewTranscription) -> Result<i64> {
+        self.app.state::<Database>().save_transcription(record)
+    }
+
+    fn keep_audio(&self, id: i64, audio: &[u8], retention: &Retention) -> Result<()> {
+        recordings::keep(&self.app.state::<Database>(), &self.app.state::<Recordings>(), id, audio, retention)
+    }
+
+    fn paste(&self, text: &str) -> Result<()> {
+        crate::clipboard::paste_text(text)
+    }
+
+    fn type_text(&self, text: &str) -> Result<()> {
+        crate::clipboard::type_text(text)
+    }
+
+    fn emit(&self, payload: StagePayload) {
+        emit_stage(&self.app, payload);
+    }
+
+    fn emit_delta(&self, payload: DeltaPayload) {
+        if let Err(e) = self.app.emit(DELTA_EVENT, payload) {
+            log::warn!("[Whisperi] Failed to emit {}: {}", DELTA_EVENT, e);
+        }
+    }
+
+    fn input_device(&self) -> Option<String> {
+        self.app.state::<RecordingState>().device_name()
+    }
+
+    fn target_app(&self) -> Option<String> {
+        crate::clipboard::foreground_app()
+    }
+}
+
+/// Emit a stage event to all windows.
+pub fn emit_stage(app: &AppHandle, payload: StagePayload) {
+    if let Err(e) = app.emit(STAGE_EVENT, payload) {
+        log::warn!("[Whisperi] Failed to emit {}: {}", STAGE_EVENT, e);
+    }
+}
+
+/// Mark the start of a dictation once the recorder is running.
+pub fn emit_recording(app: &AppHandle) {
+    emit_stage(app, StagePayload::new(Stage::Recording));
+}
+
+/// Stop the active recording and run it through the full pipeline using the
+/// current settings from the store.
+pub async fn dictate(app: &AppHandle) -> Result<DictationOutcome> {
+    let backend = AppBackend::new(app.clone());
+
+    let audio = {
+        let state = app.state::<RecordingState>();
+        AudioRecorder::stop(&state)
+    };
+    let audio = match audio {
+        Ok(audio) => audio,
+        Err(AudioError::NoSpeech) => {
+            // Nothing to send: skip the provider instead of letting it hallucinate
+            log::info!("[Whisperi] No speech detected, skipping transcription.");
+            streaming::cancel(app);
+            let outcome = DictationOutcome {
+                transcription_id: None,
+                raw_text: String::new(),
+                text: String::new(),
+                pasted: false,
+            };
+            backend.emit(StagePayload {
+                text: Some(String::new()),
+                ..StagePayload::new(Stage::Done)
+            });
+            return Ok(outcome);
+        }
+        Err(e) => {
+            streaming::cancel(app);
+            backend.emit(failed(&e));
+            return Err(e.into());
+        }
+    };
+
+    let settings = match DictationSettings::load(app) {
+        Ok(settings) => settings,
+        Err(e) => {
+            log::error!("[Whisperi] Dictation failed: {:#}", e);
+            streaming::cancel(app);
+            backend.emit(failed(&e));
+            // The default retention keeps failed recordings, and no provider is involved
+            let metadata = DictationMetadata {
+                audio_duration_ms: wav_duration_ms(&audio),
+                input_device: backend.input_device(),
+                ..Default::default()
+            };
+            keep_failed(&backend, &DictationSettings::default(), &audio, &e, metadata);
+            return Err(e);
+        }
+    };
+
+    // Live transcription finishes the tail of the recording; fall back to a
+    // full transcription if it wasn't running or failed.
+    let input = match streaming::finish(app).await {
+        Some(text) => Input::Transcript { text, audio },
+        None => Input::Audio(audio),
+    };
+    run(&backend, &settings, input).await
+}
+
+/// Run a recorded WAV clip through Transcribe → [Enhance] → Paste → Save,
+/// emitting a stage event at each step. Emits `done` on success and
+/// `failed` on error. Enhancement output is emitted as it streams in and, with
+/// [`DictationSettings::incremental_paste`], typed as it arrives.
+pub async fn run<B: Backend>(
+    backend: &B,
+    settings: &DictationSettings,
+    input: Input,
+) -> Result<DictationOutcome> {
+    match run_stages(backend, settings, input).await {
+        Ok(outcome) => {
+            backend.emit(StagePayload {
+                text: Some(outcome.text.clone()),
+                ..StagePayload::new(Stage::Done)
+            });
+            Ok(outcome)
+        }
+        Err(e) => {
+            log::error!("[Whisperi] Dictation failed: {:#}", e);
+            backend.emit(StagePayload {
+                provider_error: e.downcast_ref::<ProviderError>().map(ProviderError::info),
+                ..failed(&e)
+            });
+            Err(e)
+        }
+    }
+}
+
+async fn run_stages<B: Backend>(
+    backend: &B,
+    settings: &DictationSettings,
+    input: Input,
+) -> Result<DictationOutcome> {
+    let started = Instant::now();
+    backend.emit(StagePayload::new(Stage::Transcribing));
+
+    let audio = match &input {
+        Input::Audio(audio) | Input::Transcript { audio, .. } => audio,
+    };
+    let mut metadata = DictationMetadata {
+        audio_duration_ms: wav_duration_ms(audio),
+        input_device: backend.input_device(),
+        requested_language: Some(settings.preferred_language.clone()).filter(|l| !l.is_empty()),
+        target_app: backend.target_app(),
+        ..Default::default()
+    };
+
+    let dictionary = settings.transcription_dictionary();
+    let (transcript, transcription, audio) = match input {
+        Input::Audio(audio) => match transcribe_with_fallbacks(backend, &audio, settings, &dictionary).await {
+            Ok((transcript, choice)) => {
+                metadata.transcription_ms = elapsed_ms(started);
+                (transcript, Some(choice), audio)
+            }
+            Err(e) => {
+                metadata.transcription_ms = elapsed_ms(started);
+                keep_failed(backend, settings, &audio, &e, metadata);
+                return Err(e);
+            }
+        },
+        // Live transcription only uses the primary provider
+        Input::Transcript { text, audio } => (
+            Transcript::plain(text),
+            settings.transcription_chain().into_iter().next(),
+            audio,
+        ),
+    };
+    if let Some(choice) = &transcription {
+        metadata.transcription_model = Some(choice.model.clone()).filter(|m| !m.is_empty());
+    }
+    let transcription_provider = transcription.map(|choice| choice.provider);
+    let Transcript {
+        text: raw_text,
+        language,
+        segments,
+    } = transcript;
+    log::info!("[Whisperi] Transcription: {} chars", raw_text.len());
+
+    // Skip when transcription is empty or just dictionary words echoed back (silence hallucination)
+    if is_empty_transcription(&raw_text, &dictionary) {
+        log::info!("[Whisperi] Empty transcription (silence or dictionary echo), skipping.");
+        return Ok(DictationOutcome {
+            transcription_id: None,
+            raw_text,
+            text: String::new(),
+            pasted: false,
+        });
+    }
+
+    let mut final_text = raw_text.clone();
+    let mut raw_ai_response = None;
+    let mut enhancement_error = None;
+    let mut reasoning_provider = None;
+    // Text typed while streaming; it replaces the final paste
+    let mut typed = None;
+    // What was left when typing failed partway, pasted instead
+    let mut untyped = None;
+    let mut typing = settings.auto_paste && settings.incremental_paste && !settings.debug_mode;
+    let requests = enhancement_requests(settings, &raw_text);
+    if !requests.is_empty() {
+        backend.emit(StagePayload::new(Stage::Enhancing));
+    }
+    let enhancement_started = Instant::now();
+    let attempts = requests.len();
+    for (attempt, request) in requests.into_iter().enumerate() {
+        let provider = request.provider.clone();
+        let model = request.model.clone();
+        log::info!(
+            "[Whisperi] Enhancing (attempt {}/{}): provider={}, model={}",
+            attempt + 1,
+            attempts,
+            provider,
+            request.model
+        );
+        let mut streamed = StreamedText::default();
+        let (deltas, mut received) = mpsc::unbounded_channel();
+        let result = {
+            let enhance = backend.enhance(request, deltas);
+            tokio::pin!(enhance);
+            loop {
+                tokio::select! {
+                    biased;
+                    Some(delta) = received.recv() => deliver(backend, &provider, &mut streamed, &delta, &mut typing),
+                    result = &mut enhance => break result,
+                }
+            }
+        };
+        while let Ok(delta) = received.try_recv() {
+            deliver(backend, &provider, &mut streamed, &delta, &mut typing);
+        }
+
+        match result {
+            Ok(completion) => {
+                if typing && let Some(rest) = streamed.rest() {
+                    type_words(backend, &mut streamed, &rest, &mut typing);
+                }
+                final_text = strip_think_tags(&completion.text);
+                raw_ai_response = Some(completion.text);
+                reasoning_provider = Some(provider);
+                metadata.reasoning_model = Some(model);
+                metadata.prompt_tokens = completion.usage.map(|u| u.prompt_tokens as i64);
+                metadata.completion_tokens = completion.usage.map(|u| u.completion_tokens as i64);
+                enhancement_error = None;
+                typed = Some(streamed.typed().to_string()).filter(|t| !t.is_empty());
+                if typed.is_some() && !typing {
+                    untyped = streamed.rest();
+                }
+                break;
+            }
+            Err(e) => {
+                log::error!("[Whisperi] Enhancement with {} failed: {}", provider, e);
+                enhancement_error = Some(e);
+                // Typed text can't be taken back, so a fallback would repeat it
+                if !streamed.typed().is_empty() {
+                    log::warn!("[Whisperi] {} failed mid-stream, keeping the text typed so far", provider);
+                    typed = Some(streamed.typed().to_string());
+                    // Typing stopped earlier: paste what arrived after it
+                    if !typing {
+                        untyped = streamed.rest();
+                    }
+                    final_text = streamed.typed().to_string();
+                    reasoning_provider = Some(provider);
+                    metadata.reasoning_model = Some(model);
+                    break;
+                }
+            }
+        }
+    }
+    if attempts > 0 {
+        metadata.enhancement_ms = elapsed_ms(enhancement_started);
+    }
+    if let Some(e) = enhancement_error.as_ref().filter(|_| typed.is_none()) {
+        log::warn!("[Whisperi] No enhancement provider succeeded, keeping the raw transcription");
+        if settings.debug_mode {
+            final_text = format!("{}\n\n[Enhancement Error]\n{}", raw_text, e);
+        }
+    }
+
+    // In debug mode, output both raw and enhanced with labels, plus the raw AI
+    // response (with <think> tags) so the user can inspect reasoning
+    let text = if settings.debug_mode && final_text != raw_text {
+        let mut out = format!("[Transcription]\n{}\n\n[Enhanced]\n{}", raw_text, final_text);
+        if let Some(raw_ai) = raw_ai_response.as_ref().filter(|r| **r != final_text) {
+            out.push_str(&format!("\n\n[Raw AI Response]\n{}", raw_ai));
+        }
+        out
+    } else {
+        final_text.clone()
+    };
+
+    // Pasted before saving, so the row records whether it worked
+    let pasted = settings.auto_paste;
+    let mut paste_result = Ok(());
+    if pasted {
+        let remainder = if typed.is_none() { Some(&text) } else { untyped.as_ref() };
+        if let Some(remainder) = remainder {
+            let paste_started = Instant::now();
+            paste_result = backend.paste(remainder);
+            metadata.paste_ms = elapsed_ms(paste_started);
+        }
+        metadata.paste_succeeded = Some(paste_result.is_ok());
+    }
+    metadata.total_ms = elapsed_ms(started);
+
+    // A dictation that didn't reach its window keeps its recording for another try
+    let failed = enhancement_error.is_some() || paste_result.is_err();
+    let transcription_id = backend.save(&NewTranscription {
+        original_text: raw_text.clone(),
+        processed_text: (final_text != raw_text).then(|| final_text.clone()),
+        processing_method: if settings.use_reasoning_model { "ai" } else { "none" }.to_string(),
+        agent_name: Some(settings.agent_name().to_string()),
+        error: enhancement_error.map(|e| e.to_string()),
+        transcription_provider,
+        reasoning_provider,
+        language,
+        segments,
+        parent_id: None,
+        metadata,
+    })?;
+
+    keep_audio(backend, settings, transcription_id, &audio, failed);
+    paste_result?;
+    if pasted {
+        backend.emit(StagePayload::new(Stage::Pasted));
+    }
+
+    Ok(DictationOutcome {
+        transcription_id: Some(transcription_id),
+        raw_text,
+        text,
+        pasted,
+    })
+}
+
+/// Pass one streamed delta on to the UI, typing the words it completed when
+/// `typing` is set. Words are typed as key events rather than pasted, since a
+/// clipboard round trip per word would race and overwrite the user's clipboard.
+fn deliver<B: Backend>(backend: &B, provider: &str, streamed: &mut StreamedText, delta: &str, typing: &mut bool) {
+    let visible = streamed.push(delta);
+    if !visible.is_empty() {
+        backend.emit_delta(DeltaPayload {
+            provider: provider.to_string(),
+            delta: visible,
+            text: streamed.text().to_string(),
+        });
+    }
+    if *typing && let Some(words) = streamed.next_words() {
+        type_words(backend, streamed, &words, typing);
+    }
+}
+
+/// Type `words`. When that fails (no typing tool, focus moved), they go back
+/// to `streamed` and typing stops, leaving the rest for the final paste.
+fn type_words<B: Backend>(backend: &B, streamed: &mut StreamedText, words: &str, typing: &mut bool) {
+    if let Err(e) = backend.type_text(words) {
+        log::warn!("[Whisperi] Typing failed, pasting the rest instead: {:#}", e);
+        streamed.give_back(words);
+        *typing = false;
+    }
+}
+
+/// Keep the recording of history row `id` if the retention settings cover it.
+/// Losing the recording doesn't fail the dictation.
+fn keep_audio<B: Backend>(backend: &B, settings: &DictationSettings, id: i64, audio: &[u8], failed: bool) {
+    let retention = settings.audio_retention();
+    if audio.is_empty() || !retention.keeps(failed) {
+        return;
+    }
+    if let Err(e) = backend.keep_audio(id, audio, &retention) {
+        log::warn!("[Whisperi] Failed to keep the recording of dictation {}: {:#}", id, e);
+    }
+}
+
+/// Save a row for a recording no provider could transcribe, so its audio
+/// can be kept for another try.
+fn keep_failed<B: Backend>(
+    backend: &B,
+    settings: &DictationSettings,
+    audio: &[u8],
+    error: &anyhow::Error,
+    metadata: DictationMetadata,
+) {
+    if audio.is_empty() || !settings.audio_retention().keeps(true) {
+        return;
+    }
+    let saved = backend.save(&NewTranscription {
+        processing_method: "none".to_string(),
+        agent_name: Some(settings.agent_name().to_string()),
+        error: Some(format!("{:#}", error)),
+        metadata,
+        ..Default::default()
+    });
+    match saved {
+        Ok(id) => keep_audio(backend, settings, id, audio, true),
+        Err(e) => log::warn!("[Whisperi] Failed to save the failed dictation: {:#}", e),
+    }
+}
+
+/// Try each provider of the transcription chain until one succeeds, returning
+/// the transcript and the provider and model that produced it. Fails with the
+/// last error.
+async fn transcribe_with_fallbacks<B: Backend>(
+    backend: &B,
+    audio: &[u8],
+    settings: &DictationSettings,
+    dictionary: &[String],
+) -> Result<(Transcript, ProviderChoice)> {
+    let chain = settings.transcription_chain();
+    let mut last_error = None;
+    for (attempt, choice) in chain.iter().enumerate() {
+        log::info!(
+            "[Whisperi] Transcribing (attempt {}/{}): provider={}, model={}",
+            attempt + 1,
+            chain.len(),
+            choice.provider,
+            choice.model
+        );
+        match backend.transcribe(audio.to_vec(), choice, settings, dictionary).await {
+            Ok(transcript) => return Ok((transcript, choice.clone())),
+            Err(e) => {
+                log::error!("[Whisperi] Transcription with {} failed: {:#}", choice.provider, e);
+                last_error = Some(e);
+            }
+        }
+    }
+    Err(last_error.unwrap_or_else(|| anyhow!("No transcription provider configured")))
+}
+
+/// Build a reasoning request for each provider of the enhancement chain,
+/// skipping unconfigured ones. Empty when enhancement is disabled.
+fn enhancement_requests(settings: &DictationSettings, raw_text: &str) -> Vec<ReasoningRequest> {
+    if !settings.use_reasoning_model {
+        return Vec::new();
+    }
+
+    let agent_name = settings.agent_name();
+    let language = Some(settings.preferred_language.as_str());
+    let system_prompt = if prompts::detect_chat_mode(raw_text, agent_name, &settings.agent_aliases) {
+        prompts::chat_system_prompt(agent_name, &settings.custom_dictionary, language)
+    } else {
+        let custom = (settings.use_custom_prompt && !settings.custom_system_prompt.is_empty())
+            .then_some(settings.custom_system_prompt.as_str());
+        prompts::system_prompt(agent_name, &settings.custom_dictionary, language, custom)
+    };
+
+    settings
+        .reasoning_chain()
+        .into_iter()
+        .filter_map(|choice| enhancement_request(settings, choice, raw_text, &system_prompt))
+        .collect()
+}
+
+/// Reasoning request for one provider, or `None` when it has no model or
+/// needs an API key that isn't configured.
+fn enhancement_request(
+    settings: &DictationSettings,
+    choice: ProviderChoice,
+    raw_text: &str,
+    system_prompt: &str,
+) -> Option<ReasoningRequest> {
+    let provider = choice.provider.as_str();
+    if choice.model.is_empty() {
+        log::warn!("[Whisperi] No model configured for enhancement provider: {}", provider);
+        return None;
+    }
+
+    // Self-hosted endpoints may not need a key, local servers never do
+    let custom = provider == CUSTOM_PROVIDER;
+    let local = provider == LOCAL_PROVIDER;
+    let api_key = if local {
+        ""
+    } else if let Some(api_key) = settings.reasoning_key(provider) {
+        api_key
+    } else if custom {
+        ""
+    } else {
+        log::warn!("[Whisperi] No API key for enhancement provider: {}", provider);
+        return None;
+    };
+
+    Some(ReasoningRequest {
+        text: prompts::user_prompt(raw_text),
+        model: choice.model,
+        provider: choice.provider,
+        system_prompt: system_prompt.to_string(),
+        api_key: api_key.to_string(),
+        max_tokens: None,
+        endpoint: custom.then(|| settings.custom_reasoning.clone()),
+        local: local.then(|| settings.local_reasoning.clone()),
+    })
+}
+
+fn elapsed_ms(since: Instant) -> Option<i64> {
+    Some(since.elapsed().as_millis() as i64)
+}
+
+fn failed(error: &dyn std::fmt::Display) -> StagePayload {
+    StagePayload {
+        error: Some(error.to_string()),
+        ..StagePayload::new(Stage::Failed)
+    }
+}
+
+/// Strip `<think>...</think>` blocks from reasoning model output.
+fn strip_think_tags(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("<think>") {
+        let Some(end) = rest[start..].find("</think>") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        rest = &rest[start + end + "</think>".len()..];
+    }
+    out.push_str(rest);
+    out.trim().to_string()
+}
+
+/// Check if transcription is empty or just dictionary words echoed back
+/// (Whisper hallucination on silence).
+fn is_empty_transcription(text: &str, dictionary: &[String]) -> bool {
+    let normalize = |s: &str| -> Vec<String> {
+        s.to_lowercase()
+            .chars()
+            .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+            .collect::<String>()
+            .split_whitespace()
+            .map(str::to_string)
+            .collect()
+    };
+
+    let words = normalize(text);
+    if words.is_empty() {
+        return true;
+    }
+    if dictionary.is_empty() {
+        return false;
+    }
+
+    let dict_words: std::collections::HashSet<String> =
+        dictionary.iter().flat_map(|entry| normalize(entry)).collect();
+    words.iter().all(|w| dict_words.contains(w))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Transcription;
+    use crate::provider::TokenUsage;
+    use crate::transcription::transcript::Segment;
+    use anyhow::Context;
+    use std::cell::RefCell;
+
+    #[derive(Default)]
+    struct FakeBackend {
+        transcript: String,
+        segments: Vec<Segment>,
+        enhanced: Option<String>,
+        /// Providers whose calls fail.
+        down: Vec<&'static str>,
+        /// Providers whose enhancement stream breaks halfway.
+        broken_stream: Vec<&'static str>,
+        /// Providers called, in order.
+        attempts: RefCell<Vec<String>>,
+        stages: RefCell<Vec<Stage>>,
+        /// Accumulated text of each emitted delta.
+        deltas: RefCell<Vec<String>>,
+        saved: RefCell<Vec<NewTranscription>>,
+        /// History row ids whose recording was kept.
+        kept: RefCell<Vec<i64>>,
+        pasted: RefCell<Vec<String>>,
+        typed: RefCell<Vec<String>>,
+        paste_fails: bool,
+        /// Typing fails once this many pieces were typed.
+        typing_fails_after: Option<usize>,
+    }
+
+    impl Backend for FakeBackend {
+        async fn transcribe(
+            &self,
+            _: Vec<u8>,
+            choice: &ProviderChoice,
+            _: &DictationSettings,
+            _: &[String],
+        ) -> Result<Transcript> {
+            self.call(&choice.provider)?;
+            Ok(Transcript {
+                text: self.transcript.clone(),
+                segments: self.segments.clone(),
+                ..Default::default()
+            })
+        }
+
+        async fn enhance(&self, request: ReasoningRequest, deltas: mpsc::UnboundedSender<String>) -> Result<Completion> {
+            self.call(&request.provider)?;
+            let enhanced = self.enhanced.clone().context("provider unavailable")?;
+            // Stream word by word, letting the pipeline handle each chunk
+            let chunks: Vec<_> = enhanced.split_inclusive(' ').collect();
+            let broken = self.broken_stream.contains(&request.provider.as_str());
+            let sent = if broken { chunks.len() / 2 } else { chunks.len() };
+            for chunk in &chunks[..sent] {
+                deltas.send(chunk.to_string())?;
+                tokio::task::yield_now().await;
+            }
+            if broken {
+                anyhow::bail!("{} stream interrupted", request.provider);
+            }
+            let usage = TokenUsage {
+                prompt_tokens: 42,
+                completion_tokens: chunks.len() as u32,
+            };
+            Ok(Completion::new(enhanced, Some(usage)))
+        }
+
+        fn save(&self, record: &NewTranscription) -> Result<i64> {
+            self.saved.borrow_mut().push(record.clone());
+            Ok(self.saved.borrow().len() as i64)
+        }
+
+        fn keep_audio(&self, id: i64, _: &[u8], _: &Retention) -> Result<()> {
+            self.kept.borrow_mut().push(id);
+            Ok(())
+        }
+
+        fn paste(&self, text: &str) -> Result<()> {
+            if self.paste_fails {
+                anyhow::bail!("no window to paste into");
+            }
+            self.pasted.borrow_mut().push(text.to_string());
+            Ok(())
+        }
+
+        fn type_text(&self, text: &str) -> Result<()> {
+            if self.typing_fails_after.is_some_and(|n| self.typed.borrow().len() >= n) {
+                anyhow::bail!("no typing tool found");
+            }
+            self.typed.borrow_mut().push(text.to_string());
+            Ok(())
+        }
+
+        fn emit(&self, payload: StagePayload) {
+            self.stages.borrow_mut().push(payload.stage);
+        }
+
+        fn emit_delta(&self, payload: DeltaPayload) {
+            self.deltas.borrow_mut().push(payload.text);
+        }
+
+        fn input_device(&self) -> Option<String> {
+            Some("USB Microphone".to_string())
+        }
+
+        fn target_app(&self) -> Option<String> {
+            Some("Code".to_string())
+        }
+    }
+
+    impl FakeBackend {
+        fn call(&self, provider: &str) -> Result<()> {
+            self.attempts.borrow_mut().push(provider.to_string());
+            if self.down.contains(&provider) {
+                anyhow::bail!("{} is unavailable", provider);
+            }
+            Ok(())
+        }
+    }
+
+    fn settings_with_key() -> DictationSettings {
+        let mut settings = DictationSettings::default();
+        settings.api_keys.insert("openai".to_string(), "sk-test".to_string());
+        settings
+    }
+
+    #[tokio::test]
+    async fn test_run_enhances_saves_and_pastes() {
+        let segment = Segment {
+            start: 0.0,
+            end: 1.2,
+            text: "hello world".to_string(),
+            confidence: Some(0.4),
+            avg_logprob: None,
+        };
+        let backend = FakeBackend {
+            transcript: "hello world".to_string(),
+            segments: vec![segment.clone()],
+            enhanced: Some("<think>hmm</think>Hello, world.".to_string()),
+            ..Default::default()
+        };
+
+        let outcome = run(&backend, &settings_with_key(), Input::Audio(Vec::new())).await.unwrap();
+
+        assert_eq!(outcome.text, "Hello, world.");
+        assert_eq!(
+            *backend.stages.borrow(),
+            [Stage::Transcribing, Stage::Enhancing, Stage::Pasted, Stage::Done]
+        );
+        assert_eq!(*backend.pasted.borrow(), ["Hello, world."]);
+        let saved = backend.saved.borrow();
+        assert_eq!(saved[0].original_text, "hello world");
+        assert_eq!(saved[0].processed_text.as_deref(), Some("Hello, world."));
+        assert_eq!(saved[0].processing_method, "ai");
+        assert_eq!(saved[0].segments, [segment]);
+    }
+
+    #[tokio::test]
+    async fn test_run_records_dictation_metadata() {
+        let backend = FakeBackend {
+            transcript: "hello world".to_string(),
+            enhanced: Some("Hello, world.".to_string()),
+            ..Default::default()
+        };
+        let mut settings = settings_with_key();
+        settings.preferred_language = "de".to_string();
+        let wav = crate::audio::encode_wav(&vec![0.1; 24_000], 16_000).unwrap();
+
+        run(&backend, &settings, Input::Audio(wav)).await.unwrap();
+
+        let metadata = &backend.saved.borrow()[0].metadata;
+        assert_eq!(metadata.audio_duration_ms, Some(1500));
+        assert_eq!(metadata.input_device.as_deref(), Some("USB Microphone"));
+        assert_eq!(metadata.requested_language.as_deref(), Some("de"));
+        assert_eq!(metadata.transcription_model, settings.transcription_chain().remove(0).model.into());
+        assert_eq!(metadata.reasoning_model.as_deref(), Some(settings.reasoning_model.as_str()));
+        assert_eq!((metadata.prompt_tokens, metadata.completion_tokens), (Some(42), Some(2)));
+        assert_eq!(metadata.target_app.as_deref(), Some("Code"));
+        assert_eq!(metadata.paste_succeeded, Some(true));
+        let timings = [metadata.transcription_ms, metadata.enhancement_ms, metadata.paste_ms, metadata.total_ms];
+        assert!(timings.iter().all(Option::is_some));
+        assert!(metadata.total_ms >= metadata.enhancement_ms);
+    }
+
+    #[tokio::test]
+    async fn test_run_saves_the_row_when_paste_fails() {
+        let backend = FakeBackend {
+            transcript: "hello world".to_string(),
+            paste_fails: true,
+            ..Default::default()
+        };
+        let mut settings = settings_with_key();
+        settings.use_reasoning_model = false;
+
+        let error = run(&backend, &settings, Input::Audio(b"RIFF".to_vec())).await.unwrap_err();
+
+        assert_eq!(error.to_string(), "no window to paste into");
+        // Kept under the default `KeepAudio::Failures`
+        assert_eq!(*backend.kept.borrow(), [1]);
+        let saved = backend.saved.borrow();
+        assert_eq!(saved[0].original_text, "hello world");
+        assert_eq!(saved[0].metadata.paste_succeeded, Some(false));
+        assert_eq!(saved[0].metadata.enhancement_ms, None);
+        assert_eq!(*backend.stages.borrow(), [Stage::Transcribing, Stage::Failed]);
+    }
+
+    #[tokio::test]
+    async fn test_run_types_enhancement_as_it_streams() {
+        let backend = FakeBackend {
+            transcript: "hello big world".to_string(),
+            enhanced: Some("<think>hmm</think>Hello, big world.".to_string()),
+            ..Default::default()
+        };
+        let mut settings = settings_with_key();
+        settings.incremental_paste = true;
+
+        let outcome = run(&backend, &settings, Input::Audio(Vec::new())).await.unwrap();
+
+        assert_eq!(outcome.text, "Hello, big world.");
+        assert_eq!(*backend.typed.borrow(), ["Hello,", " big", " world."]);
+        assert!(backend.pasted.borrow().is_empty());
+        assert_eq!(backend.deltas.borrow().last().map(String::as_str), Some("Hello, big world."));
+        assert_eq!(
+            *backend.stages.borrow(),
+            [Stage::Transcribing, Stage::Enhancing, Stage::Pasted, Stage::Done]
+        );
+        assert_eq!(backend.saved.borrow()[0].processed_text.as_deref(), Some("Hello, big world."));
+    }
+
+    #[tokio::test]
+    async fn test_run_pastes_the_rest_when_typing_fails() {
+        let backend = FakeBackend {
+            transcript: "hello big world".to_string(),
+            enhanced: Some("Hello, big world.".to_string()),
+            typing_fails_after: Some(1),
+            ..Default::default()
+        };
+        let mut settings = settings_with_key();
+        settings.incremental_paste = true;
+
+        let outcome = run(&backend, &settings, Input::Audio(Vec::new())).await.unwrap();
+
+        assert_eq!(outcome.text, "Hello, big world.");
+        assert_eq!(*backend.typed.borrow(), ["Hello,"]);
+        assert_eq!(*backend.pasted.borrow(), [" big world."]);
+        let saved = backend.saved.borrow();
+        assert_eq!(saved[0].processed_text.as_deref(), Some("Hello, big world."));
+        assert_eq!(saved[0].metadata.paste_succeeded, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_run_saves_the_row_when_typing_and_paste_fail() {
+        let backend = FakeBackend {
+            transcript: "hello big world".to_string(),
+            enhanced: Some("Hello, big world.".to_string()),
+            typing_fails_after: Some(0),
+            paste_fails: true,
+            ..Default::default()
+        };
+        let mut settings = settings_with_key();
+        settings.incremental_paste = true;
+
+        let error = run(&backend, &settings, Input::Audio(b"RIFF".to_vec())).await.unwrap_err();
+
+        assert_eq!(error.to_string(), "no window to paste into");
+        assert!(backend.typed.borrow().is_empty());
+        let saved = backend.saved.borrow();
+        assert_eq!(saved[0].processed_text.as_deref(), Some("Hello, big world."));
+        assert_eq!(saved[0].metadata.paste_succeeded, Some(false));
+        assert_eq!(*backend.kept.borrow(), [1]);
+    }
+
+    #[tokio::test]
+    async fn test_run_keeps_typed_text_when_stream_breaks() {
+        let backend = FakeBackend {
+            transcript: "hello there big world".to_string(),
+            enhanced: Some("Hello there, big world.".to_string()),
+            broken_stream: vec!["openai"],
+            ..Default::default()
+        };
+        let mut settings = settings_with_key();
+        settings.incremental_paste = true;
+        settings.api_keys.insert("anthropic".to_string(), "sk-ant".to_string());
+        settings.reasoning_fallbacks = vec![ProviderChoice {
+            provider: "anthropic".to_string(),
+            model: String::new(),
+        }];
+
+        let outcome = run(&backend, &settings, Input::Audio(Vec::new())).await.unwrap();
+
+        // The fallback would type its text after what's already there
+        assert_eq!(*backend.attempts.borrow(), ["openai", "openai"]);
+        assert_eq!(*backend.typed.borrow(), ["Hello", " there,"]);
+        assert!(backend.pasted.borrow().is_empty());
+        assert_eq!(outcome.text, "Hello there,");
+        let saved = backend.saved.borrow();
+        assert_eq!(saved[0].processed_text.as_deref(), Some("Hello there,"));
+        assert!(saved[0].error.as_deref().unwrap().contains("stream interrupted"));
+    }
+
+    #[tokio::test]
+    async fn test_run_skips_empty_transcription() {
+        let backend = FakeBackend {
+            transcript: " Whisperi. ".to_string(),
+            ..Default::default()
+        };
+
+        let outcome = run(&backend, &settings_with_key(), Input::Audio(Vec::new())).await.unwrap();
+
+        assert!(outcome.transcription_id.is_none());
+        assert!(backend.saved.borrow().is_empty());
+        assert!(backend.pasted.borrow().is_empty());
+        assert_eq!(*backend.stages.borrow(), [Stage::Transcribing, Stage::Done]);
+    }
+
+    #[tokio::test]
+    async fn test_run_keeps_raw_text_when_enhancement_fails() {
+        let backend = FakeBackend {
+            transcript: "hello world".to_string(),
+            ..Default::default()
+        };
+        let mut settings = settings_with_key();
+        settings.auto_paste = false;
+
+        let outcome = run(&backend, &settings, Input::Audio(Vec::new())).await.unwrap();
+
+        assert_eq!(outcome.text, "hello world");
+        assert!(!outcome.pasted);
+        let saved = backend.saved.borrow();
+        assert_eq!(saved[0].processed_text, None);
+        assert_eq!(saved[0].metadata.reasoning_model, None);
+        assert_eq!(saved[0].metadata.paste_succeeded, None);
+        assert!(saved[0].error.as_deref().unwrap().contains("provider unavailable"));
+    }
+
+    #[tokio::test]
+    async fn test_run_falls_back_through_provider_chains() {
+        let backend = FakeBackend {
+            transcript: "hello world".to_string(),
+            enhanced: Some("Hello, world.".to_string()),
+            down: vec!["groq", "anthropic"],
+            ..Default::default()
+        };
+        let mut settings = settings_with_key();
+        settings.api_keys.insert("anthropic".to_string(), "sk-ant".to_string());
+        settings.cloud_transcription_provider = "groq".to_string();
+        settings.transcription_fallbacks = vec![ProviderChoice {
+            provider: "openai".to_string(),
+            model: String::new(),
+        }];
+        settings.reasoning_provider = "anthropic".to_string();
+        settings.reasoning_model = "claude-opus-4-6".to_string();
+        settings.reasoning_fallbacks = vec![
+            // No key configured: skipped without a call
+            ProviderChoice {
+                provider: "gemini".to_string(),
+                model: String::new(),
+            },
+            ProviderChoice {
+                provider: "openai".to_string(),
+                model: "gpt-5-mini".to_string(),
+            },
+        ];
+
+        let outcome = run(&backend, &settings, Input::Audio(Vec::new())).await.unwrap();
+
+        assert_eq!(outcome.text, "Hello, world.");
+        assert_eq!(*backend.attempts.borrow(), ["groq", "openai", "anthropic", "openai"]);
+        let saved = backend.saved.borrow();
+        assert_eq!(saved[0].transcription_provider.as_deref(), Some("openai"));
+        assert_eq!(saved[0].reasoning_provider.as_deref(), Some("openai"));
+        assert_eq!(saved[0].error, None);
+    }
+
+    #[tokio::test]
+    async fn test_run_fails_when_every_transcription_provider_fails() {
+        let backend = FakeBackend {
+            down: vec!["openai", "whisper"],
+            ..Default::default()
+        };
+        let mut settings = settings_with_key();
+        settings.transcription_fallbacks = vec![ProviderChoice {
+            provider: "local".to_string(),
+            model: String::new(),
+        }];
+
+        let error = run(&backend, &settings, Input::Audio(Vec::new())).await.unwrap_err();
+
+        assert_eq!(error.to_string(), "whisper is unavailable");
+        assert_eq!(*backend.attempts.borrow(), ["openai", "whisper"]);
+        assert!(backend.saved.borrow().is_empty());
+        assert_eq!(*backend.stages.borrow(), [Stage::Transcribing, Stage::Failed]);
+    }
+
+    #[tokio::test]
+    async fn test_run_keeps_audio_per_retention_policy() {
+        let clip = || Input::Audio(b"RIFF".to_vec());
+        let down = FakeBackend {
+            down: vec!["openai"],
+            ..Default::default()
+        };
+        assert!(run(&down, &settings_with_key(), clip()).await.is_err());
+        assert_eq!(down.saved.borrow()[0].original_text, "");
+        assert_eq!(down.saved.borrow()[0].error.as_deref(), Some("openai is unavailable"));
+        assert_eq!(*down.kept.borrow(), [1]);
+
+        // Only failures are kept by default
+        let backend = FakeBackend {
+            transcript: "hello world".to_string(),
+            enhanced: Some("Hello, world.".to_string()),
+            ..Default::default()
+        };
+        run(&backend, &settings_with_key(), clip()).await.unwrap();
+        assert!(backend.kept.borrow().is_empty());
+
+        let mut settings = settings_with_key();
+        settings.keep_audio = recordings::KeepAudio::All;
+        let live = Input::Transcript {
+            text: "hello world".to_string(),
+            audio: b"RIFF".to_vec(),
+        };
+        let outcome = run(&backend, &settings, live).await.unwrap();
+        assert_eq!(*backend.kept.borrow(), [outcome.transcription_id.unwrap()]);
+    }
+
+    fn history_row(id: i64, text: &str) -> Transcription {
+        Transcription {
+            id,
+            timestamp: String::new(),
+            original_text: text.to_string(),
+            processed_text: None,
+            is_processed: false,
+            processing_method: "none".to_string(),
+            agent_name: None,
+            error: Some("openai is unavailable".to_string()),
+            transcription_provider: Some("openai".to_string()),
+            reasoning_provider: None,
+            language: None,
+            segments: Vec::new(),
+            audio_size: Some(1024),
+            parent_id: None,
+            metadata: DictationMetadata {
+                input_device: Some("USB Microphone".to_string()),
+                target_app: Some("Code".to_string()),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retranscribe_saves_a_revision_with_the_chosen_engine() {
+        let backend = FakeBackend {
+            transcript: "hello again".to_string(),
+            ..Default::default()
+        };
+        let mut settings = settings_with_key();
+        settings.transcription_fallbacks = vec![ProviderChoice {
+            provider: "openai".to_string(),
+            model: String::new(),
+        }];
+        let engine = revisions::Engine {
+            provider: "groq".to_string(),
+            ..Default::default()
+        };
+
+        let id = revisions::transcribe_again(&backend, &settings, &history_row(3, ""), b"RIFF", &engine).await.unwrap();
+
+        assert_eq!(*backend.attempts.borrow(), ["groq"], "no fallbacks for a chosen engine");
+        let saved = &backend.saved.borrow()[id as usize - 1];
+        assert_eq!(saved.original_text, "hello again");
+        assert_eq!(saved.transcription_provider.as_deref(), Some("groq"));
+        assert_eq!(saved.metadata.input_device.as_deref(), Some("USB Microphone"));
+        assert_eq!(saved.metadata.target_app, None, "a revision isn't delivered");
+        assert_eq!(saved.parent_id, Some(3));
+        assert!(backend.stages.borrow().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reenhance_links_revisions_to_the_original() {
+        let backend = FakeBackend {
+            enhanced: Some("<think>x</think>Hello, world.".to_string()),
+            down: vec!["openai"],
+            ..Default::default()
+        };
+        let mut settings = settings_with_key();
+        settings.use_reasoning_model = false;
+        settings.api_keys.insert("anthropic".to_string(), "sk-ant".to_string());
+        settings.reasoning_fallbacks = vec![ProviderChoice {
+            provider: "anthropic".to_string(),
+            model: String::new(),
+        }];
+        let revision = Transcription {
+            parent_id: Some(3),
+            ..history_row(5, "hello world")
+        };
+        let enhancement = revisions::Enhancement {
+            prompt: Some("Make it formal.".to_string()),
+            agent_name: Some("Jarvis".to_string()),
+        };
+
+        revisions::enhance_again(&backend, &settings, &revision, &enhancement).await.unwrap();
+
+        assert_eq!(*backend.attempts.borrow(), ["openai", "anthropic"]);
+        let saved = &backend.saved.borrow()[0];
+        assert_eq!(saved.original_text, "hello world");
+        assert_eq!(saved.processed_text.as_deref(), Some("Hello, world."));
+        assert_eq!(saved.reasoning_provider.as_deref(), Some("anthropic"));
+        assert_eq!(saved.agent_name.as_deref(), Some("Jarvis"));
+        assert_eq!(saved.metadata.completion_tokens, Some(2));
+        assert_eq!(saved.parent_id, Some(3));
+    }
+
+    #[test]
+    fn test_provider_chains_resolve_models_and_drop_repeats() {
+        let mut settings = DictationSettings {
+            cloud_transcription_provider: "groq".to_string(),
+            cloud_transcription_model: "whisper-large-v3".to_string(),
+            ..Default::default()
+        };
+        let choice = |provider: &str| ProviderChoice {
+            provider: provider.to_string(),
+            model: String::new(),
+        };
+        settings.transcription_fallbacks = vec![choice("local"), choice("openai"), choice("local"), choice("")];
+
+        let chain = settings.transcription_chain();
+        let providers: Vec<_> = chain.iter().map(|c| c.provider.as_str()).collect();
+        assert_eq!(providers, ["groq", "whisper", "openai"]);
+        assert_eq!(chain[1].model, "base");
+        let openai = registry().transcription_provider("openai").unwrap();
+        assert_eq!(Some(chain[2].model.as_str()), openai.default_model());
+
+        settings.reasoning_provider = "local".to_string();
+        settings.reasoning_model = "qwen3:8b".to_string();
+        settings.reasoning_fallbacks = vec![choice("local"), choice("anthropic")];
+        let chain = settings.reasoning_chain();
+        assert_eq!(chain.len(), 2, "local with the primary's model is a repeat");
+        assert_eq!(chain[1].provider, "anthropic");
+        assert!(!chain[1].model.is_empty());
+    }
+
+    #[test]
+    fn test_enhancement_request_for_custom_endpoint() {
+        let mut settings = DictationSettings {
+            reasoning_provider: "custom".to_string(),
+            ..Default::default()
+        };
+        settings.custom_reasoning.base_url = "http://localhost:8000/v1".to_string();
+        assert!(enhancement_requests(&settings, "hello").is_empty(), "no model configured");
+
+        settings.custom_reasoning.model = "qwen3-32b".to_string();
+        let request = enhancement_requests(&settings, "hello").remove(0);
+        assert_eq!(request.model, "qwen3-32b");
+        assert_eq!(request.api_key, "");
+        assert_eq!(request.endpoint.as_ref(), Some(&settings.custom_reasoning));
+    }
+
+    #[test]
+    fn test_enhancement_request_for_local_server_needs_no_key() {
+        let settings = DictationSettings {
+            reasoning_provider: "local".to_string(),
+            reasoning_model: "qwen3:8b".to_string(),
+            ..Default::default()
+        };
+        let request = enhancement_requests(&settings, "hello").remove(0);
+        assert_eq!(request.model, "qwen3:8b");
+        assert_eq!(request.api_key, "");
+        assert_eq!(request.local.as_ref(), Some(&settings.local_reasoning));
+        assert!(request.endpoint.is_none());
+    }
+
+    #[test]
+    fn test_strip_think_tags() {
+        assert_eq!(strip_think_tags("<think>a\nb</think> Done "), "Done");
+        assert_eq!(strip_think_tags("A <think>x</think>B<think>y</think>"), "A B");
+        assert_eq!(strip_think_tags("no tags"), "no tags");
+    }
+
+    #[test]
+    fn test_settings_from_map_applies_defaults_and_collects_keys() {
+        let map = serde_json::json!({
+            "whisperModel": "small",
+            "reasoningModel": null,
+            "groqApiKey": "gsk-1",
+            "openaiApiKey": "",
+        });
+        let (settings, invalid) = DictationSettings::from_map(map.as_object().unwrap().clone());
+
+        assert!(invalid.is_empty());
+        assert_eq!(settings.whisper_model, "small");
+        assert_eq!(settings.reasoning_model, "gpt-5-mini");
+        assert_eq!(settings.api_key("groq"), Some("gsk-1"));
+        assert_eq!(settings.api_key("openai"), None);
+    }
+
+    #[test]
+    fn test_settings_from_map_falls_back_per_key() {
+        let map = serde_json::json!({
+            "useLocalWhisper": true,
+            "whisperModel": "small",
+            "reasoningProvider": "anthropic",
+            "audioRetentionDays": "thirty",
+        });
+        let (settings, invalid) = DictationSettings::from_map(map.as_object().unwrap().clone());
+
+        assert_eq!(invalid, ["audioRetentionDays"]);
+        assert!(settings.use_local_whisper, "a bad key leaves the others as set");
+        assert_eq!(settings.whisper_model, "small");
+        assert_eq!(settings.reasoning_provider, "anthropic");
+        assert_eq!(settings.audio_retention_days, DictationSettings::default().audio_retention_days);
+    }
+}
@@ -0,0 +1,121 @@
+//! Prompt assembly for AI enhancement. Mirrors `src/config/prompts.ts` and reads
+//! the same JSON files, so the frontend and backend never drift apart.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+#[derive(Deserialize)]
+#[allow(non_snake_case)]
+struct PromptData {
+    INTERNAL_SYSTEM_PROMPT: String,
+    USER_VISIBLE_PROMPT: String,
+    CHAT_SYSTEM_PROMPT: String,
+    DICTIONARY_SUFFIX: String,
+}
+
+#[derive(Deserialize)]
+struct LanguageRegistry {
+    #[serde(rename = "_genericTemplate")]
+    generic_template: String,
+    #[serde(rename = "_autoDetectInstruction")]
+    auto_detect_instruction: String,
+    languages: Vec<LanguageEntry>,
+}
+
+#[derive(Deserialize)]
+struct LanguageEntry {
+    code: String,
+    instruction: Option<String>,
+}
+
+static PROMPTS: LazyLock<PromptData> = LazyLock::new(|| {
+    serde_json::from_str(include_str!("../../../src/config/promptData.json"))
+        .expect("Invalid promptData.json")
+});
+
+static LANGUAGES: LazyLock<LanguageRegistry> = LazyLock::new(|| {
+    serde_json::from_str(include_str!("../../../src/config/languageRegistry.json"))
+        .expect("Invalid languageRegistry.json")
+});
+
+static LANGUAGE_INSTRUCTIONS: LazyLock<HashMap<&'static str, &'static str>> =
+    LazyLock::new(|| {
+        LANGUAGES
+            .languages
+            .iter()
+            .filter_map(|l| l.instruction.as_deref().map(|i| (l.code.as_str(), i)))
+            .collect()
+    });
+
+/// Check if the transcribed text contains the agent name or any alias
+/// (case-insensitive), indicating the user is addressing the agent directly.
+pub fn detect_chat_mode(text: &str, agent_name: &str, aliases: &[String]) -> bool {
+    let lower = text.to_lowercase();
+    std::iter::once(agent_name)
+        .chain(aliases.iter().map(String::as_str))
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .any(|name| lower.contains(&name.to_lowercase()))
+}
+
+/// Build the system prompt for AI reasoning (cleanup mode).
+/// The internal system prompt is always prepended; the user-visible portion
+/// can be replaced by a custom prompt.
+pub fn system_prompt(
+    agent_name: &str,
+    dictionary: &[String],
+    language: Option<&str>,
+    custom_prompt: Option<&str>,
+) -> String {
+    let name = display_name(agent_name);
+
+    let mut prompt = PROMPTS.INTERNAL_SYSTEM_PROMPT.replace("{{agentName}}", name);
+    let user_part = custom_prompt.unwrap_or(&PROMPTS.USER_VISIBLE_PROMPT);
+    prompt.push_str("\n\n");
+    prompt.push_str(&user_part.replace("{{agentName}}", name));
+
+    append_suffixes(prompt, dictionary, language)
+}
+
+/// Build the system prompt for chat mode (agent directly addressed).
+pub fn chat_system_prompt(agent_name: &str, dictionary: &[String], language: Option<&str>) -> String {
+    let prompt = PROMPTS
+        .CHAT_SYSTEM_PROMPT
+        .replace("{{agentName}}", display_name(agent_name));
+    append_suffixes(prompt, dictionary, language)
+}
+
+pub fn user_prompt(text: &str) -> String {
+    format!("[TRANSCRIBED_SPEECH]: {}", text)
+}
+
+/// Language instruction for the preferred output language.
+pub fn language_instruction(language: Option<&str>) -> String {
+    match language {
+        None | Some("auto") => LANGUAGES.auto_detect_instruction.clone(),
+        Some(code) => LANGUAGE_INSTRUCTIONS
+            .get(code)
+            .map(|i| i.to_string())
+            .unwrap_or_else(|| LANGUAGES.generic_template.replacen("{{code}}", code, 1)),
+    }
+}
+
+fn display_name(agent_name: &str) -> &str {
+    match agent_name.trim() {
+        "" => "Assistant",
+        name => name,
+    }
+}
+
+fn append_suffixes(mut prompt: String, dictionary: &[String], language: Option<&str>) -> String {
+    prompt.push_str("\n\n");
+    prompt.push_str(&language_instruction(language));
+
+    if !dictionary.is_empty() {
+        prompt.push_str(&PROMPTS.DICTIONARY_SUFFIX);
+        prompt.push_str(&dictionary.join(", "));
+    }
+
+    prompt
+}
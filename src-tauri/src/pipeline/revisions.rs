@@ -0,0 +1,186 @@
+//! Revisions of a history row: its kept recording transcribed again with
+//! another engine, or its text enhanced again with another prompt or agent.
+//! Each revision is saved as a row of its own whose `parent_id` points at the
+//! original dictation, so the versions can be compared side by side.
+
+use anyhow::{Context, Result, anyhow, bail};
+use std::time::Instant;
+use tauri::{AppHandle, Manager};
+use tokio::sync::mpsc;
+
+use super::settings::LOCAL_TRANSCRIPTION;
+use super::{
+    AppBackend, Backend, DictationSettings, elapsed_ms, enhancement_requests, strip_think_tags,
+    transcribe_with_fallbacks,
+};
+use crate::audio::wav_duration_ms;
+use crate::database::{Database, DictationMetadata, NewTranscription, Transcription};
+use crate::recordings::Recordings;
+
+/// Engine to transcribe with again. An empty or missing model means the
+/// provider's default; a missing language keeps the preferred language.
+#[derive(Debug, Clone, Default)]
+pub struct Engine {
+    /// Cloud provider id, `"local"` for the configured local engine, or
+    /// `"whisper"` / `"parakeet"`.
+    pub provider: String,
+    pub model: Option<String>,
+    pub language: Option<String>,
+}
+
+/// Enhancement overrides. Unset fields keep the current settings.
+#[derive(Debug, Clone, Default)]
+pub struct Enhancement {
+    /// System prompt to use instead of the configured one.
+    pub prompt: Option<String>,
+    pub agent_name: Option<String>,
+}
+
+/// Transcribe the recording of row `id` again and save the result as a revision.
+pub async fn retranscribe(app: &AppHandle, id: i64, engine: &Engine) -> Result<Transcription> {
+    let row = find(app, id)?;
+    let root = row.parent_id.unwrap_or(row.id);
+    let audio = app.state::<Recordings>().load(root)?;
+    let settings = DictationSettings::load(app)?;
+    let revision = transcribe_again(&AppBackend::new(app.clone()), &settings, &row, &audio, engine).await?;
+    find(app, revision)
+}
+
+/// Enhance the text of row `id` again and save the result as a revision.
+pub async fn reenhance(app: &AppHandle, id: i64, enhancement: &Enhancement) -> Result<Transcription> {
+    let row = find(app, id)?;
+    let settings = DictationSettings::load(app)?;
+    let revision = enhance_again(&AppBackend::new(app.clone()), &settings, &row, enhancement).await?;
+    find(app, revision)
+}
+
+fn find(app: &AppHandle, id: i64) -> Result<Transcription> {
+    app.state::<Database>()
+        .get_transcription(id)?
+        .with_context(|| format!("No dictation {} in the history", id))
+}
+
+pub(super) async fn transcribe_again<B: Backend>(
+    backend: &B,
+    settings: &DictationSettings,
+    row: &Transcription,
+    audio: &[u8],
+    engine: &Engine,
+) -> Result<i64> {
+    let settings = with_engine(settings, engine);
+    let dictionary = settings.transcription_dictionary();
+    let started = Instant::now();
+    let (transcript, choice) = transcribe_with_fallbacks(backend, audio, &settings, &dictionary).await?;
+    if transcript.text.trim().is_empty() {
+        bail!("{} heard no speech in the recording", choice.provider);
+    }
+    backend.save(&NewTranscription {
+        original_text: transcript.text,
+        processing_method: "none".to_string(),
+        agent_name: row.agent_name.clone(),
+        transcription_provider: Some(choice.provider),
+        language: transcript.language,
+        segments: transcript.segments,
+        parent_id: Some(row.parent_id.unwrap_or(row.id)),
+        metadata: DictationMetadata {
+            audio_duration_ms: wav_duration_ms(audio),
+            input_device: row.metadata.input_device.clone(),
+            transcription_model: Some(choice.model).filter(|m| !m.is_empty()),
+            requested_language: Some(settings.preferred_language.clone()).filter(|l| !l.is_empty()),
+            transcription_ms: elapsed_ms(started),
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+}
+
+pub(super) async fn enhance_again<B: Backend>(
+    backend: &B,
+    settings: &DictationSettings,
+    row: &Transcription,
+    enhancement: &Enhancement,
+) -> Result<i64> {
+    let mut settings = settings.clone();
+    settings.use_reasoning_model = true;
+    if let Some(prompt) = enhancement.prompt.as_ref().filter(|p| !p.trim().is_empty()) {
+        settings.use_custom_prompt = true;
+        settings.custom_system_prompt = prompt.clone();
+    }
+    if let Some(agent_name) = &enhancement.agent_name {
+        settings.agent_name = agent_name.clone();
+    }
+
+    let mut last_error = None;
+    let started = Instant::now();
+    for request in enhancement_requests(&settings, &row.original_text) {
+        let provider = request.provider.clone();
+        let model = request.model.clone();
+        // Nobody watches a revision stream in; the receiver only keeps the sender open
+        let (deltas, _received) = mpsc::unbounded_channel();
+        match backend.enhance(request, deltas).await {
+            Ok(completion) => {
+                // The text it enhanced came from the row's recording and transcription
+                let metadata = DictationMetadata {
+                    audio_duration_ms: row.metadata.audio_duration_ms,
+                    input_device: row.metadata.input_device.clone(),
+                    transcription_model: row.metadata.transcription_model.clone(),
+                    requested_language: row.metadata.requested_language.clone(),
+                    reasoning_model: Some(model),
+                    enhancement_ms: elapsed_ms(started),
+                    prompt_tokens: completion.usage.map(|u| u.prompt_tokens as i64),
+                    completion_tokens: completion.usage.map(|u| u.completion_tokens as i64),
+                    ..Default::default()
+                };
+                return backend.save(&NewTranscription {
+                    original_text: row.original_text.clone(),
+                    processed_text: Some(strip_think_tags(&completion.text)),
+                    processing_method: "ai".to_string(),
+                    agent_name: Some(settings.agent_name().to_string()),
+                    transcription_provider: row.transcription_provider.clone(),
+                    reasoning_provider: Some(provider),
+                    language: row.language.clone(),
+                    segments: row.segments.clone(),
+                    parent_id: Some(row.parent_id.unwrap_or(row.id)),
+                    metadata,
+                    ..Default::default()
+                });
+            }
+            Err(e) => {
+                log::error!("[Whisperi] Re-enhancement with {} failed: {}", provider, e);
+                last_error = Some(e);
+            }
+        }
+    }
+    Err(last_error.unwrap_or_else(|| anyhow!("No enhancement provider configured")))
+}
+
+/// Settings that transcribe with `engine` only, without fallbacks.
+fn with_engine(settings: &DictationSettings, engine: &Engine) -> DictationSettings {
+    let mut settings = settings.clone();
+    let model = engine.model.clone().unwrap_or_default();
+    settings.transcription_fallbacks.clear();
+    match engine.provider.as_str() {
+        LOCAL_TRANSCRIPTION => settings.use_local_whisper = true,
+        engine_id @ ("whisper" | "parakeet") => {
+            settings.use_local_whisper = true;
+            settings.local_engine = engine_id.to_string();
+            let local_model = if engine_id == "whisper" {
+                &mut settings.whisper_model
+            } else {
+                &mut settings.parakeet_model
+            };
+            if !model.is_empty() {
+                *local_model = model;
+            }
+        }
+        provider => {
+            settings.use_local_whisper = false;
+            settings.cloud_transcription_provider = provider.to_string();
+            settings.cloud_transcription_model = model;
+        }
+    }
+    if let Some(language) = engine.language.as_ref().filter(|l| !l.is_empty()) {
+        settings.preferred_language = language.clone();
+    }
+    settings
+}
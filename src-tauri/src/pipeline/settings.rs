@@ -0,0 +1,347 @@
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+
+use crate::audio::resampler::ResampleQuality;
+use crate::commands::settings::STORE_FILE;
+use crate::endpoint::{CUSTOM_PROVIDER, CustomEndpoint};
+use crate::models::registry::registry;
+use crate::provider::ProviderConfig;
+use crate::reasoning::local::{LOCAL_PROVIDER, LocalServer};
+use crate::recordings::{KeepAudio, Retention};
+use crate::secrets::Secrets;
+
+/// Id that stands for the configured local engine in `transcriptionFallbacks`.
+pub const LOCAL_TRANSCRIPTION: &str = "local";
+
+/// One step of a fallback chain. An empty model means the provider's default:
+/// the configured local or custom-endpoint model, else the first registry model.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProviderChoice {
+    pub provider: String,
+    pub model: String,
+}
+
+/// Snapshot of the settings the dictation pipeline needs, read from the
+/// plugin-store file. Defaults mirror `DEFAULTS` in `useSettings.ts`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DictationSettings {
+    pub use_local_whisper: bool,
+    /// Local engine when `use_local_whisper` is set: `"whisper"` or `"parakeet"`.
+    pub local_engine: String,
+    pub whisper_model: String,
+    pub parakeet_model: String,
+    /// Keep a `whisper-server` sidecar running for local transcription.
+    pub whisper_server: bool,
+    pub preferred_language: String,
+    pub cloud_transcription_provider: String,
+    pub cloud_transcription_model: String,
+    /// Endpoint used when `cloud_transcription_provider` is `custom`.
+    pub custom_transcription: CustomEndpoint,
+    /// Tried in order when the primary transcription provider fails.
+    pub transcription_fallbacks: Vec<ProviderChoice>,
+    pub custom_dictionary: Vec<String>,
+    pub use_reasoning_model: bool,
+    pub reasoning_model: String,
+    pub reasoning_provider: String,
+    /// Endpoint used when `reasoning_provider` is `custom`.
+    pub custom_reasoning: CustomEndpoint,
+    /// Ollama / llama.cpp server used when `reasoning_provider` is `local`.
+    pub local_reasoning: LocalServer,
+    /// Tried in order when the primary enhancement provider fails; once the
+    /// list is exhausted the raw transcription is kept.
+    pub reasoning_fallbacks: Vec<ProviderChoice>,
+    pub use_custom_prompt: bool,
+    pub custom_system_prompt: String,
+    pub auto_paste: bool,
+    /// With `auto_paste`, type the enhanced text as it streams in instead of
+    /// pasting it once finished.
+    pub incremental_paste: bool,
+    pub agent_name: String,
+    pub agent_aliases: Vec<String>,
+    pub debug_mode: bool,
+    /// Transcribe while recording and emit `transcript-partial` events.
+    pub streaming_transcription: bool,
+    /// Ask engines that support it for timed segments with confidence, and
+    /// store them with the history row.
+    pub transcript_timestamps: bool,
+    /// Stop recording after this many seconds of silence following speech (0 = off).
+    pub silence_timeout: u32,
+    /// Filter used to resample the microphone to 16 kHz.
+    pub resample_quality: ResampleQuality,
+    /// Which dictations keep their compressed recording.
+    pub keep_audio: KeepAudio,
+    /// Delete kept recordings older than this many days (0 = never).
+    pub audio_retention_days: u32,
+    /// Delete the oldest kept recordings beyond this many megabytes (0 = no limit).
+    pub audio_retention_mb: u32,
+    /// API keys by provider id, from secure storage (and any `{provider}ApiKey`
+    /// entries not yet migrated out of the store).
+    #[serde(skip)]
+    pub api_keys: HashMap<String, String>,
+}
+
+impl Default for DictationSettings {
+    fn default() -> Self {
+        Self {
+            use_local_whisper: false,
+            local_engine: "whisper".to_string(),
+            whisper_model: "base".to_string(),
+            parakeet_model: "parakeet-tdt-0.6b-v3".to_string(),
+            whisper_server: false,
+            preferred_language: "auto".to_string(),
+            cloud_transcription_provider: "openai".to_string(),
+            cloud_transcription_model: "gpt-4o-mini-transcribe".to_string(),
+            custom_transcription: CustomEndpoint::default(),
+            transcription_fallbacks: Vec::new(),
+            custom_dictionary: Vec::new(),
+            use_reasoning_model: true,
+            reasoning_model: "gpt-5-mini".to_string(),
+            reasoning_provider: "openai".to_string(),
+            custom_reasoning: CustomEndpoint::default(),
+            local_reasoning: LocalServer::default(),
+            reasoning_fallbacks: Vec::new(),
+            use_custom_prompt: false,
+            custom_system_prompt: String::new(),
+            auto_paste: true,
+            incremental_paste: false,
+            agent_name: "Whisperi".to_string(),
+            agent_aliases: Vec::new(),
+            debug_mode: false,
+            streaming_transcription: false,
+            transcript_timestamps: false,
+            silence_timeout: 0,
+            resample_quality: ResampleQuality::Balanced,
+            keep_audio: KeepAudio::Failures,
+            audio_retention_days: 30,
+            audio_retention_mb: 500,
+            api_keys: HashMap::new(),
+        }
+    }
+}
+
+impl DictationSettings {
+    /// File name of the selected local whisper model, from the model registry.
+    pub fn whisper_model_file(&self) -> Result<String> {
+        Ok(registry()
+            .whisper_model(&self.whisper_model)?
+            .file_name
+            .clone())
+    }
+
+    /// Load the current settings from the plugin store, with API keys from
+    /// secure storage.
+    pub fn load(app: &AppHandle) -> Result<Self> {
+        let store = app.store(STORE_FILE)?;
+        let map: Map<String, Value> = store
+            .keys()
+            .into_iter()
+            .filter_map(|key| store.get(&key).map(|val| (key, val)))
+            .collect();
+        let (mut settings, invalid) = Self::from_map(map);
+        if !invalid.is_empty() {
+            log::warn!(
+                "[Whisperi] Using defaults for invalid settings: {}. Set them again in Settings.",
+                invalid.join(", ")
+            );
+        }
+        if let Some(secrets) = app.try_state::<Secrets>() {
+            settings.api_keys.extend(secrets.api_keys());
+        }
+        Ok(settings)
+    }
+
+    /// Build settings from raw store entries, falling back to defaults for
+    /// anything missing. An entry of the wrong type falls back on its own and
+    /// its key is returned, so the caller can report it.
+    pub fn from_map(map: Map<String, Value>) -> (Self, Vec<String>) {
+        let mut api_keys = HashMap::new();
+        let mut entries = Map::new();
+        let mut invalid = Vec::new();
+        for (key, value) in map {
+            if let Some(provider) = key.strip_suffix("ApiKey") {
+                if let Some(api_key) = value.as_str().filter(|k| !k.is_empty()) {
+                    api_keys.insert(provider.to_string(), api_key.to_string());
+                }
+                continue;
+            }
+            // Null means "unset" to the frontend; keep the default
+            if value.is_null() {
+                continue;
+            }
+            let entry = Map::from_iter([(key.clone(), value)]);
+            if let Err(e) = serde_json::from_value::<Self>(Value::Object(entry.clone())) {
+                log::warn!("[Whisperi] Invalid setting {}, using its default: {}", key, e);
+                invalid.push(key);
+                continue;
+            }
+            entries.extend(entry);
+        }
+
+        // Every entry deserializes on its own, so together they do too
+        let mut settings: Self = serde_json::from_value(Value::Object(entries)).unwrap_or_default();
+        settings.api_keys = api_keys;
+        (settings, invalid)
+    }
+
+    pub fn audio_retention(&self) -> Retention {
+        Retention {
+            keep: self.keep_audio,
+            max_age_days: self.audio_retention_days,
+            max_megabytes: self.audio_retention_mb,
+        }
+    }
+
+    /// API key for a provider, if one is configured.
+    pub fn api_key(&self, provider: &str) -> Option<&str> {
+        self.api_keys.get(provider).map(String::as_str)
+    }
+
+    /// Transcription providers to try, primary first. Local engines appear as
+    /// `"whisper"` / `"parakeet"` with their model id.
+    pub fn transcription_chain(&self) -> Vec<ProviderChoice> {
+        let primary = if self.use_local_whisper {
+            ProviderChoice {
+                provider: LOCAL_TRANSCRIPTION.to_string(),
+                model: String::new(),
+            }
+        } else {
+            ProviderChoice {
+                provider: self.cloud_transcription_provider.clone(),
+                model: self.cloud_transcription_model.clone(),
+            }
+        };
+        let resolve = |choice: &ProviderChoice| {
+            let (provider, model) = match choice.provider.as_str() {
+                LOCAL_TRANSCRIPTION if self.local_engine == "parakeet" => ("parakeet", &self.parakeet_model),
+                LOCAL_TRANSCRIPTION => ("whisper", &self.whisper_model),
+                CUSTOM_PROVIDER => (CUSTOM_PROVIDER, &self.custom_transcription.model),
+                provider => (provider, &choice.model),
+            };
+            let model = match model.as_str() {
+                "" => registry()
+                    .transcription_provider(provider)
+                    .ok()
+                    .and_then(|p| p.default_model())
+                    .unwrap_or_default(),
+                model => model,
+            };
+            ProviderChoice {
+                provider: provider.to_string(),
+                model: model.to_string(),
+            }
+        };
+        chain(std::iter::once(&primary).chain(&self.transcription_fallbacks).map(resolve))
+    }
+
+    /// Enhancement providers to try, primary first. Entries without a usable
+    /// model are dropped.
+    pub fn reasoning_chain(&self) -> Vec<ProviderChoice> {
+        let primary = ProviderChoice {
+            provider: self.reasoning_provider.clone(),
+            model: self.reasoning_model.clone(),
+        };
+        let resolve = |choice: &ProviderChoice| {
+            let model = match choice.provider.as_str() {
+                CUSTOM_PROVIDER => self.custom_reasoning.model.as_str(),
+                _ if !choice.model.is_empty() => choice.model.as_str(),
+                // A local server has no default; reuse the primary's model if it is local too
+                LOCAL_PROVIDER if self.reasoning_provider == LOCAL_PROVIDER => self.reasoning_model.as_str(),
+                provider => registry()
+                    .reasoning_provider(provider)
+                    .ok()
+                    .and_then(|p| p.default_model())
+                    .unwrap_or_default(),
+            };
+            ProviderChoice {
+                provider: choice.provider.clone(),
+                model: model.to_string(),
+            }
+        };
+        chain(std::iter::once(&primary).chain(&self.reasoning_fallbacks).map(resolve))
+    }
+
+    /// Provider configuration for cloud transcription.
+    pub fn transcription_config(&self) -> ProviderConfig<'_> {
+        ProviderConfig {
+            endpoint: Some(&self.custom_transcription),
+            local: None,
+        }
+    }
+
+    /// Provider configuration for enhancement.
+    pub fn reasoning_config(&self) -> ProviderConfig<'_> {
+        ProviderConfig {
+            endpoint: Some(&self.custom_reasoning),
+            local: Some(&self.local_reasoning),
+        }
+    }
+
+    /// Key to send to a transcription provider.
+    pub fn transcription_key(&self, provider: &str) -> Option<&str> {
+        if provider == CUSTOM_PROVIDER {
+            self.custom_transcription_key()
+        } else {
+            self.api_key(provider)
+        }
+    }
+
+    /// Key to send to an enhancement provider.
+    pub fn reasoning_key(&self, provider: &str) -> Option<&str> {
+        if provider == CUSTOM_PROVIDER {
+            self.custom_reasoning_key()
+        } else {
+            self.api_key(provider)
+        }
+    }
+
+    /// Optional key for the custom transcription endpoint (`customTranscriptionApiKey`).
+    pub fn custom_transcription_key(&self) -> Option<&str> {
+        self.api_key("customTranscription")
+    }
+
+    /// Optional key for the custom reasoning endpoint (`customReasoningApiKey`).
+    pub fn custom_reasoning_key(&self) -> Option<&str> {
+        self.api_key("customReasoning")
+    }
+
+    /// Trimmed agent name, falling back to the default.
+    pub fn agent_name(&self) -> &str {
+        match self.agent_name.trim() {
+            "" => "Whisperi",
+            name => name,
+        }
+    }
+
+    /// Dictionary used for transcription: the custom dictionary plus the agent
+    /// name and aliases, so speech-to-text recognizes them.
+    pub fn transcription_dictionary(&self) -> Vec<String> {
+        let mut words = self.custom_dictionary.clone();
+        let extra = std::iter::once(self.agent_name.as_str())
+            .chain(self.agent_aliases.iter().map(String::as_str))
+            .map(str::trim)
+            .filter(|w| !w.is_empty());
+        for word in extra {
+            if !words.iter().any(|w| w == word) {
+                words.push(word.to_string());
+            }
+        }
+        words
+    }
+}
+
+/// Drop unnamed providers and repeats, keeping the first occurrence.
+fn chain(choices: impl Iterator<Item = ProviderChoice>) -> Vec<ProviderChoice> {
+    let mut out: Vec<ProviderChoice> = Vec::new();
+    for choice in choices {
+        if !choice.provider.is_empty() && !out.contains(&choice) {
+            out.push(choice);
+        }
+    }
+    out
+}
@@ -0,0 +1,215 @@
+//! Live transcription while recording. A background task re-transcribes the
+//! current segment of the 16 kHz buffer every few seconds and emits partial
+//! hypotheses. Once a segment reaches [`SEGMENT_SECS`] its text is committed and
+//! the next segment starts [`OVERLAP_SECS`] before the cut, so words split at the
+//! boundary are recovered and de-duplicated when the two texts are merged.
+//!
+//! Every engine is driven through the same windowed requests, so this works
+//! with the whisper.cpp sidecar and all cloud providers alike.
+
+use super::{AppBackend, Backend, DictationSettings};
+use crate::audio::{RecordingState, TARGET_SAMPLE_RATE, encode_wav, speech_bounds};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Event carrying the current partial (or final) live transcript.
+pub const PARTIAL_EVENT: &str = "transcript-partial";
+
+/// How often the current segment is re-transcribed.
+const STEP: Duration = Duration::from_millis(2000);
+/// Segment length at which the hypothesis is committed.
+const SEGMENT_SECS: usize = 10;
+/// Audio shared between consecutive segments.
+const OVERLAP_SECS: usize = 1;
+/// Don't bother transcribing less than this much new audio.
+const MIN_CHUNK_MS: usize = 500;
+
+/// Longest run of words checked when stitching overlapping segments.
+const MAX_OVERLAP_WORDS: usize = 8;
+
+#[derive(Clone, Serialize)]
+struct PartialPayload {
+    text: String,
+    is_final: bool,
+}
+
+/// Managed handle to the live transcription task of the current recording.
+#[derive(Default)]
+pub struct LiveTranscription {
+    task: Mutex<Option<JoinHandle<Result<String>>>>,
+}
+
+/// Start live transcription for the recording that was just started.
+pub fn start(app: &AppHandle, mut settings: DictationSettings) {
+    // Windows overlap and are re-transcribed, so their segment times mean nothing
+    settings.transcript_timestamps = false;
+    let live = app.state::<LiveTranscription>();
+    let mut task = live.task.lock().unwrap();
+    if let Some(previous) = task.take() {
+        previous.abort();
+    }
+
+    let app = app.clone();
+    *task = Some(tauri::async_runtime::spawn(async move {
+        let result = run(&app, &settings).await;
+        if let Err(e) = &result {
+            log::warn!("[Whisperi] Live transcription stopped: {}", e);
+        }
+        result
+    }));
+}
+
+/// Abort live transcription without producing a final transcript (recording cancelled).
+pub fn cancel(app: &AppHandle) {
+    if let Some(task) = app.state::<LiveTranscription>().task.lock().unwrap().take() {
+        task.abort();
+    }
+}
+
+/// Wait for the live task to transcribe the remaining audio after the recorder
+/// has stopped. Returns `None` if live transcription wasn't running or failed,
+/// in which case the caller should transcribe the full recording instead.
+pub async fn finish(app: &AppHandle) -> Option<String> {
+    let task = app.state::<LiveTranscription>().task.lock().unwrap().take()?;
+    match task.await {
+        Ok(Ok(text)) => Some(text),
+        Ok(Err(_)) => None,
+        Err(e) => {
+            log::warn!("[Whisperi] Live transcription task failed: {}", e);
+            None
+        }
+    }
+}
+
+async fn run(app: &AppHandle, settings: &DictationSettings) -> Result<String> {
+    let backend = AppBackend::new(app.clone());
+    let dictionary = settings.transcription_dictionary();
+    let primary = settings
+        .transcription_chain()
+        .into_iter()
+        .next()
+        .context("No transcription provider configured")?;
+    let segment_len = SEGMENT_SECS * TARGET_SAMPLE_RATE as usize;
+    let overlap_len = OVERLAP_SECS * TARGET_SAMPLE_RATE as usize;
+    let min_chunk_len = MIN_CHUNK_MS * TARGET_SAMPLE_RATE as usize / 1000;
+
+    let mut committed = String::new();
+    let mut segment_start = 0;
+
+    loop {
+        let recording = wait_step(app).await;
+        let chunk = app.state::<RecordingState>().chunk_16k(segment_start);
+
+        if recording && chunk.len() < min_chunk_len {
+            continue;
+        }
+        if chunk.is_empty() {
+            break;
+        }
+        // Silent segments only produce hallucinations ("Thank you.")
+        if speech_bounds(&chunk, TARGET_SAMPLE_RATE).is_none() {
+            if !recording {
+                break;
+            }
+            if chunk.len() >= segment_len {
+                segment_start += chunk.len() - overlap_len;
+            }
+            continue;
+        }
+
+        let wav = encode_wav(&chunk, TARGET_SAMPLE_RATE)?;
+        let text = backend.transcribe(wav, &primary, settings, &dictionary).await?.text;
+        let merged = merge_overlap(&committed, text.trim());
+
+        if !recording {
+            committed = merged;
+            break;
+        }
+
+        if chunk.len() >= segment_len {
+            committed = merged.clone();
+            segment_start += chunk.len() - overlap_len;
+        }
+        emit_partial(app, merged, false);
+    }
+
+    emit_partial(app, committed.clone(), true);
+    Ok(committed)
+}
+
+/// Sleep for one step, returning early once recording stops.
+/// Returns whether recording is still active.
+async fn wait_step(app: &AppHandle) -> bool {
+    let poll = Duration::from_millis(100);
+    let mut waited = Duration::ZERO;
+    while waited < STEP {
+        if !app.state::<RecordingState>().is_recording() {
+            return false;
+        }
+        tokio::time::sleep(poll).await;
+        waited += poll;
+    }
+    app.state::<RecordingState>().is_recording()
+}
+
+fn emit_partial(app: &AppHandle, text: String, is_final: bool) {
+    let _ = app.emit(PARTIAL_EVENT, PartialPayload { text, is_final });
+}
+
+/// Append `next` to `committed`, dropping the words at the start of `next` that
+/// repeat the end of `committed` (the audio both segments share).
+fn merge_overlap(committed: &str, next: &str) -> String {
+    let normalize = |w: &str| -> String {
+        w.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+
+    let old: Vec<&str> = committed.split_whitespace().collect();
+    let new: Vec<&str> = next.split_whitespace().collect();
+    if old.is_empty() {
+        return new.join(" ");
+    }
+
+    let max = MAX_OVERLAP_WORDS.min(old.len()).min(new.len());
+    let overlap = (1..=max)
+        .rev()
+        .find(|&k| {
+            old[old.len() - k..]
+                .iter()
+                .zip(&new[..k])
+                .all(|(a, b)| normalize(a) == normalize(b))
+        })
+        .unwrap_or(0);
+
+    old.iter()
+        .chain(&new[overlap..])
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_overlap_drops_repeated_words() {
+        assert_eq!(
+            merge_overlap("the quick brown fox", "Brown fox, jumps over"),
+            "the quick brown fox jumps over"
+        );
+    }
+
+    #[test]
+    fn test_merge_overlap_without_shared_words() {
+        assert_eq!(merge_overlap("hello there", "general kenobi"), "hello there general kenobi");
+        assert_eq!(merge_overlap("", "first words"), "first words");
+        assert_eq!(merge_overlap("kept", ""), "kept");
+    }
+}
@@ -0,0 +1,346 @@
+//! Provider abstraction for cloud transcription and text enhancement.
+//!
+//! Each provider module implements [`TranscriptionProvider`] or
+//! [`ReasoningProvider`] and registers a factory under its id in
+//! `transcription::providers()` / `reasoning::providers()`. Callers look
+//! providers up by id and consult [`Capabilities`] instead of matching on
+//! provider names. HTTP failures are classified into [`ProviderError`] by
+//! `crate::http`.
+
+use async_trait::async_trait;
+use indexmap::IndexMap;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::endpoint::CustomEndpoint;
+pub use crate::models::registry::Capabilities;
+use crate::reasoning::local::LocalServer;
+use crate::transcription::transcript::Transcript;
+
+/// Why a provider call failed. The messages are meant for the user; response
+/// bodies are logged by the HTTP layer instead.
+#[derive(Debug, thiserror::Error)]
+pub enum ProviderError {
+    #[error("Unknown provider: {0}")]
+    UnknownProvider(String),
+    #[error("No API key configured for {0}. Set it in Settings.")]
+    MissingApiKey(String),
+    #[error("{provider} is misconfigured: {message}")]
+    Config { provider: String, message: String },
+    #[error("Your {provider} API key is invalid or not authorized ({status})")]
+    Unauthorized { provider: String, status: StatusCode },
+    #[error("{provider} is rate limiting requests{}", retry_hint(*.retry_after))]
+    RateLimited {
+        provider: String,
+        retry_after: Option<Duration>,
+    },
+    #[error("{provider} quota or credits are exhausted. Check your plan or billing.")]
+    QuotaExceeded { provider: String },
+    #[error("{provider} is unavailable ({status})")]
+    Server {
+        provider: String,
+        status: StatusCode,
+        retry_after: Option<Duration>,
+    },
+    #[error("{provider} did not respond in time")]
+    Timeout { provider: String },
+    #[error("{provider} API error ({status}): {body}")]
+    Api {
+        provider: String,
+        status: StatusCode,
+        body: String,
+    },
+    #[error("Could not reach {provider}: {source}")]
+    Network {
+        provider: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("{provider} returned an unexpected response: {message}")]
+    InvalidResponse { provider: String, message: String },
+}
+
+fn retry_hint(retry_after: Option<Duration>) -> String {
+    retry_after
+        .map(|d| format!(" (retry after {}s)", d.as_secs_f32().ceil()))
+        .unwrap_or_default()
+}
+
+/// [`ProviderError`] without its payload, for the frontend to branch on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ErrorKind {
+    UnknownProvider,
+    MissingApiKey,
+    Config,
+    Unauthorized,
+    RateLimited,
+    QuotaExceeded,
+    Server,
+    Timeout,
+    Api,
+    Network,
+    InvalidResponse,
+}
+
+/// Serializable summary of a [`ProviderError`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorInfo {
+    pub kind: ErrorKind,
+    pub message: String,
+    pub retryable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after_secs: Option<u64>,
+}
+
+impl ProviderError {
+    pub fn config(provider: &str, error: impl std::fmt::Display) -> Self {
+        Self::Config {
+            provider: provider.to_string(),
+            message: error.to_string(),
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::UnknownProvider(_) => ErrorKind::UnknownProvider,
+            Self::MissingApiKey(_) => ErrorKind::MissingApiKey,
+            Self::Config { .. } => ErrorKind::Config,
+            Self::Unauthorized { .. } => ErrorKind::Unauthorized,
+            Self::RateLimited { .. } => ErrorKind::RateLimited,
+            Self::QuotaExceeded { .. } => ErrorKind::QuotaExceeded,
+            Self::Server { .. } => ErrorKind::Server,
+            Self::Timeout { .. } => ErrorKind::Timeout,
+            Self::Api { .. } => ErrorKind::Api,
+            Self::Network { .. } => ErrorKind::Network,
+            Self::InvalidResponse { .. } => ErrorKind::InvalidResponse,
+        }
+    }
+
+    /// Whether the same request may succeed if sent again.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::RateLimited { .. } | Self::Server { .. } | Self::Timeout { .. } | Self::Network { .. }
+        )
+    }
+
+    /// Wait the provider asked for before the next request.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimited { retry_after, .. } | Self::Server { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    pub fn info(&self) -> ErrorInfo {
+        ErrorInfo {
+            kind: self.kind(),
+            message: self.to_string(),
+            retryable: self.is_retryable(),
+            retry_after_secs: self.retry_after().map(|d| d.as_secs_f32().ceil() as u64),
+        }
+    }
+}
+
+/// What the frontend needs to know about a registered provider.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderSummary {
+    pub id: String,
+    pub capabilities: Capabilities,
+    pub requires_api_key: bool,
+}
+
+/// Which registry a provider id belongs to; `openai` and `custom` exist in both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Purpose {
+    Transcription,
+    Reasoning,
+}
+
+impl Purpose {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Transcription => "transcription",
+            Self::Reasoning => "reasoning",
+        }
+    }
+}
+
+/// A model from a provider's own list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListedModel {
+    pub id: String,
+    /// Input kinds the provider reports (`text`, `audio`, ...). Empty when
+    /// it doesn't say, which is most providers.
+    pub inputs: Vec<String>,
+}
+
+impl ListedModel {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            inputs: Vec::new(),
+        }
+    }
+}
+
+/// User configuration a provider may need beyond its registry entry.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProviderConfig<'a> {
+    /// Endpoint for the `custom` provider.
+    pub endpoint: Option<&'a CustomEndpoint>,
+    /// Server for the `local` reasoning provider.
+    pub local: Option<&'a LocalServer>,
+}
+
+pub struct TranscriptionRequest<'a> {
+    /// WAV bytes.
+    pub audio: Vec<u8>,
+    pub api_key: Option<&'a str>,
+    pub model: &'a str,
+    pub language: Option<&'a str>,
+    pub prompt: Option<&'a str>,
+    /// Ask for timed segments; dropped for providers without the `timestamps` capability.
+    pub timestamps: bool,
+}
+
+pub struct CompletionRequest<'a> {
+    pub api_key: Option<&'a str>,
+    pub model: &'a str,
+    pub system_prompt: &'a str,
+    pub user_text: &'a str,
+    pub max_tokens: Option<u32>,
+}
+
+/// A completion's text and the tokens it cost, when the provider reports them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Completion {
+    pub text: String,
+    pub usage: Option<TokenUsage>,
+}
+
+impl Completion {
+    pub fn new(text: String, usage: Option<TokenUsage>) -> Self {
+        Self { text, usage }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    /// Tokens of the system prompt and user text.
+    pub prompt_tokens: u32,
+    /// Tokens generated, including any reasoning.
+    pub completion_tokens: u32,
+}
+
+#[async_trait]
+pub trait TranscriptionProvider: Send + Sync {
+    fn id(&self) -> &str;
+    fn capabilities(&self) -> Capabilities;
+    /// Self-hosted providers may run without a key.
+    fn requires_api_key(&self) -> bool {
+        true
+    }
+    async fn transcribe(&self, request: TranscriptionRequest<'_>) -> Result<Transcript, ProviderError>;
+    /// Models the key can use, from the provider's cheapest authenticated
+    /// endpoint. Doubles as a credential check.
+    async fn list_models(&self, api_key: Option<&str>) -> Result<Vec<ListedModel>, ProviderError>;
+}
+
+/// Receives streamed text as it arrives.
+pub type OnDelta<'a> = &'a (dyn Fn(&str) + Send + Sync);
+
+#[async_trait]
+pub trait ReasoningProvider: Send + Sync {
+    fn id(&self) -> &str;
+    fn capabilities(&self) -> Capabilities;
+    fn requires_api_key(&self) -> bool {
+        true
+    }
+    /// Model fixed by the provider's own configuration, overriding the requested one.
+    fn configured_model(&self) -> Option<&str> {
+        None
+    }
+    async fn complete(&self, request: CompletionRequest<'_>) -> Result<Completion, ProviderError>;
+    /// [`complete`](Self::complete), passing text to `on_delta` as it arrives
+    /// and returning all of it. Providers that can't stream deliver the whole
+    /// response as one delta.
+    async fn complete_stream(
+        &self,
+        request: CompletionRequest<'_>,
+        on_delta: OnDelta<'_>,
+    ) -> Result<Completion, ProviderError> {
+        let completion = self.complete(request).await?;
+        on_delta(&completion.text);
+        Ok(completion)
+    }
+    /// Models the key can use; see [`TranscriptionProvider::list_models`].
+    async fn list_models(&self, api_key: Option<&str>) -> Result<Vec<ListedModel>, ProviderError>;
+}
+
+/// Builds a provider for an id from the model registry and user configuration.
+pub type Factory<P> = fn(&str, ProviderConfig<'_>) -> Result<Box<P>, ProviderError>;
+
+/// Provider factories keyed by id, in registration order.
+pub struct Registry<P: ?Sized> {
+    factories: IndexMap<&'static str, Factory<P>>,
+}
+
+impl<P: ?Sized> Default for Registry<P> {
+    fn default() -> Self {
+        Self {
+            factories: IndexMap::new(),
+        }
+    }
+}
+
+impl<P: ?Sized> Registry<P> {
+    pub fn register(mut self, id: &'static str, factory: Factory<P>) -> Self {
+        self.factories.insert(id, factory);
+        self
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.factories.keys().copied()
+    }
+
+    /// Build `id`, failing with [`ProviderError::UnknownProvider`] when it isn't registered.
+    pub fn get(&self, id: &str, config: ProviderConfig<'_>) -> Result<Box<P>, ProviderError> {
+        let factory = self
+            .factories
+            .get(id)
+            .ok_or_else(|| ProviderError::UnknownProvider(id.to_string()))?;
+        factory(id, config)
+    }
+}
+
+/// The key to send, or [`ProviderError::MissingApiKey`] when one is needed
+/// and none was given.
+pub fn require_key<'a>(
+    provider: &str,
+    required: bool,
+    api_key: Option<&'a str>,
+) -> Result<Option<&'a str>, ProviderError> {
+    let api_key = api_key.filter(|k| !k.is_empty());
+    if required && api_key.is_none() {
+        return Err(ProviderError::MissingApiKey(provider.to_string()));
+    }
+    Ok(api_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_require_key() {
+        assert!(matches!(require_key("groq", true, Some("")), Err(ProviderError::MissingApiKey(_))));
+        assert_eq!(require_key("custom", false, Some("")).unwrap(), None);
+        assert_eq!(require_key("groq", true, Some("k")).unwrap(), Some("k"));
+    }
+}
@@ -1,12 +1,21 @@
-use anyhow::Result;
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
+use crate::models::registry::{ProviderInfo, registry};
+use crate::http;
+use crate::provider::{
+    Capabilities, Completion, CompletionRequest, ListedModel, OnDelta, ProviderConfig, ProviderError,
+    ReasoningProvider, TokenUsage,
+};
+
 #[derive(Serialize)]
 struct MessagesRequest {
     model: String,
     max_tokens: u32,
     system: String,
     messages: Vec<Message>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
 }
 
 #[derive(Serialize)]
@@ -18,6 +27,24 @@ struct Message {
 #[derive(Deserialize)]
 struct MessagesResponse {
     content: Vec<ContentBlock>,
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Usage {
+    input_tokens: u32,
+    output_tokens: u32,
+}
+
+impl From<Usage> for TokenUsage {
+    fn from(usage: Usage) -> Self {
+        Self {
+            prompt_tokens: usage.input_tokens,
+            completion_tokens: usage.output_tokens,
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -27,47 +54,149 @@ struct ContentBlock {
     text: Option<String>,
 }
 
-pub async fn complete(
-    api_key: &str,
-    model: &str,
-    system_prompt: &str,
-    user_text: &str,
-    max_tokens: Option<u32>,
-) -> Result<String> {
-    let request = MessagesRequest {
-        model: model.to_string(),
-        max_tokens: max_tokens.unwrap_or(4096),
-        system: system_prompt.to_string(),
-        messages: vec![Message {
-            role: "user".to_string(),
-            content: user_text.to_string(),
-        }],
-    };
-
-    let response = crate::HTTP_CLIENT
-        .post("https://api.anthropic.com/v1/messages")
-        .header("x-api-key", api_key)
-        .header("anthropic-version", "2023-06-01")
-        .header("content-type", "application/json")
-        .json(&request)
-        .send()
-        .await?;
+/// Server-sent event of a streamed message.
+#[derive(Deserialize)]
+struct StreamEvent {
+    #[serde(rename = "type")]
+    event_type: String,
+    #[serde(default)]
+    delta: Option<StreamDelta>,
+    #[serde(default)]
+    error: Option<StreamError>,
+    /// Set on `message_start`, with the prompt's token count.
+    #[serde(default)]
+    message: Option<StreamMessage>,
+    /// Set on `message_delta`, with the output tokens so far.
+    #[serde(default)]
+    usage: Option<Usage>,
+}
 
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        anyhow::bail!("Anthropic API error ({}): {}", status, body);
+#[derive(Deserialize)]
+struct StreamMessage {
+    #[serde(default)]
+    usage: Usage,
+}
+
+#[derive(Deserialize)]
+struct StreamDelta {
+    text: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct StreamError {
+    message: String,
+}
+
+/// Anthropic Messages API.
+pub struct Anthropic {
+    info: &'static ProviderInfo,
+}
+
+impl Anthropic {
+    pub fn build(id: &str, _: ProviderConfig<'_>) -> Result<Box<dyn ReasoningProvider>, ProviderError> {
+        let info = registry()
+            .reasoning_provider(id)
+            .map_err(|_| ProviderError::UnknownProvider(id.to_string()))?;
+        Ok(Box::new(Self { info }))
+    }
+
+    fn messages_request(request: &CompletionRequest<'_>, stream: bool) -> MessagesRequest {
+        MessagesRequest {
+            model: request.model.to_string(),
+            max_tokens: request.max_tokens.unwrap_or(4096),
+            system: request.system_prompt.to_string(),
+            messages: vec![Message {
+                role: "user".to_string(),
+                content: request.user_text.to_string(),
+            }],
+            stream,
+        }
+    }
+
+    fn post(api_key: &str, body: &MessagesRequest) -> reqwest::RequestBuilder {
+        crate::HTTP_CLIENT
+            .post("https://api.anthropic.com/v1/messages")
+            .header("x-api-key", api_key)
+            .header("anthropic-version", "2023-06-01")
+            .header("content-type", "application/json")
+            .json(body)
+    }
+}
+
+#[async_trait]
+impl ReasoningProvider for Anthropic {
+    fn id(&self) -> &str {
+        &self.info.id
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.info.capabilities
     }
 
-    let result: MessagesResponse = response.json().await?;
+    async fn complete(&self, request: CompletionRequest<'_>) -> Result<Completion, ProviderError> {
+        self.info.check_model(request.model);
+        let body = Self::messages_request(&request, false);
+        let api_key = request.api_key.unwrap_or_default();
+        let result: MessagesResponse = http::send_json(&self.info.name, || Self::post(api_key, &body)).await?;
 
-    let text = result
-        .content
-        .iter()
-        .filter(|block| block.block_type == "text")
-        .filter_map(|block| block.text.as_deref())
-        .collect::<Vec<_>>()
-        .join("");
+        let text = result
+            .content
+            .iter()
+            .filter(|block| block.block_type == "text")
+            .filter_map(|block| block.text.as_deref())
+            .collect::<Vec<_>>()
+            .join("");
+
+        Ok(Completion::new(text, result.usage.map(Into::into)))
+    }
 
-    Ok(text)
+    async fn complete_stream(
+        &self,
+        request: CompletionRequest<'_>,
+        on_delta: OnDelta<'_>,
+    ) -> Result<Completion, ProviderError> {
+        self.info.check_model(request.model);
+        let body = Self::messages_request(&request, true);
+        let api_key = request.api_key.unwrap_or_default();
+        let mut text = String::new();
+        let mut usage: Option<TokenUsage> = None;
+        http::send_sse(&self.info.name, || Self::post(api_key, &body), |event: StreamEvent| {
+            match event.event_type.as_str() {
+                // Thinking and tool-input deltas carry no `text`
+                "content_block_delta" => {
+                    if let Some(delta) = event.delta.and_then(|d| d.text) {
+                        on_delta(&delta);
+                        text.push_str(&delta);
+                    }
+                }
+                "message_start" => usage = event.message.map(|m| m.usage.into()),
+                "message_delta" => {
+                    if let (Some(usage), Some(delta)) = (usage.as_mut(), event.usage) {
+                        usage.completion_tokens = delta.output_tokens;
+                    }
+                }
+                "error" => {
+                    return Err(ProviderError::InvalidResponse {
+                        provider: self.info.name.clone(),
+                        message: event.error.map_or_else(|| "the stream failed".to_string(), |e| e.message),
+                    });
+                }
+                _ => {}
+            }
+            Ok(())
+        })
+        .await?;
+        Ok(Completion::new(text, usage))
+    }
+
+    async fn list_models(&self, api_key: Option<&str>) -> Result<Vec<ListedModel>, ProviderError> {
+        let api_key = api_key.unwrap_or_default();
+        http::list_models(&self.info.name, || {
+            crate::HTTP_CLIENT
+                .get("https://api.anthropic.com/v1/models?limit=1000")
+                .header("x-api-key", api_key)
+                .header("anthropic-version", "2023-06-01")
+        })
+        .await
+    }
 }
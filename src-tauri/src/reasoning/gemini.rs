@@ -1,6 +1,13 @@
-use anyhow::Result;
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
+use crate::models::registry::{ProviderInfo, registry};
+use crate::http;
+use crate::provider::{
+    Capabilities, Completion, CompletionRequest, ListedModel, OnDelta, ProviderConfig, ProviderError,
+    ReasoningProvider, TokenUsage,
+};
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 struct GeminiRequest {
@@ -17,11 +24,13 @@ struct SystemInstruction {
 #[derive(Serialize, Deserialize)]
 struct Content {
     role: Option<String>,
+    #[serde(default)]
     parts: Vec<Part>,
 }
 
 #[derive(Serialize, Deserialize)]
 struct Part {
+    #[serde(default)]
     text: String,
 }
 
@@ -32,8 +41,55 @@ struct GenerationConfig {
 }
 
 #[derive(Deserialize)]
+struct ModelsResponse {
+    #[serde(default)]
+    models: Vec<ModelEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModelEntry {
+    /// `models/<id>`
+    name: String,
+    #[serde(default)]
+    supported_generation_methods: Vec<String>,
+}
+
+/// A whole response, or one server-sent chunk of a streamed one.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct GeminiResponse {
+    #[serde(default)]
     candidates: Vec<Candidate>,
+    /// Running totals; the last streamed chunk has the final counts.
+    #[serde(default)]
+    usage_metadata: Option<UsageMetadata>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct UsageMetadata {
+    prompt_token_count: u32,
+    candidates_token_count: u32,
+    thoughts_token_count: u32,
+}
+
+impl From<UsageMetadata> for TokenUsage {
+    fn from(usage: UsageMetadata) -> Self {
+        Self {
+            prompt_tokens: usage.prompt_token_count,
+            completion_tokens: usage.candidates_token_count + usage.thoughts_token_count,
+        }
+    }
+}
+
+impl GeminiResponse {
+    fn text(&self) -> String {
+        self.candidates
+            .first()
+            .map(|c| c.content.parts.iter().map(|p| p.text.as_str()).collect::<Vec<_>>().join(""))
+            .unwrap_or_default()
+    }
 }
 
 #[derive(Deserialize)]
@@ -41,62 +97,108 @@ struct Candidate {
     content: Content,
 }
 
-pub async fn complete(
-    api_key: &str,
-    model: &str,
-    system_prompt: &str,
-    user_text: &str,
-    max_tokens: Option<u32>,
-) -> Result<String> {
-    let url = format!(
-        "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-        model, api_key
-    );
-
-    let request = GeminiRequest {
-        system_instruction: SystemInstruction {
-            parts: vec![Part {
-                text: system_prompt.to_string(),
-            }],
-        },
-        contents: vec![Content {
-            role: Some("user".to_string()),
-            parts: vec![Part {
-                text: user_text.to_string(),
+/// Google Generative Language API.
+pub struct Gemini {
+    info: &'static ProviderInfo,
+}
+
+impl Gemini {
+    pub fn build(id: &str, _: ProviderConfig<'_>) -> Result<Box<dyn ReasoningProvider>, ProviderError> {
+        let info = registry()
+            .reasoning_provider(id)
+            .map_err(|_| ProviderError::UnknownProvider(id.to_string()))?;
+        Ok(Box::new(Self { info }))
+    }
+
+    /// POST `request` to the model's `method`, e.g. `generateContent`.
+    fn post(request: &CompletionRequest<'_>, method: &str) -> impl FnMut() -> reqwest::RequestBuilder {
+        let url = format!(
+            "https://generativelanguage.googleapis.com/v1beta/models/{}:{}",
+            request.model, method
+        );
+        let body = GeminiRequest {
+            system_instruction: SystemInstruction {
+                parts: vec![Part {
+                    text: request.system_prompt.to_string(),
+                }],
+            },
+            contents: vec![Content {
+                role: Some("user".to_string()),
+                parts: vec![Part {
+                    text: request.user_text.to_string(),
+                }],
             }],
-        }],
-        generation_config: GenerationConfig {
-            max_output_tokens: max_tokens.unwrap_or(2048),
-        },
-    };
-
-    let response = crate::HTTP_CLIENT
-        .post(&url)
-        .header("content-type", "application/json")
-        .json(&request)
-        .send()
-        .await?;
+            generation_config: GenerationConfig {
+                max_output_tokens: request.max_tokens.unwrap_or(2048),
+            },
+        };
 
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        anyhow::bail!("Gemini API error ({}): {}", status, body);
+        // Key in a header rather than the query string, so it never shows up in error messages
+        let api_key = request.api_key.unwrap_or_default().to_string();
+        move || {
+            crate::HTTP_CLIENT
+                .post(&url)
+                .header("x-goog-api-key", &api_key)
+                .header("content-type", "application/json")
+                .json(&body)
+        }
+    }
+}
+
+#[async_trait]
+impl ReasoningProvider for Gemini {
+    fn id(&self) -> &str {
+        &self.info.id
     }
 
-    let result: GeminiResponse = response.json().await?;
-
-    let text = result
-        .candidates
-        .first()
-        .map(|c| {
-            c.content
-                .parts
-                .iter()
-                .map(|p| p.text.as_str())
-                .collect::<Vec<_>>()
-                .join("")
+    fn capabilities(&self) -> Capabilities {
+        self.info.capabilities
+    }
+
+    async fn complete(&self, request: CompletionRequest<'_>) -> Result<Completion, ProviderError> {
+        self.info.check_model(request.model);
+        let result: GeminiResponse = http::send_json(&self.info.name, Self::post(&request, "generateContent")).await?;
+        Ok(Completion::new(result.text(), result.usage_metadata.map(Into::into)))
+    }
+
+    async fn complete_stream(
+        &self,
+        request: CompletionRequest<'_>,
+        on_delta: OnDelta<'_>,
+    ) -> Result<Completion, ProviderError> {
+        self.info.check_model(request.model);
+        let post = Self::post(&request, "streamGenerateContent?alt=sse");
+        let mut text = String::new();
+        let mut usage = None;
+        http::send_sse(&self.info.name, post, |chunk: GeminiResponse| {
+            let delta = chunk.text();
+            if !delta.is_empty() {
+                on_delta(&delta);
+                text.push_str(&delta);
+            }
+            if let Some(metadata) = chunk.usage_metadata {
+                usage = Some(metadata.into());
+            }
+            Ok(())
         })
-        .unwrap_or_default();
+        .await?;
+        Ok(Completion::new(text, usage))
+    }
 
-    Ok(text)
+    async fn list_models(&self, api_key: Option<&str>) -> Result<Vec<ListedModel>, ProviderError> {
+        let api_key = api_key.unwrap_or_default();
+        let result: ModelsResponse = http::send_json(&self.info.name, || {
+            crate::HTTP_CLIENT
+                .get("https://generativelanguage.googleapis.com/v1beta/models?pageSize=1000")
+                .header("x-goog-api-key", api_key)
+        })
+        .await?;
+        // Embedding and other non-chat models can't serve `generateContent`
+        let models = result
+            .models
+            .into_iter()
+            .filter(|m| m.supported_generation_methods.iter().any(|g| g == "generateContent"))
+            .map(|m| ListedModel::new(m.name.trim_start_matches("models/")));
+        Ok(models.collect())
+    }
 }
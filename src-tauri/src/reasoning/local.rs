@@ -0,0 +1,320 @@
+//! Local LLM enhancement through an Ollama or llama.cpp server, so dictation
+//! can stay fully offline when paired with a local transcription engine.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::endpoint::{AuthScheme, CustomEndpoint};
+use crate::http;
+use crate::provider::{
+    Capabilities, Completion, CompletionRequest, ListedModel, ProviderConfig, ProviderError, ReasoningProvider,
+    TokenUsage,
+};
+
+/// Provider id for the local server.
+pub const LOCAL_PROVIDER: &str = "local";
+
+/// Which HTTP API the local server speaks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LocalApi {
+    /// Ollama's native `/api/chat` and `/api/tags`
+    #[default]
+    Ollama,
+    /// OpenAI-compatible `/v1/chat/completions` and `/v1/models`
+    /// (llama.cpp `llama-server`, LM Studio, or Ollama's compat layer)
+    OpenAi,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LocalServer {
+    pub base_url: String,
+    pub api: LocalApi,
+}
+
+impl Default for LocalServer {
+    fn default() -> Self {
+        Self {
+            base_url: "http://127.0.0.1:11434".to_string(),
+            api: LocalApi::Ollama,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: [ChatMessage<'a>; 2],
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<ChatOptions>,
+}
+
+#[derive(Serialize)]
+struct ChatMessage<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Serialize)]
+struct ChatOptions {
+    num_predict: u32,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    message: ResponseMessage,
+    /// Prompt tokens evaluated; missing when the prompt was cached.
+    #[serde(default)]
+    prompt_eval_count: Option<u32>,
+    #[serde(default)]
+    eval_count: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct ResponseMessage {
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct TagsResponse {
+    models: Vec<TagModel>,
+}
+
+#[derive(Deserialize)]
+struct TagModel {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct ModelsResponse {
+    data: Vec<ModelEntry>,
+}
+
+#[derive(Deserialize)]
+struct ModelEntry {
+    id: String,
+}
+
+/// The `local` reasoning provider: no key, model chosen from the server's list.
+pub struct Local {
+    server: LocalServer,
+}
+
+impl Local {
+    pub fn build(_: &str, config: ProviderConfig<'_>) -> Result<Box<dyn ReasoningProvider>, ProviderError> {
+        Ok(Box::new(Self {
+            server: config.local.cloned().unwrap_or_default(),
+        }))
+    }
+}
+
+#[async_trait]
+impl ReasoningProvider for Local {
+    fn id(&self) -> &str {
+        LOCAL_PROVIDER
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
+
+    fn requires_api_key(&self) -> bool {
+        false
+    }
+
+    async fn complete(&self, request: CompletionRequest<'_>) -> Result<Completion, ProviderError> {
+        self.server.complete(&request).await
+    }
+
+    async fn list_models(&self, _: Option<&str>) -> Result<Vec<ListedModel>, ProviderError> {
+        let models = self.server.list_models().await?;
+        Ok(models.into_iter().map(ListedModel::new).collect())
+    }
+}
+
+impl LocalServer {
+    fn root(&self) -> &str {
+        self.base_url.trim().trim_end_matches('/')
+    }
+
+    /// The server as an OpenAI-compatible endpoint without credentials.
+    fn openai_endpoint(&self, model: &str) -> CustomEndpoint {
+        let root = self.root();
+        let base_url = if root.ends_with("/v1") {
+            root.to_string()
+        } else {
+            format!("{}/v1", root)
+        };
+        CustomEndpoint {
+            base_url,
+            auth_scheme: AuthScheme::None,
+            model: model.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub async fn complete(&self, request: &CompletionRequest<'_>) -> Result<Completion, ProviderError> {
+        if self.api == LocalApi::OpenAi {
+            let endpoint = self.openai_endpoint(request.model);
+            return super::openai::complete_custom(&endpoint, request).await;
+        }
+
+        let body = ChatRequest {
+            model: request.model,
+            messages: [
+                ChatMessage {
+                    role: "system",
+                    content: request.system_prompt,
+                },
+                ChatMessage {
+                    role: "user",
+                    content: request.user_text,
+                },
+            ],
+            stream: false,
+            options: request.max_tokens.map(|num_predict| ChatOptions { num_predict }),
+        };
+
+        let url = format!("{}/api/chat", self.root());
+        log::info!("[Whisperi] POST {} (model={})", url, request.model);
+        let result: ChatResponse = http::send_json("Ollama", || crate::HTTP_CLIENT.post(&url).json(&body)).await?;
+        let usage = result.eval_count.map(|completion_tokens| TokenUsage {
+            prompt_tokens: result.prompt_eval_count.unwrap_or_default(),
+            completion_tokens,
+        });
+        Ok(Completion::new(result.message.content, usage))
+    }
+
+    /// Models installed on the server, sorted by name.
+    pub async fn list_models(&self) -> Result<Vec<String>, ProviderError> {
+        let mut models: Vec<String> = match self.api {
+            LocalApi::Ollama => {
+                let url = format!("{}/api/tags", self.root());
+                let tags: TagsResponse = http::send_json("Ollama", || crate::HTTP_CLIENT.get(&url)).await?;
+                tags.models.into_iter().map(|m| m.name).collect()
+            }
+            LocalApi::OpenAi => {
+                let url = self
+                    .openai_endpoint("")
+                    .url("models")
+                    .map_err(|e| ProviderError::config(LOCAL_PROVIDER, format!("{:#}", e)))?;
+                let list: ModelsResponse = http::send_json("Local server", || crate::HTTP_CLIENT.get(&url)).await?;
+                list.data.into_iter().map(|m| m.id).collect()
+            }
+        };
+        models.sort();
+        Ok(models)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn server(uri: String, api: LocalApi) -> LocalServer {
+        LocalServer { base_url: uri, api }
+    }
+
+    fn request(model: &str, max_tokens: Option<u32>) -> CompletionRequest<'_> {
+        CompletionRequest {
+            api_key: None,
+            model,
+            system_prompt: "clean up",
+            user_text: "um hello",
+            max_tokens,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ollama_chat() {
+        let mock = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/chat"))
+            .and(body_partial_json(serde_json::json!({
+                "model": "qwen3:8b",
+                "stream": false,
+                "messages": [{ "role": "system", "content": "clean up" }, { "role": "user", "content": "um hello" }],
+                "options": { "num_predict": 256 }
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "model": "qwen3:8b",
+                "message": { "role": "assistant", "content": "Hello." },
+                "done": true,
+                "prompt_eval_count": 26,
+                "eval_count": 3
+            })))
+            .expect(1)
+            .mount(&mock)
+            .await;
+
+        let completion = server(mock.uri(), LocalApi::Ollama)
+            .complete(&request("qwen3:8b", Some(256)))
+            .await
+            .unwrap();
+        assert_eq!(completion.text, "Hello.");
+        let usage = completion.usage.unwrap();
+        assert_eq!((usage.prompt_tokens, usage.completion_tokens), (26, 3));
+    }
+
+    #[tokio::test]
+    async fn test_openai_compatible_chat() {
+        let mock = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(body_partial_json(serde_json::json!({ "model": "gemma-3-4b" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{ "message": { "role": "assistant", "content": "Hello." } }],
+                "usage": { "prompt_tokens": 20, "completion_tokens": 2, "total_tokens": 22 }
+            })))
+            .expect(1)
+            .mount(&mock)
+            .await;
+
+        let completion = server(mock.uri(), LocalApi::OpenAi)
+            .complete(&request("gemma-3-4b", None))
+            .await
+            .unwrap();
+        assert_eq!(completion.text, "Hello.");
+        assert_eq!(completion.usage.map(|u| u.completion_tokens), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_list_models_for_both_apis() {
+        let mock = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/tags"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "models": [{ "name": "qwen3:8b", "size": 5_200_000_000u64 }, { "name": "llama3.2:3b" }]
+            })))
+            .mount(&mock)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "object": "list",
+                "data": [{ "id": "gemma-3-4b", "object": "model" }]
+            })))
+            .mount(&mock)
+            .await;
+
+        let ollama = server(mock.uri(), LocalApi::Ollama).list_models().await.unwrap();
+        assert_eq!(ollama, ["llama3.2:3b", "qwen3:8b"]);
+        let openai = server(format!("{}/v1/", mock.uri()), LocalApi::OpenAi).list_models().await.unwrap();
+        assert_eq!(openai, ["gemma-3-4b"]);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_server_is_reported() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let uri = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let err = server(uri, LocalApi::Ollama).list_models().await.unwrap_err();
+        assert!(matches!(err, ProviderError::Network { .. }));
+        assert!(err.to_string().starts_with("Could not reach Ollama"));
+    }
+}
@@ -1,9 +1,17 @@
 pub mod anthropic;
 pub mod gemini;
+pub mod local;
 pub mod openai;
 
-use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+use crate::endpoint::{CUSTOM_PROVIDER, CustomEndpoint};
+use crate::provider::{
+    CompletionRequest, OnDelta, ProviderConfig, ProviderError, ProviderSummary, ReasoningProvider, Registry, TokenUsage,
+    require_key,
+};
+use local::{LOCAL_PROVIDER, LocalServer};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReasoningRequest {
@@ -13,6 +21,12 @@ pub struct ReasoningRequest {
     pub system_prompt: String,
     pub api_key: String,
     pub max_tokens: Option<u32>,
+    /// Endpoint used when `provider` is `custom`; its model overrides `model`.
+    #[serde(default)]
+    pub endpoint: Option<CustomEndpoint>,
+    /// Server used when `provider` is `local`.
+    #[serde(default)]
+    pub local: Option<LocalServer>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,44 +34,113 @@ pub struct ReasoningResponse {
     pub text: String,
     pub model: String,
     pub provider: String,
+    /// Tokens used, when the provider reports them.
+    pub usage: Option<TokenUsage>,
+}
+
+static PROVIDERS: LazyLock<Registry<dyn ReasoningProvider>> = LazyLock::new(|| {
+    Registry::default()
+        .register("openai", openai::OpenAi::build)
+        .register("groq", openai::OpenAi::build)
+        .register("qwen", openai::OpenAi::build)
+        .register("openrouter", openai::OpenAi::build)
+        .register("anthropic", anthropic::Anthropic::build)
+        .register("gemini", gemini::Gemini::build)
+        .register(LOCAL_PROVIDER, local::Local::build)
+        .register(CUSTOM_PROVIDER, openai::Custom::build)
+});
+
+/// Reasoning providers by id.
+pub fn providers() -> &'static Registry<dyn ReasoningProvider> {
+    &PROVIDERS
+}
+
+/// Every registered provider that can be built with `config`.
+pub fn summaries(config: ProviderConfig<'_>) -> Vec<ProviderSummary> {
+    providers()
+        .ids()
+        .filter_map(|id| providers().get(id, config).ok())
+        .map(|provider| ProviderSummary {
+            id: provider.id().to_string(),
+            capabilities: provider.capabilities(),
+            requires_api_key: provider.requires_api_key(),
+        })
+        .collect()
 }
 
 /// Process text through the appropriate AI provider
-pub async fn process(req: &ReasoningRequest) -> Result<ReasoningResponse> {
-    let text = match req.provider.as_str() {
-        "openai" => {
-            openai::complete(&req.api_key, &req.model, &req.system_prompt, &req.text, req.max_tokens, None).await?
-        }
-        "groq" => {
-            openai::complete(
-                &req.api_key, &req.model, &req.system_prompt, &req.text,
-                req.max_tokens, Some("https://api.groq.com/openai/v1"),
-            ).await?
-        }
-        "qwen" => {
-            openai::complete(
-                &req.api_key, &req.model, &req.system_prompt, &req.text,
-                req.max_tokens, Some("https://dashscope-intl.aliyuncs.com/compatible-mode/v1"),
-            ).await?
-        }
-        "openrouter" => {
-            openai::complete(
-                &req.api_key, &req.model, &req.system_prompt, &req.text,
-                req.max_tokens, Some("https://openrouter.ai/api/v1"),
-            ).await?
-        }
-        "anthropic" => {
-            anthropic::complete(&req.api_key, &req.model, &req.system_prompt, &req.text, req.max_tokens).await?
-        }
-        "gemini" => {
-            gemini::complete(&req.api_key, &req.model, &req.system_prompt, &req.text, req.max_tokens).await?
-        }
-        other => anyhow::bail!("Unknown reasoning provider: {}", other),
+pub async fn process(req: &ReasoningRequest) -> Result<ReasoningResponse, ProviderError> {
+    run(req, None).await
+}
+
+/// Like [`process`], passing the output to `on_delta` as it arrives.
+/// Providers that can't stream deliver it as a single delta.
+pub async fn process_stream(req: &ReasoningRequest, on_delta: OnDelta<'_>) -> Result<ReasoningResponse, ProviderError> {
+    run(req, Some(on_delta)).await
+}
+
+async fn run(req: &ReasoningRequest, on_delta: Option<OnDelta<'_>>) -> Result<ReasoningResponse, ProviderError> {
+    let config = ProviderConfig {
+        endpoint: req.endpoint.as_ref(),
+        local: req.local.as_ref(),
+    };
+    let provider = providers().get(&req.provider, config)?;
+    let api_key = require_key(&req.provider, provider.requires_api_key(), Some(&req.api_key))?;
+
+    let request = CompletionRequest {
+        api_key,
+        model: &req.model,
+        system_prompt: &req.system_prompt,
+        user_text: &req.text,
+        max_tokens: req.max_tokens,
+    };
+    let completion = match on_delta {
+        Some(on_delta) => provider.complete_stream(request, on_delta).await?,
+        None => provider.complete(request).await?,
     };
 
     Ok(ReasoningResponse {
-        text,
-        model: req.model.clone(),
+        text: completion.text,
+        model: provider.configured_model().unwrap_or(&req.model).to_string(),
         provider: req.provider.clone(),
+        usage: completion.usage,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::registry::registry;
+
+    fn request(provider: &str, api_key: &str) -> ReasoningRequest {
+        ReasoningRequest {
+            text: "hello".to_string(),
+            model: "m".to_string(),
+            provider: provider.to_string(),
+            system_prompt: String::new(),
+            api_key: api_key.to_string(),
+            max_tokens: None,
+            endpoint: None,
+            local: None,
+        }
+    }
+
+    #[test]
+    fn test_registry_covers_registry_data() {
+        let ids: Vec<_> = providers().ids().collect();
+        for info in &registry().reasoning_providers {
+            assert!(ids.contains(&info.id.as_str()), "no client for {}", info.id);
+        }
+        assert!(ids.contains(&LOCAL_PROVIDER) && ids.contains(&CUSTOM_PROVIDER));
+    }
+
+    #[tokio::test]
+    async fn test_process_fails_fast_with_typed_errors() {
+        let unknown = process(&request("nope", "k")).await.unwrap_err();
+        assert!(matches!(unknown, ProviderError::UnknownProvider(_)));
+        let no_key = process(&request("anthropic", "")).await.unwrap_err();
+        assert!(matches!(no_key, ProviderError::MissingApiKey(_)));
+        let no_endpoint = process(&request(CUSTOM_PROVIDER, "")).await.unwrap_err();
+        assert!(matches!(no_endpoint, ProviderError::Config { .. }));
+    }
+}
@@ -1,6 +1,15 @@
-use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::RequestBuilder;
 use serde::{Deserialize, Serialize};
 
+use crate::endpoint::{CUSTOM_PROVIDER, CustomEndpoint};
+use crate::models::registry::{ProviderInfo, registry};
+use crate::http;
+use crate::provider::{
+    Capabilities, Completion, CompletionRequest, ListedModel, OnDelta, ProviderConfig, ProviderError,
+    ReasoningProvider, TokenUsage,
+};
+
 // --- Responses API types ---
 
 #[derive(Serialize)]
@@ -9,6 +18,8 @@ struct ResponsesRequest {
     input: Vec<InputItem>,
     #[serde(skip_serializing_if = "Option::is_none")]
     max_output_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
 }
 
 #[derive(Serialize)]
@@ -20,6 +31,24 @@ struct InputItem {
 #[derive(Deserialize)]
 struct ResponsesResponse {
     output: Vec<OutputItem>,
+    #[serde(default)]
+    usage: Option<ResponsesUsage>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct ResponsesUsage {
+    input_tokens: u32,
+    output_tokens: u32,
+}
+
+impl From<ResponsesUsage> for TokenUsage {
+    fn from(usage: ResponsesUsage) -> Self {
+        Self {
+            prompt_tokens: usage.input_tokens,
+            completion_tokens: usage.output_tokens,
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -36,6 +65,21 @@ struct ContentBlock {
     text: Option<String>,
 }
 
+/// Server-sent event of a streamed response.
+#[derive(Deserialize)]
+struct ResponsesEvent {
+    #[serde(rename = "type")]
+    event_type: String,
+    #[serde(default)]
+    delta: Option<String>,
+    /// Set on `error` events.
+    #[serde(default)]
+    message: Option<String>,
+    /// The finished response, on `response.completed`.
+    #[serde(default)]
+    response: Option<ResponsesResponse>,
+}
+
 // --- Chat Completions API types ---
 
 #[derive(Serialize)]
@@ -44,6 +88,16 @@ struct ChatRequest {
     messages: Vec<ChatMessage>,
     #[serde(skip_serializing_if = "Option::is_none")]
     max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<StreamOptions>,
+}
+
+/// Asks for a final chunk with the token usage of a streamed completion.
+#[derive(Serialize)]
+struct StreamOptions {
+    include_usage: bool,
 }
 
 #[derive(Serialize)]
@@ -55,6 +109,25 @@ struct ChatMessage {
 #[derive(Deserialize)]
 struct ChatResponse {
     choices: Vec<ChatChoice>,
+    #[serde(default)]
+    usage: Option<ChatUsage>,
+}
+
+#[derive(Deserialize)]
+struct ChatUsage {
+    #[serde(default)]
+    prompt_tokens: u32,
+    #[serde(default)]
+    completion_tokens: u32,
+}
+
+impl From<ChatUsage> for TokenUsage {
+    fn from(usage: ChatUsage) -> Self {
+        Self {
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: usage.completion_tokens,
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -62,74 +135,145 @@ struct ChatChoice {
     message: ChatChoiceMessage,
 }
 
-#[derive(Deserialize)]
+#[derive(Default, Deserialize)]
 struct ChatChoiceMessage {
     content: Option<String>,
 }
 
-pub async fn complete(
-    api_key: &str,
-    model: &str,
-    system_prompt: &str,
-    user_text: &str,
-    max_tokens: Option<u32>,
-    base_url: Option<&str>,
-) -> Result<String> {
-    let client = &*crate::HTTP_CLIENT;
-    let base = base_url.unwrap_or("https://api.openai.com/v1");
-
-    // Try Responses API first (newer models) — only for OpenAI
-    if base_url.is_none() {
-        match complete_responses(client, api_key, model, system_prompt, user_text, max_tokens, base).await {
-            Ok(text) => return Ok(text),
-            Err(e) => {
-                log::debug!("Responses API failed, falling back to Chat Completions: {}", e);
+/// Server-sent chunk of a streamed chat completion.
+#[derive(Deserialize)]
+struct ChatChunk {
+    #[serde(default)]
+    choices: Vec<ChunkChoice>,
+    /// Only on the last chunk, when `stream_options.include_usage` is set.
+    #[serde(default)]
+    usage: Option<ChatUsage>,
+}
+
+#[derive(Deserialize)]
+struct ChunkChoice {
+    #[serde(default)]
+    delta: ChatChoiceMessage,
+}
+
+const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// OpenAI and the OpenAI-compatible chat APIs in the registry (Groq, Qwen,
+/// OpenRouter). Providers without a base URL are OpenAI itself, which tries
+/// the Responses API first.
+pub struct OpenAi {
+    info: &'static ProviderInfo,
+}
+
+impl OpenAi {
+    pub fn build(id: &str, _: ProviderConfig<'_>) -> Result<Box<dyn ReasoningProvider>, ProviderError> {
+        let info = registry()
+            .reasoning_provider(id)
+            .map_err(|_| ProviderError::UnknownProvider(id.to_string()))?;
+        Ok(Box::new(Self { info }))
+    }
+
+    /// Complete `request`, streaming to `on_delta` when given.
+    async fn run(&self, request: CompletionRequest<'_>, on_delta: Option<OnDelta<'_>>) -> Result<Completion, ProviderError> {
+        self.info.check_model(request.model);
+        let api_key = request.api_key.unwrap_or_default();
+        let Some(base_url) = self.info.base_url.as_deref() else {
+            // Try Responses API first (newer models) — only for OpenAI. Key,
+            // quota and availability errors would fail Chat Completions too.
+            match complete_responses(api_key, &request, OPENAI_BASE_URL, on_delta).await {
+                Ok(completion) => return Ok(completion),
+                Err(e @ ProviderError::Api { .. }) => {
+                    log::debug!("Responses API failed, falling back to Chat Completions: {}", e);
+                }
+                // A broken stream may already have delivered text, so only a rejected request falls back
+                Err(e @ ProviderError::InvalidResponse { .. }) if on_delta.is_none() => {
+                    log::debug!("Responses API failed, falling back to Chat Completions: {}", e);
+                }
+                Err(e) => return Err(e),
             }
-        }
+            return complete_chat(&self.info.name, api_key, &request, OPENAI_BASE_URL, on_delta).await;
+        };
+        complete_chat(&self.info.name, api_key, &request, base_url, on_delta).await
+    }
+}
+
+#[async_trait]
+impl ReasoningProvider for OpenAi {
+    fn id(&self) -> &str {
+        &self.info.id
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.info.capabilities
     }
 
-    // Fall back to Chat Completions API
-    complete_chat(client, api_key, model, system_prompt, user_text, max_tokens, base).await
+    async fn complete(&self, request: CompletionRequest<'_>) -> Result<Completion, ProviderError> {
+        self.run(request, None).await
+    }
+
+    async fn complete_stream(
+        &self,
+        request: CompletionRequest<'_>,
+        on_delta: OnDelta<'_>,
+    ) -> Result<Completion, ProviderError> {
+        self.run(request, Some(on_delta)).await
+    }
+
+    async fn list_models(&self, api_key: Option<&str>) -> Result<Vec<ListedModel>, ProviderError> {
+        let base_url = self.info.base_url.as_deref().unwrap_or(OPENAI_BASE_URL);
+        http::list_bearer_models(&self.info.name, base_url, api_key.unwrap_or_default()).await
+    }
 }
 
 async fn complete_responses(
-    client: &reqwest::Client,
     api_key: &str,
-    model: &str,
-    system_prompt: &str,
-    user_text: &str,
-    max_tokens: Option<u32>,
+    request: &CompletionRequest<'_>,
     base_url: &str,
-) -> Result<String> {
-    let request = ResponsesRequest {
-        model: model.to_string(),
+    on_delta: Option<OnDelta<'_>>,
+) -> Result<Completion, ProviderError> {
+    let body = ResponsesRequest {
+        model: request.model.to_string(),
         input: vec![
             InputItem {
                 role: "system".to_string(),
-                content: system_prompt.to_string(),
+                content: request.system_prompt.to_string(),
             },
             InputItem {
                 role: "user".to_string(),
-                content: user_text.to_string(),
+                content: request.user_text.to_string(),
             },
         ],
-        max_output_tokens: max_tokens,
+        max_output_tokens: request.max_tokens,
+        stream: on_delta.is_some(),
     };
 
-    let response = client
-        .post(format!("{}/responses", base_url))
-        .bearer_auth(api_key)
-        .json(&request)
-        .send()
+    let url = format!("{}/responses", base_url);
+    let send = || crate::HTTP_CLIENT.post(&url).bearer_auth(api_key).json(&body);
+    if let Some(on_delta) = on_delta {
+        let mut text = String::new();
+        let mut usage = None;
+        http::send_sse("OpenAI", send, |event: ResponsesEvent| {
+            match event.event_type.as_str() {
+                "response.output_text.delta" => {
+                    let delta = event.delta.unwrap_or_default();
+                    on_delta(&delta);
+                    text.push_str(&delta);
+                }
+                "response.completed" => usage = event.response.and_then(|r| r.usage).map(Into::into),
+                "error" | "response.failed" => {
+                    return Err(ProviderError::InvalidResponse {
+                        provider: "OpenAI".to_string(),
+                        message: event.message.unwrap_or_else(|| "the response failed".to_string()),
+                    });
+                }
+                _ => {}
+            }
+            Ok(())
+        })
         .await?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        anyhow::bail!("OpenAI Responses API error ({}): {}", status, body);
+        return Ok(Completion::new(text, usage));
     }
-
-    let result: ResponsesResponse = response.json().await?;
+    let result: ResponsesResponse = http::send_json("OpenAI", send).await?;
 
     let text = result
         .output
@@ -142,59 +286,123 @@ async fn complete_responses(
         .collect::<Vec<_>>()
         .join("");
 
-    Ok(text)
+    Ok(Completion::new(text, result.usage.map(Into::into)))
 }
 
 async fn complete_chat(
-    client: &reqwest::Client,
+    provider: &str,
     api_key: &str,
-    model: &str,
-    system_prompt: &str,
-    user_text: &str,
-    max_tokens: Option<u32>,
+    request: &CompletionRequest<'_>,
     base_url: &str,
-) -> Result<String> {
-    let request = ChatRequest {
+    on_delta: Option<OnDelta<'_>>,
+) -> Result<Completion, ProviderError> {
+    let mut body = chat_request(request.model, request);
+    body.stream = on_delta.is_some();
+    body.stream_options = body.stream.then_some(StreamOptions { include_usage: true });
+    let url = format!("{}/chat/completions", base_url);
+    // OpenRouter requires these headers for proper authentication routing
+    let openrouter = base_url.contains("openrouter.ai");
+
+    let authorize = |builder: RequestBuilder| {
+        let builder = builder.bearer_auth(api_key);
+        if openrouter {
+            builder
+                .header("HTTP-Referer", "https://github.com/xarthurx/whisperi")
+                .header("X-Title", "Whisperi")
+        } else {
+            builder
+        }
+    };
+    match on_delta {
+        Some(on_delta) => stream_chat(provider, &url, &body, authorize, on_delta).await,
+        None => send_chat(provider, &url, &body, authorize).await,
+    }
+}
+
+/// A user-configured OpenAI-compatible endpoint; its model overrides the requested one.
+pub struct Custom {
+    endpoint: CustomEndpoint,
+}
+
+impl Custom {
+    pub fn build(_: &str, config: ProviderConfig<'_>) -> Result<Box<dyn ReasoningProvider>, ProviderError> {
+        let endpoint = config
+            .endpoint
+            .ok_or_else(|| ProviderError::config(CUSTOM_PROVIDER, "no endpoint configured. Set it in Settings."))?;
+        Ok(Box::new(Self {
+            endpoint: endpoint.clone(),
+        }))
+    }
+}
+
+#[async_trait]
+impl ReasoningProvider for Custom {
+    fn id(&self) -> &str {
+        CUSTOM_PROVIDER
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
+
+    fn requires_api_key(&self) -> bool {
+        false
+    }
+
+    fn configured_model(&self) -> Option<&str> {
+        Some(&self.endpoint.model)
+    }
+
+    async fn complete(&self, request: CompletionRequest<'_>) -> Result<Completion, ProviderError> {
+        complete_custom(&self.endpoint, &request).await
+    }
+
+    async fn list_models(&self, api_key: Option<&str>) -> Result<Vec<ListedModel>, ProviderError> {
+        self.endpoint.list_models(api_key).await
+    }
+}
+
+/// Chat Completions against a user-configured OpenAI-compatible endpoint.
+pub async fn complete_custom(
+    endpoint: &CustomEndpoint,
+    request: &CompletionRequest<'_>,
+) -> Result<Completion, ProviderError> {
+    let config_error = |e: anyhow::Error| ProviderError::config(CUSTOM_PROVIDER, format!("{:#}", e));
+    let body = chat_request(&endpoint.model, request);
+    let url = endpoint.url("chat/completions").map_err(config_error)?;
+    let headers = endpoint.headers(request.api_key).map_err(config_error)?;
+    send_chat("Custom endpoint", &url, &body, |builder| builder.headers(headers.clone())).await
+}
+
+fn chat_request(model: &str, request: &CompletionRequest<'_>) -> ChatRequest {
+    ChatRequest {
         model: model.to_string(),
         messages: vec![
             ChatMessage {
                 role: "system".to_string(),
-                content: system_prompt.to_string(),
+                content: request.system_prompt.to_string(),
             },
             ChatMessage {
                 role: "user".to_string(),
-                content: user_text.to_string(),
+                content: request.user_text.to_string(),
             },
         ],
-        max_tokens,
-    };
-
-    let url = format!("{}/chat/completions", base_url);
-    log::info!("[Whisperi] POST {} (model={})", url, model);
-    let mut req_builder = client
-        .post(&url)
-        .bearer_auth(api_key);
-
-    // OpenRouter requires these headers for proper authentication routing
-    if base_url.contains("openrouter.ai") {
-        req_builder = req_builder
-            .header("HTTP-Referer", "https://github.com/xarthurx/whisperi")
-            .header("X-Title", "Whisperi");
-    }
-
-    let response = req_builder
-        .json(&request)
-        .send()
-        .await?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        log::error!("[Whisperi] Chat API error ({}): {}", status, body);
-        anyhow::bail!("Chat API error ({}): {}", status, body);
+        max_tokens: request.max_tokens,
+        stream: false,
+        stream_options: None,
     }
+}
 
-    let result: ChatResponse = response.json().await?;
+/// POST a Chat Completions request; `authorize` adds the provider's headers.
+async fn send_chat(
+    provider: &str,
+    url: &str,
+    body: &ChatRequest,
+    authorize: impl Fn(RequestBuilder) -> RequestBuilder,
+) -> Result<Completion, ProviderError> {
+    log::info!("[Whisperi] POST {} (model={})", url, body.model);
+    let result: ChatResponse =
+        http::send_json(provider, || authorize(crate::HTTP_CLIENT.post(url)).json(body)).await?;
 
     let text = result
         .choices
@@ -202,5 +410,35 @@ async fn complete_chat(
         .and_then(|c| c.message.content.clone())
         .unwrap_or_default();
 
-    Ok(text)
+    Ok(Completion::new(text, result.usage.map(Into::into)))
+}
+
+/// POST a streaming Chat Completions request, passing each content delta to `on_delta`.
+async fn stream_chat(
+    provider: &str,
+    url: &str,
+    body: &ChatRequest,
+    authorize: impl Fn(RequestBuilder) -> RequestBuilder,
+    on_delta: OnDelta<'_>,
+) -> Result<Completion, ProviderError> {
+    log::info!("[Whisperi] POST {} (model={}, streaming)", url, body.model);
+    let mut text = String::new();
+    let mut usage = None;
+    http::send_sse(
+        provider,
+        || authorize(crate::HTTP_CLIENT.post(url)).json(body),
+        |chunk: ChatChunk| {
+            if let Some(chunk_usage) = chunk.usage {
+                usage = Some(chunk_usage.into());
+            }
+            let delta = chunk.choices.into_iter().next().and_then(|c| c.delta.content);
+            if let Some(delta) = delta.filter(|d| !d.is_empty()) {
+                on_delta(&delta);
+                text.push_str(&delta);
+            }
+            Ok(())
+        },
+    )
+    .await?;
+    Ok(Completion::new(text, usage))
 }
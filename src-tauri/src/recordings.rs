@@ -0,0 +1,251 @@
+//! Audio kept per dictation, so a failed transcription or enhancement can be
+//! retried without speaking again. Each clip is the 16 kHz WAV the recorder
+//! produced, bzip2-compressed into `recordings/<id>.wav.bz2` next to the
+//! database and linked to its `transcriptions` row by id.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::database::{Database, StoredAudio};
+
+/// Which dictations keep their audio.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeepAudio {
+    Off,
+    /// Only dictations whose transcription, enhancement or paste failed.
+    #[default]
+    Failures,
+    All,
+}
+
+/// How much audio to keep; a zero limit is no limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Retention {
+    pub keep: KeepAudio,
+    pub max_age_days: u32,
+    pub max_megabytes: u32,
+}
+
+impl Retention {
+    /// Whether a dictation that just finished keeps its audio.
+    pub fn keeps(&self, failed: bool) -> bool {
+        match self.keep {
+            KeepAudio::Off => false,
+            KeepAudio::Failures => failed,
+            KeepAudio::All => true,
+        }
+    }
+}
+
+/// Managed state owning the recordings directory.
+pub struct Recordings {
+    dir: PathBuf,
+}
+
+impl Recordings {
+    pub fn open(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn path(&self, id: i64) -> PathBuf {
+        self.dir.join(format!("{}.wav.bz2", id))
+    }
+
+    /// Compress and store the clip of history row `id`, returning the size on disk.
+    pub fn save(&self, id: i64, wav: &[u8]) -> Result<u64> {
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        encoder.write_all(wav)?;
+        let compressed = encoder.finish()?;
+
+        // Written aside first so a crash never leaves a truncated clip behind
+        let path = self.path(id);
+        let partial = path.with_extension("part");
+        std::fs::write(&partial, &compressed).with_context(|| format!("Failed to write {}", partial.display()))?;
+        std::fs::rename(&partial, &path)?;
+        Ok(compressed.len() as u64)
+    }
+
+    /// The WAV clip of history row `id`.
+    pub fn load(&self, id: i64) -> Result<Vec<u8>> {
+        let path = self.path(id);
+        let file = std::fs::File::open(&path).with_context(|| format!("No recording for dictation {}", id))?;
+        let mut wav = Vec::new();
+        bzip2::read::BzDecoder::new(file)
+            .read_to_end(&mut wav)
+            .with_context(|| format!("Corrupt recording {}", path.display()))?;
+        Ok(wav)
+    }
+
+    /// Write the clip of history row `id` to `dest` as a plain WAV file.
+    pub fn export(&self, id: i64, dest: &Path) -> Result<()> {
+        let wav = self.load(id)?;
+        std::fs::write(dest, wav).with_context(|| format!("Failed to write {}", dest.display()))
+    }
+
+    /// Remove the clip of history row `id`, if there is one.
+    pub fn delete(&self, id: i64) -> Result<()> {
+        match std::fs::remove_file(self.path(id)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Remove every stored clip.
+    pub fn clear(&self) -> Result<()> {
+        match std::fs::remove_dir_all(&self.dir) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Store the clip of a dictation just saved as row `id`, then drop whatever
+/// `retention` no longer covers.
+pub fn keep(db: &Database, recordings: &Recordings, id: i64, wav: &[u8], retention: &Retention) -> Result<()> {
+    let size = recordings.save(id, wav)?;
+    db.set_audio_size(id, Some(size as i64))?;
+    log::info!("[Whisperi] Kept recording of dictation {} ({} KB)", id, size / 1024);
+    prune(db, recordings, retention)?;
+    Ok(())
+}
+
+/// Delete the audio of row `id` and unlink it from the row.
+pub fn delete(db: &Database, recordings: &Recordings, id: i64) -> Result<()> {
+    recordings.delete(id)?;
+    db.set_audio_size(id, None)
+}
+
+/// Delete the clips `retention` no longer covers, returning how many went.
+pub fn prune(db: &Database, recordings: &Recordings, retention: &Retention) -> Result<usize> {
+    let stored = db.get_stored_audio()?;
+    let expired = expired(&stored, retention);
+    for &id in &expired {
+        delete(db, recordings, id)?;
+    }
+    if !expired.is_empty() {
+        log::info!("[Whisperi] Deleted {} recordings past the retention limits", expired.len());
+    }
+    Ok(expired.len())
+}
+
+/// Ids of the clips to delete. `stored` is newest first, so the size limit
+/// keeps the most recent clips that fit.
+fn expired(stored: &[StoredAudio], retention: &Retention) -> Vec<i64> {
+    let max_bytes = retention.max_megabytes as i64 * 1024 * 1024;
+    let mut total = 0;
+    stored
+        .iter()
+        .filter(|audio| {
+            let too_old = retention.max_age_days > 0 && audio.age_days > retention.max_age_days as f64;
+            let kept = retention.keeps(audio.failed) && !too_old;
+            if kept {
+                total += audio.size;
+            }
+            !kept || (max_bytes > 0 && total > max_bytes)
+        })
+        .map(|audio| audio.id)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{DictationMetadata, NewTranscription};
+
+    #[test]
+    fn test_recordings_round_trip_compressed() {
+        let dir = std::env::temp_dir().join(format!("whisperi-recordings-{}", std::process::id()));
+        let recordings = Recordings::open(dir.clone());
+        let wav: Vec<u8> = b"RIFF....WAVE".iter().copied().chain(std::iter::repeat_n(0, 32_000)).collect();
+
+        let size = recordings.save(7, &wav).unwrap();
+        assert!(size < wav.len() as u64 / 10, "silence compresses well");
+        assert_eq!(recordings.load(7).unwrap(), wav);
+        let exported = dir.join("exported.wav");
+        recordings.export(7, &exported).unwrap();
+        assert_eq!(std::fs::read(&exported).unwrap(), wav);
+
+        recordings.delete(7).unwrap();
+        recordings.delete(7).unwrap();
+        assert!(recordings.load(7).is_err());
+        recordings.clear().unwrap();
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn test_keep_retains_failed_paste_under_failures_policy() {
+        let db = Database::open_in_memory().unwrap();
+        let dir = std::env::temp_dir().join(format!("whisperi-recordings-paste-{}", std::process::id()));
+        let recordings = Recordings::open(dir.clone());
+        let save = |paste_succeeded| {
+            db.save_transcription(&NewTranscription {
+                original_text: "hello".to_string(),
+                processing_method: "none".to_string(),
+                metadata: DictationMetadata {
+                    paste_succeeded,
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .unwrap()
+        };
+        let unpasted = save(Some(false));
+        let pasted = save(Some(true));
+
+        let failures = Retention::default();
+        keep(&db, &recordings, unpasted, b"RIFF", &failures).unwrap();
+        keep(&db, &recordings, pasted, b"RIFF", &failures).unwrap();
+
+        let stored: Vec<i64> = db.get_stored_audio().unwrap().iter().map(|a| a.id).collect();
+        assert_eq!(stored, [unpasted]);
+        assert_eq!(recordings.load(unpasted).unwrap(), b"RIFF");
+        recordings.clear().unwrap();
+    }
+
+    #[test]
+    fn test_expired_applies_policy_age_and_size() {
+        let audio = |id, size, failed, age_days| StoredAudio {
+            id,
+            size,
+            failed,
+            age_days,
+        };
+        let mb = 1024 * 1024;
+        // Newest first
+        let stored = [
+            audio(5, mb, false, 0.1),
+            audio(4, mb, true, 1.0),
+            audio(3, mb, true, 2.0),
+            audio(2, mb, true, 40.0),
+            audio(1, mb, false, 41.0),
+        ];
+
+        let all = Retention {
+            keep: KeepAudio::All,
+            ..Default::default()
+        };
+        assert!(expired(&stored, &all).is_empty());
+        let failures = Retention::default();
+        assert_eq!(expired(&stored, &failures), [5, 1]);
+        let recent = Retention {
+            max_age_days: 30,
+            ..all
+        };
+        assert_eq!(expired(&stored, &recent), [2, 1]);
+        let small = Retention {
+            max_megabytes: 2,
+            ..all
+        };
+        assert_eq!(expired(&stored, &small), [3, 2, 1]);
+        let off = Retention {
+            keep: KeepAudio::Off,
+            ..all
+        };
+        assert_eq!(expired(&stored, &off).len(), stored.len());
+    }
+}
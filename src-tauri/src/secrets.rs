@@ -0,0 +1,376 @@
+//! API key storage. Keys live in the OS credential store (Secret Service on
+//! Linux, Keychain on macOS, Credential Manager on Windows) and are read by
+//! the backend itself, so they never cross IPC. When no credential store is
+//! reachable (e.g. a Linux session without a keyring daemon) keys fall back to
+//! a user-only file in the app data directory.
+
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+use crate::commands::settings::STORE_FILE;
+use crate::models::registry::registry;
+
+/// Service name the keys are filed under in the OS credential store.
+const KEYRING_SERVICE: &str = "com.whisperi.app";
+const FALLBACK_FILE: &str = "secrets.json";
+/// Accounts for the custom endpoints' optional keys, next to the provider ids.
+const CUSTOM_ACCOUNTS: [&str; 2] = ["customTranscription", "customReasoning"];
+
+/// Where keys are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StoreKind {
+    Keyring,
+    File,
+    #[cfg(test)]
+    Memory,
+}
+
+pub trait SecretStore: Send + Sync {
+    fn kind(&self) -> StoreKind;
+    fn get(&self, account: &str) -> Result<Option<String>>;
+    fn set(&self, account: &str, secret: &str) -> Result<()>;
+    fn delete(&self, account: &str) -> Result<()>;
+}
+
+/// The platform credential store through `keyring`.
+pub struct KeyringStore;
+
+impl KeyringStore {
+    /// Whether the platform store answers at all; a missing entry counts as
+    /// reachable.
+    fn is_available() -> bool {
+        let probe = keyring::Entry::new(KEYRING_SERVICE, "probe").and_then(|entry| entry.get_password());
+        match probe {
+            Ok(_) | Err(keyring::Error::NoEntry) => true,
+            Err(e) => {
+                log::warn!("[Whisperi] OS keyring unavailable: {}", e);
+                false
+            }
+        }
+    }
+
+    fn entry(account: &str) -> Result<keyring::Entry> {
+        keyring::Entry::new(KEYRING_SERVICE, account).context("Invalid keyring entry")
+    }
+}
+
+impl SecretStore for KeyringStore {
+    fn kind(&self) -> StoreKind {
+        StoreKind::Keyring
+    }
+
+    fn get(&self, account: &str) -> Result<Option<String>> {
+        match Self::entry(account)?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e).context("Failed to read from the OS keyring"),
+        }
+    }
+
+    fn set(&self, account: &str, secret: &str) -> Result<()> {
+        Self::entry(account)?
+            .set_password(secret)
+            .context("Failed to write to the OS keyring")
+    }
+
+    fn delete(&self, account: &str) -> Result<()> {
+        match Self::entry(account)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e).context("Failed to delete from the OS keyring"),
+        }
+    }
+}
+
+/// JSON file readable only by the user, for systems without a credential store.
+pub struct FileStore {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl FileStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn read(&self) -> Result<HashMap<String, String>> {
+        match std::fs::read(&self.path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .with_context(|| format!("Corrupt secrets file {}", self.path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", self.path.display())),
+        }
+    }
+
+    fn write(&self, secrets: &HashMap<String, String>) -> Result<()> {
+        let tmp = self.path.with_extension("json.tmp");
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let file = options
+            .open(&tmp)
+            .with_context(|| format!("Failed to write {}", tmp.display()))?;
+        serde_json::to_writer(file, secrets)?;
+        std::fs::rename(&tmp, &self.path).with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+}
+
+impl SecretStore for FileStore {
+    fn kind(&self) -> StoreKind {
+        StoreKind::File
+    }
+
+    fn get(&self, account: &str) -> Result<Option<String>> {
+        let _guard = self.lock.lock().unwrap();
+        Ok(self.read()?.remove(account))
+    }
+
+    fn set(&self, account: &str, secret: &str) -> Result<()> {
+        let _guard = self.lock.lock().unwrap();
+        let mut secrets = self.read()?;
+        secrets.insert(account.to_string(), secret.to_string());
+        self.write(&secrets)
+    }
+
+    fn delete(&self, account: &str) -> Result<()> {
+        let _guard = self.lock.lock().unwrap();
+        let mut secrets = self.read()?;
+        if secrets.remove(account).is_some() {
+            self.write(&secrets)?;
+        }
+        Ok(())
+    }
+}
+
+/// Process-lifetime store, for tests.
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemoryStore(Mutex<HashMap<String, String>>);
+
+#[cfg(test)]
+impl SecretStore for MemoryStore {
+    fn kind(&self) -> StoreKind {
+        StoreKind::Memory
+    }
+
+    fn get(&self, account: &str) -> Result<Option<String>> {
+        Ok(self.0.lock().unwrap().get(account).cloned())
+    }
+
+    fn set(&self, account: &str, secret: &str) -> Result<()> {
+        self.0.lock().unwrap().insert(account.to_string(), secret.to_string());
+        Ok(())
+    }
+
+    fn delete(&self, account: &str) -> Result<()> {
+        self.0.lock().unwrap().remove(account);
+        Ok(())
+    }
+}
+
+/// Managed state: the active store plus a cache, since platform stores can
+/// take a D-Bus round trip per read.
+pub struct Secrets {
+    store: Box<dyn SecretStore>,
+    cache: Mutex<HashMap<String, Option<String>>>,
+}
+
+impl Secrets {
+    pub fn new(store: impl SecretStore + 'static) -> Self {
+        Self {
+            store: Box::new(store),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The OS keyring, or the fallback file in `data_dir` when it is unreachable.
+    pub fn open(data_dir: PathBuf) -> Self {
+        if KeyringStore::is_available() {
+            return Self::new(KeyringStore);
+        }
+        let path = data_dir.join(FALLBACK_FILE);
+        log::warn!("[Whisperi] Storing API keys in {}", path.display());
+        Self::new(FileStore::new(path))
+    }
+
+    pub fn kind(&self) -> StoreKind {
+        self.store.kind()
+    }
+
+    /// Stored key for `account`; read errors are logged and treated as absent.
+    pub fn get(&self, account: &str) -> Option<String> {
+        if let Some(cached) = self.cache.lock().unwrap().get(account) {
+            return cached.clone();
+        }
+        match self.store.get(account) {
+            Ok(secret) => {
+                self.cache.lock().unwrap().insert(account.to_string(), secret.clone());
+                secret
+            }
+            // Not cached: a locked keyring may be unlocked by the next read
+            Err(e) => {
+                log::error!("[Whisperi] Failed to read API key for {}: {:#}", account, e);
+                None
+            }
+        }
+    }
+
+    /// Store a key; an empty one deletes it.
+    pub fn set(&self, account: &str, secret: &str) -> Result<()> {
+        check_account(account)?;
+        let secret = secret.trim();
+        if secret.is_empty() {
+            return self.delete(account);
+        }
+        self.store.set(account, secret)?;
+        self.cache.lock().unwrap().insert(account.to_string(), Some(secret.to_string()));
+        Ok(())
+    }
+
+    pub fn delete(&self, account: &str) -> Result<()> {
+        check_account(account)?;
+        self.store.delete(account)?;
+        self.cache.lock().unwrap().insert(account.to_string(), None);
+        Ok(())
+    }
+
+    /// Every stored key by account, in the form `DictationSettings::api_keys` uses.
+    pub fn api_keys(&self) -> HashMap<String, String> {
+        accounts()
+            .filter_map(|account| self.get(&account).map(|secret| (account, secret)))
+            .collect()
+    }
+
+    /// Move `{account}ApiKey` entries out of the settings store. Returns the
+    /// settings keys that were moved and can be deleted there.
+    pub fn import_plaintext(&self, settings: &Map<String, Value>) -> Vec<String> {
+        let mut moved = Vec::new();
+        for (key, value) in settings {
+            let Some(account) = key.strip_suffix("ApiKey") else {
+                continue;
+            };
+            let secret = value.as_str().unwrap_or_default();
+            match self.set(account, secret) {
+                Ok(()) => moved.push(key.clone()),
+                Err(e) => log::error!("[Whisperi] Failed to move the {} key to secure storage: {:#}", account, e),
+            }
+        }
+        moved
+    }
+}
+
+/// Move plain-text API keys left in `settings.json` by older versions into
+/// `secrets`.
+pub fn migrate_settings(app: &AppHandle, secrets: &Secrets) -> Result<()> {
+    let store = app.store(STORE_FILE)?;
+    let entries: Map<String, Value> = store
+        .keys()
+        .into_iter()
+        .filter(|key| key.ends_with("ApiKey"))
+        .filter_map(|key| store.get(&key).map(|val| (key, val)))
+        .collect();
+    if entries.is_empty() {
+        return Ok(());
+    }
+    for key in secrets.import_plaintext(&entries) {
+        store.delete(&key);
+    }
+    store.save()?;
+    log::info!("[Whisperi] Moved API keys to {:?} storage", secrets.kind());
+    Ok(())
+}
+
+/// Accounts keys can be stored under: every registry provider plus the
+/// custom endpoints.
+fn accounts() -> impl Iterator<Item = String> {
+    let registry = registry();
+    let mut ids: Vec<String> = registry
+        .transcription_providers
+        .iter()
+        .chain(&registry.reasoning_providers)
+        .map(|p| p.id.clone())
+        .chain(CUSTOM_ACCOUNTS.map(String::from))
+        .collect();
+    ids.sort();
+    ids.dedup();
+    ids.into_iter()
+}
+
+fn check_account(account: &str) -> Result<()> {
+    if !accounts().any(|a| a == account) {
+        bail!("Unknown provider: {}", account);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_get_delete_and_cache() {
+        let secrets = Secrets::new(MemoryStore::default());
+        assert_eq!(secrets.get("groq"), None);
+
+        secrets.set("groq", " gsk-1 ").unwrap();
+        secrets.set("customReasoning", "local-key").unwrap();
+        assert_eq!(secrets.get("groq").as_deref(), Some("gsk-1"));
+        let keys = secrets.api_keys();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys["customReasoning"], "local-key");
+
+        secrets.set("groq", "").unwrap();
+        assert_eq!(secrets.get("groq"), None);
+        assert!(secrets.set("nope", "x").is_err(), "unknown accounts are rejected");
+    }
+
+    #[test]
+    fn test_file_store_round_trip() {
+        let dir = std::env::temp_dir().join(format!("whisperi-secrets-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(FALLBACK_FILE);
+        let store = FileStore::new(path.clone());
+        store.set("openai", "sk-1").unwrap();
+        store.set("groq", "gsk-1").unwrap();
+        store.delete("openai").unwrap();
+
+        let reopened = FileStore::new(path.clone());
+        assert_eq!(reopened.get("openai").unwrap(), None);
+        assert_eq!(reopened.get("groq").unwrap().as_deref(), Some("gsk-1"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_import_plaintext_moves_api_keys() {
+        let secrets = Secrets::new(MemoryStore::default());
+        let settings = serde_json::json!({
+            "openaiApiKey": "sk-1",
+            "groqApiKey": "",
+            "bogusApiKey": "x",
+            "reasoningModel": "gpt-5-mini",
+        });
+
+        let mut moved = secrets.import_plaintext(settings.as_object().unwrap());
+        moved.sort();
+
+        assert_eq!(moved, ["groqApiKey", "openaiApiKey"]);
+        assert_eq!(secrets.get("openai").as_deref(), Some("sk-1"));
+        assert_eq!(secrets.get("groq"), None);
+    }
+}
@@ -1,12 +1,16 @@
-use anyhow::Result;
+use async_trait::async_trait;
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderValue};
 use reqwest::multipart;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize)]
-struct TranscriptionResponse {
-    text: String,
-}
+use crate::endpoint::{CUSTOM_PROVIDER, CustomEndpoint};
+use crate::models::registry::{ProviderInfo, registry};
+use crate::http;
+use crate::provider::{
+    Capabilities, ListedModel, ProviderConfig, ProviderError, TranscriptionProvider, TranscriptionRequest,
+};
+use super::transcript::{Transcript, VerboseJson};
 
 /// Log transcription result with a note when likely no voice was detected.
 fn log_transcription_result(provider: &str, text: &str) {
@@ -18,77 +22,146 @@ fn log_transcription_result(provider: &str, text: &str) {
     }
 }
 
-/// Transcribe audio via OpenAI Whisper API
-pub async fn transcribe_openai(
-    audio_data: Vec<u8>,
-    api_key: &str,
-    model: &str,
-    language: Option<&str>,
-    prompt: Option<&str>,
-    base_url: Option<&str>,
-) -> Result<String> {
-    let url = format!(
-        "{}/audio/transcriptions",
-        base_url.unwrap_or("https://api.openai.com/v1")
-    );
-
-    let file_part = multipart::Part::bytes(audio_data)
-        .file_name("audio.wav")
-        .mime_str("audio/wav")?;
-
-    let mut form = multipart::Form::new()
-        .text("model", model.to_string())
-        .part("file", file_part);
-
-    if let Some(lang) = language {
-        if lang != "auto" {
-            form = form.text("language", lang.to_string());
-        }
+/// A provider's registry entry and API root.
+fn registered(id: &str) -> Result<(&'static ProviderInfo, &'static str), ProviderError> {
+    let info = registry()
+        .transcription_provider(id)
+        .map_err(|_| ProviderError::UnknownProvider(id.to_string()))?;
+    let base_url = info
+        .base_url
+        .as_deref()
+        .ok_or_else(|| ProviderError::config(&info.name, "no base URL in the model registry"))?;
+    Ok((info, base_url))
+}
+
+/// OpenAI's `/audio/transcriptions` multipart API, also served by Groq and Mistral.
+pub struct OpenAiCompatible {
+    info: &'static ProviderInfo,
+    base_url: &'static str,
+}
+
+impl OpenAiCompatible {
+    pub fn build(id: &str, _: ProviderConfig<'_>) -> Result<Box<dyn TranscriptionProvider>, ProviderError> {
+        let (info, base_url) = registered(id)?;
+        Ok(Box::new(Self { info, base_url }))
     }
+}
 
-    if let Some(p) = prompt {
-        if !p.is_empty() {
-            form = form.text("prompt", p.to_string());
-        }
+#[async_trait]
+impl TranscriptionProvider for OpenAiCompatible {
+    fn id(&self) -> &str {
+        &self.info.id
     }
 
-    log::info!("[Whisperi] POST {}", url);
-    let response = crate::HTTP_CLIENT
-        .post(&url)
-        .bearer_auth(api_key)
-        .multipart(form)
-        .send()
-        .await?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        log::error!("[Whisperi] Transcription API error ({}): {}", status, body);
-        anyhow::bail!("Transcription API error ({}): {}", status, body);
+    fn capabilities(&self) -> Capabilities {
+        self.info.capabilities
+    }
+
+    async fn list_models(&self, api_key: Option<&str>) -> Result<Vec<ListedModel>, ProviderError> {
+        http::list_bearer_models(&self.info.name, self.base_url, api_key.unwrap_or_default()).await
+    }
+
+    async fn transcribe(&self, mut request: TranscriptionRequest<'_>) -> Result<Transcript, ProviderError> {
+        self.info.check_model(request.model);
+        // OpenAI's gpt-4o transcription models only answer in `json` or `text`
+        request.timestamps &= !request.model.starts_with("gpt-4o");
+        let url = format!("{}/audio/transcriptions", self.base_url);
+        let mut headers = HeaderMap::new();
+        let mut auth = HeaderValue::from_str(&format!("Bearer {}", request.api_key.unwrap_or_default()))
+            .map_err(|_| ProviderError::config(&self.info.name, "API key contains invalid characters"))?;
+        auth.set_sensitive(true);
+        headers.insert(AUTHORIZATION, auth);
+        let model = request.model;
+        transcribe_multipart(&self.info.name, &url, headers, model, request).await
     }
+}
 
-    let result: TranscriptionResponse = response.json().await?;
-    log_transcription_result("Cloud", &result.text);
-    Ok(result.text)
+/// A user-configured OpenAI-compatible endpoint.
+pub struct Custom {
+    endpoint: CustomEndpoint,
 }
 
-/// Transcribe audio via Groq Whisper API
-pub async fn transcribe_groq(
-    audio_data: Vec<u8>,
-    api_key: &str,
+impl Custom {
+    pub fn build(_: &str, config: ProviderConfig<'_>) -> Result<Box<dyn TranscriptionProvider>, ProviderError> {
+        let endpoint = config
+            .endpoint
+            .ok_or_else(|| ProviderError::config(CUSTOM_PROVIDER, "no endpoint configured. Set it in Settings."))?;
+        Ok(Box::new(Self {
+            endpoint: endpoint.clone(),
+        }))
+    }
+}
+
+#[async_trait]
+impl TranscriptionProvider for Custom {
+    fn id(&self) -> &str {
+        CUSTOM_PROVIDER
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
+
+    fn requires_api_key(&self) -> bool {
+        false
+    }
+
+    async fn list_models(&self, api_key: Option<&str>) -> Result<Vec<ListedModel>, ProviderError> {
+        self.endpoint.list_models(api_key).await
+    }
+
+    /// The endpoint's own model is used; the requested one is ignored.
+    async fn transcribe(&self, request: TranscriptionRequest<'_>) -> Result<Transcript, ProviderError> {
+        let config_error = |e: anyhow::Error| ProviderError::config(CUSTOM_PROVIDER, format!("{:#}", e));
+        let url = self.endpoint.url("audio/transcriptions").map_err(config_error)?;
+        let headers = self.endpoint.headers(request.api_key).map_err(config_error)?;
+        transcribe_multipart("Custom endpoint", &url, headers, &self.endpoint.model, request).await
+    }
+}
+
+/// Send an `/audio/transcriptions` multipart form with the given auth headers,
+/// asking for `verbose_json` segments when timestamps were requested.
+async fn transcribe_multipart(
+    provider: &str,
+    url: &str,
+    headers: HeaderMap,
     model: &str,
-    language: Option<&str>,
-    prompt: Option<&str>,
-) -> Result<String> {
-    transcribe_openai(
-        audio_data,
-        api_key,
-        model,
-        language,
-        prompt,
-        Some("https://api.groq.com/openai/v1"),
-    )
-    .await
+    request: TranscriptionRequest<'_>,
+) -> Result<Transcript, ProviderError> {
+    let language = request.language.filter(|lang| *lang != "auto");
+    let prompt = request.prompt.filter(|p| !p.is_empty());
+    // The form is a stream, so it is rebuilt for every attempt
+    let form = || {
+        let file_part = multipart::Part::bytes(request.audio.clone())
+            .file_name("audio.wav")
+            .mime_str("audio/wav")
+            .expect("audio/wav is a valid MIME type");
+
+        let mut form = multipart::Form::new()
+            .text("model", model.to_string())
+            .part("file", file_part);
+        if let Some(lang) = language {
+            form = form.text("language", lang.to_string());
+        }
+        if let Some(p) = prompt {
+            form = form.text("prompt", p.to_string());
+        }
+        if request.timestamps {
+            form = form
+                .text("response_format", "verbose_json")
+                .text("timestamp_granularities[]", "segment");
+        }
+        form
+    };
+
+    log::info!("[Whisperi] POST {}", url);
+    let result: VerboseJson = http::send_json(provider, || {
+        crate::HTTP_CLIENT.post(url).headers(headers.clone()).multipart(form())
+    })
+    .await?;
+    let transcript = Transcript::from(result);
+    log_transcription_result(provider, &transcript.text);
+    Ok(transcript)
 }
 
 // --- Qwen ASR types (multimodal chat completions) ---
@@ -118,65 +191,87 @@ struct QwenAsrAudio {
     data: String,
 }
 
-/// Transcribe audio via Qwen ASR (DashScope multimodal chat completions)
-pub async fn transcribe_qwen(
-    audio_data: Vec<u8>,
-    api_key: &str,
-    model: &str,
-) -> Result<String> {
-    let b64 = BASE64.encode(&audio_data);
-    let data_url = format!("data:audio/wav;base64,{}", b64);
-
-    let request = QwenAsrRequest {
-        model: model.to_string(),
-        messages: vec![QwenAsrMessage {
-            role: "user".to_string(),
-            content: vec![QwenAsrContent {
-                content_type: "input_audio".to_string(),
-                input_audio: QwenAsrAudio { data: data_url },
-            }],
-        }],
-        stream: false,
-    };
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
 
-    log::info!("[Whisperi] POST https://dashscope-intl.aliyuncs.com/compatible-mode/v1/chat/completions");
-    let response = crate::HTTP_CLIENT
-        .post("https://dashscope-intl.aliyuncs.com/compatible-mode/v1/chat/completions")
-        .bearer_auth(api_key)
-        .json(&request)
-        .send()
-        .await?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        log::error!("[Whisperi] Qwen ASR API error ({}): {}", status, body);
-        anyhow::bail!("Qwen ASR API error ({}): {}", status, body);
-    }
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatMsg,
+}
+
+#[derive(Deserialize)]
+struct ChatMsg {
+    content: Option<String>,
+}
 
-    #[derive(Deserialize)]
-    struct ChatResponse {
-        choices: Vec<ChatChoice>,
+impl ChatResponse {
+    fn into_text(self) -> String {
+        self.choices
+            .into_iter()
+            .next()
+            .and_then(|c| c.message.content)
+            .unwrap_or_default()
     }
-    #[derive(Deserialize)]
-    struct ChatChoice {
-        message: ChatMsg,
+}
+
+/// Qwen ASR via DashScope multimodal chat completions.
+pub struct Qwen {
+    info: &'static ProviderInfo,
+    base_url: &'static str,
+}
+
+impl Qwen {
+    pub fn build(id: &str, _: ProviderConfig<'_>) -> Result<Box<dyn TranscriptionProvider>, ProviderError> {
+        let (info, base_url) = registered(id)?;
+        Ok(Box::new(Self { info, base_url }))
     }
-    #[derive(Deserialize)]
-    struct ChatMsg {
-        content: Option<String>,
+}
+
+#[async_trait]
+impl TranscriptionProvider for Qwen {
+    fn id(&self) -> &str {
+        &self.info.id
     }
 
-    let result: ChatResponse = response.json().await?;
+    fn capabilities(&self) -> Capabilities {
+        self.info.capabilities
+    }
 
-    let text = result
-        .choices
-        .first()
-        .and_then(|c| c.message.content.clone())
-        .unwrap_or_default();
+    async fn list_models(&self, api_key: Option<&str>) -> Result<Vec<ListedModel>, ProviderError> {
+        http::list_bearer_models(&self.info.name, self.base_url, api_key.unwrap_or_default()).await
+    }
 
-    log_transcription_result("Qwen", &text);
-    Ok(text)
+    async fn transcribe(&self, request: TranscriptionRequest<'_>) -> Result<Transcript, ProviderError> {
+        self.info.check_model(request.model);
+        let b64 = BASE64.encode(&request.audio);
+        let data_url = format!("data:audio/wav;base64,{}", b64);
+
+        let body = QwenAsrRequest {
+            model: request.model.to_string(),
+            messages: vec![QwenAsrMessage {
+                role: "user".to_string(),
+                content: vec![QwenAsrContent {
+                    content_type: "input_audio".to_string(),
+                    input_audio: QwenAsrAudio { data: data_url },
+                }],
+            }],
+            stream: false,
+        };
+
+        let url = format!("{}/chat/completions", self.base_url);
+        log::info!("[Whisperi] POST {}", url);
+        let api_key = request.api_key.unwrap_or_default();
+        let text = http::send_json::<ChatResponse>(&self.info.name, || {
+            crate::HTTP_CLIENT.post(&url).bearer_auth(api_key).json(&body)
+        })
+        .await?
+        .into_text();
+
+        log_transcription_result("Qwen", &text);
+        Ok(Transcript::plain(text))
+    }
 }
 
 // --- OpenRouter multimodal types (chat completions with audio) ---
@@ -194,108 +289,157 @@ struct OpenRouterAsrMessage {
     content: Vec<serde_json::Value>,
 }
 
-/// Transcribe audio via OpenRouter multimodal chat completions
-pub async fn transcribe_openrouter(
-    audio_data: Vec<u8>,
-    api_key: &str,
-    model: &str,
-    language: Option<&str>,
-    prompt: Option<&str>,
-) -> Result<String> {
-    log::info!(
-        "[Whisperi] OpenRouter transcription: model={}, audio={} bytes ({:.1} KB base64)",
-        model,
-        audio_data.len(),
-        audio_data.len() as f64 * 4.0 / 3.0 / 1024.0
-    );
-    let b64 = BASE64.encode(&audio_data);
-
-    let mut instruction =
-        String::from("Transcribe this audio. Output only the transcribed text, nothing else.");
-    if let Some(lang) = language {
-        if lang != "auto" {
-            instruction.push_str(&format!(" Output language: {}.", lang));
-        }
-    }
-    if let Some(p) = prompt {
-        if !p.is_empty() {
-            instruction.push_str(&format!(" Context/vocabulary hints: {}", p));
-        }
-    }
-
-    let content = vec![
-        serde_json::json!({ "type": "text", "text": instruction }),
-        serde_json::json!({
-            "type": "input_audio",
-            "input_audio": { "data": b64, "format": "wav" }
-        }),
-    ];
-
-    let request = OpenRouterAsrRequest {
-        model: model.to_string(),
-        modalities: vec!["text".to_string()],
-        messages: vec![OpenRouterAsrMessage {
-            role: "user".to_string(),
-            content,
-        }],
-    };
+/// OpenRouter multimodal chat completions with an audio part.
+pub struct OpenRouter {
+    info: &'static ProviderInfo,
+    base_url: &'static str,
+}
 
-    log::info!("[Whisperi] POST https://openrouter.ai/api/v1/chat/completions (transcription)");
-    let response = crate::HTTP_CLIENT
-        .post("https://openrouter.ai/api/v1/chat/completions")
-        .bearer_auth(api_key)
-        .header("HTTP-Referer", "https://github.com/xarthurx/whisperi")
-        .header("X-Title", "Whisperi")
-        .json(&request)
-        .send()
-        .await?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        log::error!("[Whisperi] OpenRouter transcription API error ({}): {}", status, body);
-        anyhow::bail!("OpenRouter transcription API error ({}): {}", status, body);
+impl OpenRouter {
+    pub fn build(id: &str, _: ProviderConfig<'_>) -> Result<Box<dyn TranscriptionProvider>, ProviderError> {
+        let (info, base_url) = registered(id)?;
+        Ok(Box::new(Self { info, base_url }))
     }
+}
 
-    #[derive(Deserialize)]
-    struct ChatResponse {
-        choices: Vec<ChatChoice>,
+#[async_trait]
+impl TranscriptionProvider for OpenRouter {
+    fn id(&self) -> &str {
+        &self.info.id
     }
-    #[derive(Deserialize)]
-    struct ChatChoice {
-        message: ChatMsg,
+
+    fn capabilities(&self) -> Capabilities {
+        self.info.capabilities
     }
-    #[derive(Deserialize)]
-    struct ChatMsg {
-        content: Option<String>,
+
+    async fn list_models(&self, api_key: Option<&str>) -> Result<Vec<ListedModel>, ProviderError> {
+        http::list_bearer_models(&self.info.name, self.base_url, api_key.unwrap_or_default()).await
     }
 
-    let result: ChatResponse = response.json().await?;
-    let text = result
-        .choices
-        .first()
-        .and_then(|c| c.message.content.clone())
-        .unwrap_or_default();
+    async fn transcribe(&self, request: TranscriptionRequest<'_>) -> Result<Transcript, ProviderError> {
+        let model = request.model;
+        self.info.check_model(model);
+        log::info!(
+            "[Whisperi] OpenRouter transcription: model={}, audio={} bytes ({:.1} KB base64)",
+            model,
+            request.audio.len(),
+            request.audio.len() as f64 * 4.0 / 3.0 / 1024.0
+        );
+        let b64 = BASE64.encode(&request.audio);
+
+        let mut instruction =
+            String::from("Transcribe this audio. Output only the transcribed text, nothing else.");
+        if let Some(lang) = request.language
+            && lang != "auto"
+        {
+            instruction.push_str(&format!(" Output language: {}.", lang));
+        }
+        if let Some(p) = request.prompt
+            && !p.is_empty()
+        {
+            instruction.push_str(&format!(" Context/vocabulary hints: {}", p));
+        }
 
-    log_transcription_result("OpenRouter", &text);
-    Ok(text)
+        let content = vec![
+            serde_json::json!({ "type": "text", "text": instruction }),
+            serde_json::json!({
+                "type": "input_audio",
+                "input_audio": { "data": b64, "format": "wav" }
+            }),
+        ];
+
+        let body = OpenRouterAsrRequest {
+            model: model.to_string(),
+            modalities: vec!["text".to_string()],
+            messages: vec![OpenRouterAsrMessage {
+                role: "user".to_string(),
+                content,
+            }],
+        };
+
+        let url = format!("{}/chat/completions", self.base_url);
+        log::info!("[Whisperi] POST {} (transcription)", url);
+        let api_key = request.api_key.unwrap_or_default();
+        let text = http::send_json::<ChatResponse>(&self.info.name, || {
+            crate::HTTP_CLIENT
+                .post(&url)
+                .bearer_auth(api_key)
+                .header("HTTP-Referer", "https://github.com/xarthurx/whisperi")
+                .header("X-Title", "Whisperi")
+                .json(&body)
+        })
+        .await?
+        .into_text();
+
+        log_transcription_result("OpenRouter", &text);
+        Ok(Transcript::plain(text))
+    }
 }
 
-/// Transcribe audio via Mistral Voxtral API
-pub async fn transcribe_mistral(
-    audio_data: Vec<u8>,
-    api_key: &str,
-    model: &str,
-    language: Option<&str>,
-    prompt: Option<&str>,
-) -> Result<String> {
-    transcribe_openai(
-        audio_data,
-        api_key,
-        model,
-        language,
-        prompt,
-        Some("https://api.mistral.ai/v1"),
-    )
-    .await
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endpoint::AuthScheme;
+    use wiremock::matchers::{body_string_contains, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_custom_provider_uses_endpoint_auth_and_model() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/audio/transcriptions"))
+            .and(header("api-key", "team-key"))
+            .and(header("x-tenant", "speech"))
+            .and(body_string_contains("Systran/faster-whisper-large-v3"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "text": "hello" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let endpoint = CustomEndpoint {
+            base_url: format!("{}/v1", server.uri()),
+            auth_scheme: AuthScheme::Header,
+            auth_header: "api-key".to_string(),
+            extra_headers: [("X-Tenant".to_string(), "speech".to_string())].into(),
+            model: "Systran/faster-whisper-large-v3".to_string(),
+        };
+        let config = ProviderConfig {
+            endpoint: Some(&endpoint),
+            ..Default::default()
+        };
+        let request = TranscriptionRequest {
+            audio: b"RIFF".to_vec(),
+            api_key: Some("team-key"),
+            model: "ignored",
+            language: Some("en"),
+            prompt: None,
+            timestamps: true,
+        };
+        let transcript = super::super::transcribe_cloud(CUSTOM_PROVIDER, config, request).await.unwrap();
+        assert_eq!(transcript, Transcript::plain("hello"));
+        // Custom endpoints don't claim timestamp support, so no verbose_json was asked for
+        let requests = server.received_requests().await.unwrap();
+        assert!(!String::from_utf8_lossy(&requests[0].body).contains("verbose_json"));
+    }
+
+    #[tokio::test]
+    async fn test_registry_covers_providers_and_checks_keys() {
+        let ids: Vec<_> = super::super::providers().ids().collect();
+        for info in &registry().transcription_providers {
+            assert!(ids.contains(&info.id.as_str()), "no client for {}", info.id);
+        }
+
+        let request = TranscriptionRequest {
+            audio: Vec::new(),
+            api_key: None,
+            model: "whisper-large-v3",
+            language: None,
+            prompt: None,
+            timestamps: false,
+        };
+        let err = super::super::transcribe_cloud("groq", ProviderConfig::default(), request)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ProviderError::MissingApiKey(_)));
+    }
 }
@@ -1,2 +1,59 @@
 pub mod cloud;
+#[cfg(feature = "native-whisper")]
+pub mod native;
+pub mod parakeet;
+pub mod server;
+pub mod transcript;
 pub mod whisper;
+
+use std::sync::LazyLock;
+
+use crate::endpoint::CUSTOM_PROVIDER;
+use crate::provider::{
+    ProviderConfig, ProviderError, ProviderSummary, Registry, TranscriptionProvider, TranscriptionRequest, require_key,
+};
+use transcript::Transcript;
+
+static PROVIDERS: LazyLock<Registry<dyn TranscriptionProvider>> = LazyLock::new(|| {
+    Registry::default()
+        .register("openai", cloud::OpenAiCompatible::build)
+        .register("groq", cloud::OpenAiCompatible::build)
+        .register("mistral", cloud::OpenAiCompatible::build)
+        .register("qwen", cloud::Qwen::build)
+        .register("openrouter", cloud::OpenRouter::build)
+        .register(CUSTOM_PROVIDER, cloud::Custom::build)
+});
+
+/// Cloud transcription providers by id.
+pub fn providers() -> &'static Registry<dyn TranscriptionProvider> {
+    &PROVIDERS
+}
+
+/// Every registered provider that can be built with `config`.
+pub fn summaries(config: ProviderConfig<'_>) -> Vec<ProviderSummary> {
+    providers()
+        .ids()
+        .filter_map(|id| providers().get(id, config).ok())
+        .map(|provider| ProviderSummary {
+            id: provider.id().to_string(),
+            capabilities: provider.capabilities(),
+            requires_api_key: provider.requires_api_key(),
+        })
+        .collect()
+}
+
+/// Transcribe with the cloud provider `id`. Hints the provider doesn't
+/// accept are dropped, and a missing key fails before any request is sent.
+pub async fn transcribe_cloud(
+    id: &str,
+    config: ProviderConfig<'_>,
+    mut request: TranscriptionRequest<'_>,
+) -> Result<Transcript, ProviderError> {
+    let provider = providers().get(id, config)?;
+    let capabilities = provider.capabilities();
+    request.api_key = require_key(id, provider.requires_api_key(), request.api_key)?;
+    request.language = request.language.filter(|_| capabilities.language);
+    request.prompt = request.prompt.filter(|_| capabilities.prompt);
+    request.timestamps &= capabilities.timestamps;
+    provider.transcribe(request).await
+}
@@ -0,0 +1,123 @@
+//! In-process whisper.cpp through `whisper-rs`, enabled with the
+//! `native-whisper` cargo feature. The model context is cached in managed
+//! state, so only the first dictation (or a model switch) pays the load time.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+
+use super::transcript::{self, Segment, Transcript};
+
+/// Managed state holding the currently loaded whisper model.
+#[derive(Default)]
+pub struct NativeWhisper {
+    loaded: Mutex<Option<LoadedModel>>,
+}
+
+struct LoadedModel {
+    path: PathBuf,
+    context: Arc<WhisperContext>,
+}
+
+impl NativeWhisper {
+    /// Return the context for `model_path`, loading it (and dropping the
+    /// previous model) if it isn't the one currently cached.
+    fn context(&self, model_path: &Path) -> Result<Arc<WhisperContext>> {
+        let mut loaded = self.loaded.lock().unwrap();
+        if let Some(model) = loaded.as_ref()
+            && model.path == model_path
+        {
+            return Ok(Arc::clone(&model.context));
+        }
+
+        // Free the old model before loading the new one
+        *loaded = None;
+        log::info!("[Whisperi] Loading whisper model: {}", model_path.display());
+        let context = WhisperContext::new_with_params(model_path, WhisperContextParameters::default())
+            .with_context(|| format!("Failed to load whisper model {}", model_path.display()))?;
+        let context = Arc::new(context);
+        *loaded = Some(LoadedModel {
+            path: model_path.to_path_buf(),
+            context: Arc::clone(&context),
+        });
+        Ok(context)
+    }
+
+    /// Transcribe 16 kHz mono samples, with timed segments when `timestamps`
+    /// is set. Blocking; call from a blocking task.
+    pub fn transcribe(
+        &self,
+        model_path: &Path,
+        samples: &[f32],
+        language: Option<&str>,
+        dictionary: &[String],
+        timestamps: bool,
+        threads: usize,
+    ) -> Result<Transcript> {
+        let context = self.context(model_path)?;
+        let mut state = context
+            .create_state()
+            .context("Failed to create whisper state")?;
+
+        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+        params.set_n_threads(threads as i32);
+        params.set_no_timestamps(!timestamps);
+        params.set_print_special(false);
+        params.set_print_progress(false);
+        params.set_print_realtime(false);
+        params.set_print_timestamps(false);
+        // Same semantics as the sidecar's `-l`: omitted means auto-detect
+        params.set_language(Some(language.unwrap_or("auto")));
+
+        let prompt = dictionary.join(" ").replace('\0', "");
+        if !prompt.is_empty() {
+            params.set_initial_prompt(&prompt);
+        }
+
+        state
+            .full(params, samples)
+            .context("whisper.cpp inference failed")?;
+
+        if !timestamps {
+            let mut text = String::new();
+            for segment in state.as_iter() {
+                text.push_str(&segment.to_str_lossy()?);
+            }
+            return Ok(Transcript::plain(text.trim()));
+        }
+
+        // Ids from end-of-text on are special tokens (timestamps, language, ...)
+        let eot = context.token_eot();
+        let mut segments = Vec::new();
+        for segment in state.as_iter() {
+            let probabilities: Vec<f32> = (0..segment.n_tokens())
+                .filter_map(|i| segment.get_token(i))
+                .filter(|token| token.token_id() < eot)
+                .map(|token| token.token_probability())
+                .collect();
+            segments.push(Segment {
+                // Centiseconds
+                start: segment.start_timestamp() as f64 / 100.0,
+                end: segment.end_timestamp() as f64 / 100.0,
+                text: segment.to_str_lossy()?.trim().to_string(),
+                confidence: transcript::mean(&probabilities),
+                avg_logprob: None,
+            });
+        }
+        Ok(Transcript {
+            text: transcript::join(&segments),
+            language: whisper_rs::get_lang_str(state.full_lang_id_from_state()).map(str::to_string),
+            segments,
+        })
+    }
+}
+
+/// Decode a 16-bit PCM mono WAV (as produced by `audio::encode_wav`) to f32 samples.
+pub fn decode_wav(audio_data: &[u8]) -> Result<Vec<f32>> {
+    let reader = hound::WavReader::new(std::io::Cursor::new(audio_data)).context("Invalid WAV data")?;
+    reader
+        .into_samples::<i16>()
+        .map(|s| Ok(s? as f32 / i16::MAX as f32))
+        .collect()
+}
@@ -0,0 +1,203 @@
+//! Parakeet (NeMo transducer) local transcription through sherpa-onnx.
+//!
+//! Models are the sherpa-onnx `.tar.bz2` releases listed under
+//! `parakeetModels` in the shared model registry. Inference runs on CPU via
+//! the `sherpa-onnx-offline` sidecar, mirroring how whisper.cpp is invoked.
+
+use anyhow::{Context, Result};
+use crate::models::registry::{ParakeetModel, registry};
+use indexmap::IndexMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tauri::AppHandle;
+use tauri_plugin_shell::ShellExt;
+use tokio_util::sync::CancellationToken;
+
+/// Files every extracted Parakeet model directory must contain.
+const MODEL_FILES: [&str; 4] = [
+    "encoder.int8.onnx",
+    "decoder.int8.onnx",
+    "joiner.int8.onnx",
+    "tokens.txt",
+];
+
+/// All Parakeet models from the model registry, keyed by id.
+pub fn models() -> &'static IndexMap<String, ParakeetModel> {
+    &registry().parakeet_models
+}
+
+pub fn model(model_id: &str) -> Result<&'static ParakeetModel> {
+    registry().parakeet_model(model_id)
+}
+
+/// Get the directory where Parakeet models are extracted
+pub fn models_dir() -> Result<PathBuf> {
+    let cache_dir = dirs::cache_dir().context("Failed to find cache directory")?;
+    let models_path = cache_dir.join("whisperi").join("parakeet-models");
+    std::fs::create_dir_all(&models_path)?;
+    Ok(models_path)
+}
+
+fn model_path(model: &ParakeetModel) -> Result<PathBuf> {
+    Ok(models_dir()?.join(&model.extract_dir))
+}
+
+/// Whether every file the model needs has been extracted.
+pub fn is_downloaded(model_id: &str) -> Result<bool> {
+    let dir = model_path(model(model_id)?)?;
+    Ok(MODEL_FILES.iter().all(|f| dir.join(f).exists()))
+}
+
+/// Download the model archive and extract it into [`models_dir`].
+pub async fn download_model(
+    model_id: &str,
+    cancel: &CancellationToken,
+    on_progress: impl Fn(u64, u64),
+) -> Result<()> {
+    let model = model(model_id)?;
+    let dir = models_dir()?;
+    let archive = dir.join(format!("{}.tar.bz2", model.extract_dir));
+
+    crate::models::download_file(
+        &model.download_url,
+        &archive,
+        model.sha256.as_deref(),
+        cancel,
+        on_progress,
+    )
+    .await?;
+    let extracted = crate::models::extract_tar_bz2(&archive, &dir).await;
+    let _ = tokio::fs::remove_file(&archive).await;
+    extracted?;
+
+    if !is_downloaded(model_id)? {
+        anyhow::bail!("Archive for {} is missing model files", model_id);
+    }
+    Ok(())
+}
+
+/// Delete an extracted Parakeet model and any partial archive download
+pub fn delete_model(model_id: &str) -> Result<()> {
+    let model = model(model_id)?;
+    let path = model_path(model)?;
+    if path.exists() {
+        std::fs::remove_dir_all(&path)?;
+    }
+    let partial = models_dir()?.join(format!("{}.tar.part", model.extract_dir));
+    if partial.exists() {
+        std::fs::remove_file(&partial)?;
+    }
+    Ok(())
+}
+
+/// Transcribe a 16 kHz WAV clip with sherpa-onnx. Parakeet detects the
+/// language itself, so `language` is only checked against the model's list.
+pub async fn transcribe(
+    app: &AppHandle,
+    audio_data: &[u8],
+    model_id: &str,
+    language: Option<&str>,
+) -> Result<String> {
+    let model = model(model_id)?;
+    if !is_downloaded(model_id)? {
+        anyhow::bail!("Parakeet model not downloaded: {}", model_id);
+    }
+    if let Some(lang) = language
+        && lang != "auto"
+        && !model.supported_languages.iter().any(|l| l == lang)
+    {
+        log::warn!("[Whisperi] {} does not list language '{}', auto-detecting", model.name, lang);
+    }
+
+    let dir = model_path(model)?;
+    static NEXT_TEMP_ID: AtomicU64 = AtomicU64::new(0);
+    let temp_audio = std::env::temp_dir().join(format!(
+        "whisperi-parakeet-{}-{}.wav",
+        std::process::id(),
+        NEXT_TEMP_ID.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::write(&temp_audio, audio_data)?;
+
+    let output = app
+        .shell()
+        .sidecar("sherpa-onnx-offline")
+        .map_err(|e| anyhow::anyhow!("Failed to create sidecar: {}", e))?
+        .args(sherpa_args(&dir, &temp_audio))
+        .output()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to run sherpa-onnx-offline: {}", e));
+
+    let _ = std::fs::remove_file(&temp_audio);
+    let output = output?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        anyhow::bail!("sherpa-onnx-offline failed: {}", stderr);
+    }
+
+    // The result JSON is printed to stderr by current releases, stdout by older ones
+    let text = parse_result(&stderr)
+        .or_else(|| parse_result(&stdout))
+        .context("No transcription result in sherpa-onnx output")?;
+
+    if text.is_empty() {
+        log::warn!("[Whisperi] Parakeet transcription result: empty (no voice detected)");
+    } else {
+        log::info!("[Whisperi] Parakeet transcription result: {} chars", text.len());
+    }
+    Ok(text)
+}
+
+fn sherpa_args(model_dir: &Path, audio: &Path) -> Vec<String> {
+    let file = |name: &str| model_dir.join(name).to_string_lossy().to_string();
+    vec![
+        format!("--encoder={}", file("encoder.int8.onnx")),
+        format!("--decoder={}", file("decoder.int8.onnx")),
+        format!("--joiner={}", file("joiner.int8.onnx")),
+        format!("--tokens={}", file("tokens.txt")),
+        "--model-type=nemo_transducer".to_string(),
+        "--provider=cpu".to_string(),
+        format!("--num-threads={}", super::whisper::num_cpus()),
+        audio.to_string_lossy().to_string(),
+    ]
+}
+
+/// Pull `text` out of the per-file result JSON sherpa-onnx prints after its
+/// config dump.
+fn parse_result(output: &str) -> Option<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with('{'))
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .find_map(|json| json.get("text")?.as_str().map(|t| t.trim().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_lists_parakeet_models() {
+        let model = model("parakeet-tdt-0.6b-v3").unwrap();
+        assert!(model.download_url.ends_with(".tar.bz2"));
+        assert!(model.supported_languages.iter().any(|l| l == "de"));
+    }
+
+    #[test]
+    fn test_parse_result_from_sherpa_output() {
+        let output = r#"OfflineRecognizerConfig(feat_config=FeatureExtractorConfig(sampling_rate=16000), ...)
+Creating recognizer ...
+Started
+Done!
+
+/tmp/whisperi-parakeet-1-0.wav
+{"lang": "", "emotion": "", "event": "", "text": " Hello world.", "timestamps": [0.24, 0.56], "tokens":[" Hello", " world", "."], "words": []}
+----
+num threads: 6
+"#;
+        assert_eq!(parse_result(output).as_deref(), Some("Hello world."));
+        assert_eq!(parse_result("Creating recognizer ...\n"), None);
+    }
+}
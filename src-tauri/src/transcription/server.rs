@@ -0,0 +1,338 @@
+//! Warm local transcription through whisper.cpp's HTTP server mode.
+//!
+//! The `whisper-server` sidecar is started once (at launch or on first use),
+//! keeps the model loaded, and receives each recording over localhost. A crashed
+//! or unhealthy server is restarted on the next request.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::AppHandle;
+use tauri_plugin_shell::ShellExt;
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
+use tokio::sync::Mutex;
+
+use super::transcript::{Transcript, VerboseJson};
+
+/// How long to wait for a freshly spawned server to load its model.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+const HEALTH_POLL: Duration = Duration::from_millis(250);
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Managed state owning the running `whisper-server` sidecar, if any.
+#[derive(Default)]
+pub struct WhisperServer {
+    running: Mutex<Option<RunningServer>>,
+}
+
+struct RunningServer {
+    child: CommandChild,
+    model_path: PathBuf,
+    base_url: String,
+    /// Cleared by the event task when the process exits.
+    alive: Arc<AtomicBool>,
+}
+
+impl WhisperServer {
+    /// Transcribe a WAV clip, starting the server for `model_path` if needed.
+    /// If the server died mid-request it is restarted and the clip retried once.
+    pub async fn transcribe(
+        &self,
+        app: &AppHandle,
+        audio_data: &[u8],
+        model_path: &Path,
+        language: Option<&str>,
+        dictionary: &[String],
+        timestamps: bool,
+    ) -> Result<Transcript> {
+        let base_url = self.ensure(app, model_path).await?;
+        match infer(&base_url, audio_data, language, dictionary, timestamps).await {
+            Err(e) if is_connection_error(&e) => {
+                log::warn!("[Whisperi] whisper-server unreachable ({}), restarting", e);
+                self.shutdown().await;
+                let base_url = self.ensure(app, model_path).await?;
+                infer(&base_url, audio_data, language, dictionary, timestamps).await
+            }
+            result => result,
+        }
+    }
+
+    /// Make sure a healthy server for `model_path` is running and return its base URL.
+    pub async fn ensure(&self, app: &AppHandle, model_path: &Path) -> Result<String> {
+        let mut running = self.running.lock().await;
+
+        if let Some(server) = running.as_ref()
+            && server.model_path == model_path
+            && server.alive.load(Ordering::SeqCst)
+            && health(&server.base_url).await
+        {
+            return Ok(server.base_url.clone());
+        }
+
+        if let Some(server) = running.take() {
+            log::info!("[Whisperi] Restarting whisper-server");
+            let _ = server.child.kill();
+        }
+
+        let server = spawn(app, model_path).await?;
+        let base_url = server.base_url.clone();
+        *running = Some(server);
+        Ok(base_url)
+    }
+
+    /// Stop the server (model switch, app exit).
+    pub async fn shutdown(&self) {
+        if let Some(server) = self.running.lock().await.take() {
+            let _ = server.child.kill();
+        }
+    }
+
+    /// Non-blocking variant of [`shutdown`](Self::shutdown) for the exit handler.
+    pub fn kill(&self) {
+        if let Ok(mut running) = self.running.try_lock()
+            && let Some(server) = running.take()
+        {
+            let _ = server.child.kill();
+        }
+    }
+}
+
+async fn spawn(app: &AppHandle, model_path: &Path) -> Result<RunningServer> {
+    let port = free_port()?;
+    let base_url = format!("http://127.0.0.1:{}", port);
+    log::info!(
+        "[Whisperi] Starting whisper-server on port {} with {}",
+        port,
+        model_path.display()
+    );
+
+    let (mut events, child) = app
+        .shell()
+        .sidecar("whisper-server")
+        .map_err(|e| anyhow::anyhow!("Failed to create sidecar: {}", e))?
+        .args([
+            "-m".to_string(),
+            model_path.to_string_lossy().to_string(),
+            "--host".to_string(),
+            "127.0.0.1".to_string(),
+            "--port".to_string(),
+            port.to_string(),
+            "-t".to_string(),
+            super::whisper::num_cpus().to_string(),
+            "--no-timestamps".to_string(),
+        ])
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Failed to start whisper-server: {}", e))?;
+
+    let alive = Arc::new(AtomicBool::new(true));
+    let alive_events = Arc::clone(&alive);
+    tauri::async_runtime::spawn(async move {
+        while let Some(event) = events.recv().await {
+            match event {
+                CommandEvent::Stderr(line) => {
+                    log::debug!("[whisper-server] {}", String::from_utf8_lossy(&line).trim_end());
+                }
+                CommandEvent::Terminated(payload) => {
+                    log::warn!("[Whisperi] whisper-server exited (code {:?})", payload.code);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        alive_events.store(false, Ordering::SeqCst);
+    });
+
+    if let Err(e) = wait_healthy(&base_url, STARTUP_TIMEOUT, &alive).await {
+        let _ = child.kill();
+        return Err(e);
+    }
+    log::info!("[Whisperi] whisper-server ready");
+
+    Ok(RunningServer {
+        child,
+        model_path: model_path.to_path_buf(),
+        base_url,
+        alive,
+    })
+}
+
+/// Whether the server answers `GET /health` with a success status.
+pub async fn health(base_url: &str) -> bool {
+    crate::HTTP_CLIENT
+        .get(format!("{}/health", base_url))
+        .timeout(HEALTH_TIMEOUT)
+        .send()
+        .await
+        .is_ok_and(|r| r.status().is_success())
+}
+
+/// Poll `/health` until the server reports ready, the process exits, or `timeout` elapses.
+pub async fn wait_healthy(base_url: &str, timeout: Duration, alive: &AtomicBool) -> Result<()> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        if !alive.load(Ordering::SeqCst) {
+            anyhow::bail!("whisper-server exited during startup");
+        }
+        if health(base_url).await {
+            return Ok(());
+        }
+        if tokio::time::Instant::now() >= deadline {
+            anyhow::bail!("whisper-server did not become ready within {}s", timeout.as_secs());
+        }
+        tokio::time::sleep(HEALTH_POLL).await;
+    }
+}
+
+/// Send one WAV clip to a running server's `/inference` endpoint. With
+/// `timestamps`, the server answers in `verbose_json` with segments.
+pub async fn infer(
+    base_url: &str,
+    audio_data: &[u8],
+    language: Option<&str>,
+    dictionary: &[String],
+    timestamps: bool,
+) -> Result<Transcript> {
+    let file_part = reqwest::multipart::Part::bytes(audio_data.to_vec())
+        .file_name("audio.wav")
+        .mime_str("audio/wav")?;
+
+    let mut form = reqwest::multipart::Form::new()
+        .part("file", file_part)
+        .text("response_format", if timestamps { "verbose_json" } else { "json" })
+        // The server starts with `--no-timestamps`; segments need them back on
+        .text("no_timestamps", if timestamps { "false" } else { "true" })
+        .text("temperature", "0.0")
+        .text("language", language.unwrap_or("auto").to_string());
+    if !dictionary.is_empty() {
+        form = form.text("prompt", dictionary.join(" "));
+    }
+
+    let response = crate::HTTP_CLIENT
+        .post(format!("{}/inference", base_url))
+        .multipart(form)
+        .send()
+        .await
+        .context("whisper-server request failed")?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        anyhow::bail!("whisper-server error ({}): {}", status, body);
+    }
+
+    let result: VerboseJson = response
+        .json()
+        .await
+        .context("Invalid whisper-server response")?;
+    Ok(result.into())
+}
+
+fn is_connection_error(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| e.is_connect() || e.is_request())
+}
+
+/// Ask the OS for an unused localhost port.
+fn free_port() -> Result<u16> {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").context("No free local port")?;
+    Ok(listener.local_addr()?.port())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_infer_sends_clip_and_parses_text() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/inference"))
+            .and(body_string_contains("name=\"language\"\r\n\r\nde"))
+            .and(body_string_contains("Whisperi Tauri"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({ "text": " Hallo Welt\n" })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dictionary = vec!["Whisperi".to_string(), "Tauri".to_string()];
+        let transcript = infer(&server.uri(), b"RIFF", Some("de"), &dictionary, false).await.unwrap();
+        assert_eq!(transcript, Transcript::plain("Hallo Welt"));
+    }
+
+    #[tokio::test]
+    async fn test_infer_asks_for_segments_with_timestamps() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/inference"))
+            .and(body_string_contains("name=\"response_format\"\r\n\r\nverbose_json"))
+            .and(body_string_contains("name=\"no_timestamps\"\r\n\r\nfalse"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "language": "de",
+                "text": " Hallo Welt",
+                "segments": [{ "start": 0.0, "end": 0.9, "text": " Hallo Welt", "avg_logprob": -0.2 }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let transcript = infer(&server.uri(), b"RIFF", None, &[], true).await.unwrap();
+        assert_eq!(transcript.language.as_deref(), Some("de"));
+        assert_eq!(transcript.segments.len(), 1);
+        assert_eq!(transcript.segments[0].end, 0.9);
+    }
+
+    #[tokio::test]
+    async fn test_infer_reports_server_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/inference"))
+            .respond_with(ResponseTemplate::new(500).set_body_string("model not loaded"))
+            .mount(&server)
+            .await;
+
+        let err = infer(&server.uri(), b"RIFF", None, &[], false).await.unwrap_err();
+        assert!(err.to_string().contains("model not loaded"));
+        assert!(!is_connection_error(&err));
+    }
+
+    #[tokio::test]
+    async fn test_infer_against_stopped_server_is_a_connection_error() {
+        let base_url = format!("http://127.0.0.1:{}", free_port().unwrap());
+        let err = infer(&base_url, b"RIFF", None, &[], false).await.unwrap_err();
+        assert!(is_connection_error(&err));
+    }
+
+    #[tokio::test]
+    async fn test_wait_healthy_polls_until_model_is_loaded() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/health"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/health"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "status": "ok" })))
+            .mount(&server)
+            .await;
+
+        let alive = AtomicBool::new(true);
+        wait_healthy(&server.uri(), Duration::from_secs(5), &alive).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_wait_healthy_gives_up_when_process_exits() {
+        let server = MockServer::start().await;
+        let alive = AtomicBool::new(false);
+        let err = wait_healthy(&server.uri(), Duration::from_secs(5), &alive).await.unwrap_err();
+        assert!(err.to_string().contains("exited"));
+    }
+}
@@ -0,0 +1,227 @@
+//! Rich transcription results. Every engine returns a [`Transcript`]; when
+//! timestamps are requested, those that can also fill in timed segments with
+//! a confidence score and the detected language.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Transcript {
+    pub text: String,
+    /// Language the engine detected, in its own notation ("en", "english").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Empty unless timestamps were requested and the engine returns them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Segment {
+    /// Offset into the recording, in seconds.
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+    /// Likelihood that the text is right, from 0 to 1: the mean token
+    /// probability, or `exp(avg_logprob)` when only that is reported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    /// Mean token log probability, as reported by the provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avg_logprob: Option<f32>,
+}
+
+impl Transcript {
+    /// Text without segments, from engines that don't report any.
+    pub fn plain(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Segment texts joined into the full transcript.
+pub fn join(segments: &[Segment]) -> String {
+    segments
+        .iter()
+        .map(|s| s.text.as_str())
+        .filter(|t| !t.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn mean(values: &[f32]) -> Option<f32> {
+    (!values.is_empty()).then(|| values.iter().sum::<f32>() / values.len() as f32)
+}
+
+/// OpenAI's `verbose_json` transcription response, also returned by Groq and
+/// whisper-server. A plain `json` response parses too, without segments.
+#[derive(Debug, Deserialize)]
+pub struct VerboseJson {
+    text: String,
+    #[serde(default)]
+    language: Option<String>,
+    #[serde(default)]
+    segments: Vec<VerboseSegment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VerboseSegment {
+    start: f64,
+    end: f64,
+    text: String,
+    #[serde(default)]
+    avg_logprob: Option<f32>,
+}
+
+impl From<VerboseJson> for Transcript {
+    fn from(response: VerboseJson) -> Self {
+        Self {
+            text: response.text.trim().to_string(),
+            language: response.language.filter(|l| !l.is_empty()),
+            segments: response
+                .segments
+                .into_iter()
+                .map(|s| Segment {
+                    start: s.start,
+                    end: s.end,
+                    text: s.text.trim().to_string(),
+                    confidence: s.avg_logprob.map(|l| l.exp().clamp(0.0, 1.0)),
+                    avg_logprob: s.avg_logprob,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// whisper.cpp's JSON output, read from the sidecar's output file.
+#[cfg(not(feature = "native-whisper"))]
+mod whisper_json {
+    use super::*;
+    use anyhow::{Context, Result};
+
+    impl Transcript {
+        /// Parse whisper.cpp's `--output-json-full` file. Confidence is the mean
+        /// probability of a segment's text tokens.
+        pub fn from_whisper_json(json: &str) -> Result<Self> {
+            let output: WhisperOutput = serde_json::from_str(json).context("Invalid whisper.cpp JSON output")?;
+            let segments: Vec<Segment> = output
+                .transcription
+                .into_iter()
+                .map(|segment| {
+                    // Special tokens ([_BEG_], [_TT_150], ...) say nothing about the words
+                    let probabilities: Vec<f32> = segment
+                        .tokens
+                        .iter()
+                        .filter(|t| !t.text.starts_with("[_"))
+                        .map(|t| t.p)
+                        .collect();
+                    Segment {
+                        start: segment.offsets.from as f64 / 1000.0,
+                        end: segment.offsets.to as f64 / 1000.0,
+                        text: segment.text.trim().to_string(),
+                        confidence: mean(&probabilities),
+                        avg_logprob: None,
+                    }
+                })
+                .collect();
+            Ok(Self {
+                text: join(&segments),
+                language: output.result.and_then(|r| r.language),
+                segments,
+            })
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct WhisperOutput {
+        #[serde(default)]
+        result: Option<WhisperResult>,
+        #[serde(default)]
+        transcription: Vec<WhisperSegment>,
+    }
+
+    #[derive(Deserialize)]
+    struct WhisperResult {
+        language: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct WhisperSegment {
+        /// Milliseconds.
+        offsets: WhisperOffsets,
+        text: String,
+        #[serde(default)]
+        tokens: Vec<WhisperToken>,
+    }
+
+    #[derive(Deserialize)]
+    struct WhisperOffsets {
+        from: u64,
+        to: u64,
+    }
+
+    #[derive(Deserialize)]
+    struct WhisperToken {
+        text: String,
+        p: f32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_verbose_json_and_plain_json() {
+        let verbose: VerboseJson = serde_json::from_value(serde_json::json!({
+            "task": "transcribe",
+            "language": "english",
+            "duration": 3.2,
+            "text": " Hello there. General Kenobi. ",
+            "segments": [
+                { "id": 0, "start": 0.0, "end": 1.4, "text": " Hello there.", "avg_logprob": -0.1, "no_speech_prob": 0.01 },
+                { "id": 1, "start": 1.4, "end": 3.2, "text": " General Kenobi.", "avg_logprob": -1.2 }
+            ]
+        }))
+        .unwrap();
+        let transcript = Transcript::from(verbose);
+        assert_eq!(transcript.text, "Hello there. General Kenobi.");
+        assert_eq!(transcript.language.as_deref(), Some("english"));
+        assert_eq!(transcript.segments[1].text, "General Kenobi.");
+        assert_eq!(transcript.segments[1].start, 1.4);
+        let confidence = transcript.segments[0].confidence.unwrap();
+        assert!((confidence - (-0.1f32).exp()).abs() < 1e-6);
+        assert!(transcript.segments[1].confidence.unwrap() < 0.5);
+
+        let plain: VerboseJson = serde_json::from_str(r#"{ "text": "hi" }"#).unwrap();
+        assert_eq!(Transcript::from(plain), Transcript::plain("hi"));
+    }
+
+    #[cfg(not(feature = "native-whisper"))]
+    #[test]
+    fn test_parses_whisper_cpp_full_json() {
+        let json = r#"{
+            "result": { "language": "en" },
+            "transcription": [
+                {
+                    "timestamps": { "from": "00:00:00,000", "to": "00:00:01,500" },
+                    "offsets": { "from": 0, "to": 1500 },
+                    "text": " Hello world",
+                    "tokens": [
+                        { "text": "[_BEG_]", "p": 0.1, "id": 50364 },
+                        { "text": " Hello", "p": 0.9, "id": 2425 },
+                        { "text": " world", "p": 0.7, "id": 1002 }
+                    ]
+                },
+                { "offsets": { "from": 1500, "to": 2000 }, "text": " again", "tokens": [] }
+            ]
+        }"#;
+        let transcript = Transcript::from_whisper_json(json).unwrap();
+        assert_eq!(transcript.text, "Hello world again");
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+        assert_eq!(transcript.segments[0].end, 1.5);
+        assert!((transcript.segments[0].confidence.unwrap() - 0.8).abs() < 1e-6);
+        assert_eq!(transcript.segments[1].confidence, None);
+    }
+}
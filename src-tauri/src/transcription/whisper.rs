@@ -1,7 +1,8 @@
 use anyhow::{Context, Result};
-use std::path::PathBuf;
-use tauri::AppHandle;
-use tauri_plugin_shell::ShellExt;
+use std::path::{Path, PathBuf};
+use super::server::WhisperServer;
+use super::transcript::Transcript;
+use tauri::{AppHandle, Manager};
 
 /// Get the directory where whisper models are stored
 pub fn models_dir() -> Result<PathBuf> {
@@ -14,31 +15,143 @@ pub fn models_dir() -> Result<PathBuf> {
 /// Delete a downloaded whisper model
 pub fn delete_model(file_name: &str) -> Result<()> {
     let path = models_dir()?.join(file_name);
-    if path.exists() {
-        std::fs::remove_file(&path)?;
+    // Also drop a partial download so the next one starts fresh
+    for path in [path.with_extension("part"), path] {
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
     }
     Ok(())
 }
 
-/// Transcribe audio using whisper.cpp sidecar
+/// Transcribe audio using whisper.cpp: in-process when built with the
+/// `native-whisper` feature, otherwise through the sidecar. With `timestamps`,
+/// the transcript carries timed segments with token confidence.
 pub async fn transcribe(
     app: &AppHandle,
     audio_data: &[u8],
     model_file: &str,
     language: Option<&str>,
     dictionary: &[String],
-) -> Result<String> {
-    let models_path = models_dir()?;
-    let model_path = models_path.join(model_file);
+    timestamps: bool,
+) -> Result<Transcript> {
+    let model_path = model_path(model_file)?;
+
+    // Every engine gets the recorder's WAV, which fallbacks and kept
+    // recordings need anyway, so the native path decodes it back to samples
+    #[cfg(feature = "native-whisper")]
+    let transcript = {
+        let samples = super::native::decode_wav(audio_data)?;
+        transcribe_samples(app, samples, &model_path, language, dictionary, timestamps).await?
+    };
+    #[cfg(not(feature = "native-whisper"))]
+    let transcript = transcribe_sidecar(app, audio_data, &model_path, language, dictionary, timestamps).await?;
+
+    log_result(&transcript.text);
+    Ok(transcript)
+}
 
+/// Transcribe audio through the long-lived `whisper-server` sidecar, starting
+/// it on first use. Saves the model load on every dictation.
+pub async fn transcribe_server(
+    app: &AppHandle,
+    audio_data: &[u8],
+    model_file: &str,
+    language: Option<&str>,
+    dictionary: &[String],
+    timestamps: bool,
+) -> Result<Transcript> {
+    let model_path = model_path(model_file)?;
+    let transcript = app
+        .state::<WhisperServer>()
+        .transcribe(app, audio_data, &model_path, language, dictionary, timestamps)
+        .await?;
+
+    log_result(&transcript.text);
+    Ok(transcript)
+}
+
+/// Start `whisper-server` for `model_file` ahead of the first dictation.
+pub async fn warm_server(app: &AppHandle, model_file: &str) -> Result<()> {
+    let model_path = model_path(model_file)?;
+    app.state::<WhisperServer>().ensure(app, &model_path).await?;
+    Ok(())
+}
+
+fn model_path(model_file: &str) -> Result<PathBuf> {
+    let model_path = models_dir()?.join(model_file);
     if !model_path.exists() {
         anyhow::bail!("Model file not found: {}", model_path.display());
     }
+    Ok(model_path)
+}
+
+fn log_result(text: &str) {
+    if text.is_empty() {
+        log::warn!("[Whisperi] Local transcription result: empty (no voice detected)");
+    } else {
+        log::info!("[Whisperi] Local transcription result: {} chars", text.len());
+    }
+}
+
+/// Run 16 kHz mono samples through the cached in-process model.
+#[cfg(feature = "native-whisper")]
+async fn transcribe_samples(
+    app: &AppHandle,
+    samples: Vec<f32>,
+    model_path: &Path,
+    language: Option<&str>,
+    dictionary: &[String],
+    timestamps: bool,
+) -> Result<Transcript> {
+    use super::native::NativeWhisper;
 
-    // Write audio data to a temporary file
+    let app = app.clone();
+    let model_path = model_path.to_path_buf();
+    let language = language.map(str::to_string);
+    let dictionary = dictionary.to_vec();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        app.state::<NativeWhisper>().transcribe(
+            &model_path,
+            &samples,
+            language.as_deref(),
+            &dictionary,
+            timestamps,
+            num_cpus(),
+        )
+    })
+    .await
+    .context("whisper.cpp task failed")?
+}
+
+/// Run the whisper.cpp sidecar on a temporary WAV file and read the text from
+/// stdout, or from its full JSON output when `timestamps` is set.
+#[cfg(not(feature = "native-whisper"))]
+async fn transcribe_sidecar(
+    app: &AppHandle,
+    audio_data: &[u8],
+    model_path: &Path,
+    language: Option<&str>,
+    dictionary: &[String],
+    timestamps: bool,
+) -> Result<Transcript> {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use tauri_plugin_shell::ShellExt;
+
+    // Write audio data to a temporary file. Live transcription can run the
+    // sidecar while a previous run is still winding down, so names are unique.
+    static NEXT_TEMP_ID: AtomicU64 = AtomicU64::new(0);
     let temp_dir = std::env::temp_dir();
-    let temp_audio = temp_dir.join(format!("whisperi-audio-{}.wav", std::process::id()));
+    let temp_audio = temp_dir.join(format!(
+        "whisperi-audio-{}-{}.wav",
+        std::process::id(),
+        NEXT_TEMP_ID.fetch_add(1, Ordering::Relaxed)
+    ));
     std::fs::write(&temp_audio, audio_data)?;
+    // `-of` takes the path without the `.json` whisper.cpp appends
+    let temp_output = temp_audio.with_extension("");
+    let temp_json = temp_audio.with_extension("json");
 
     // Build whisper.cpp arguments
     let mut args: Vec<String> = vec![
@@ -46,16 +159,20 @@ pub async fn transcribe(
         model_path.to_string_lossy().to_string(),
         "-f".into(),
         temp_audio.to_string_lossy().to_string(),
-        "--no-timestamps".into(),
         "-t".into(),
         num_cpus().to_string(),
     ];
+    if timestamps {
+        args.extend(["-ojf".into(), "-of".into(), temp_output.to_string_lossy().to_string()]);
+    } else {
+        args.push("--no-timestamps".into());
+    }
 
-    if let Some(lang) = language {
-        if lang != "auto" {
-            args.push("-l".into());
-            args.push(lang.to_string());
-        }
+    if let Some(lang) = language
+        && lang != "auto"
+    {
+        args.push("-l".into());
+        args.push(lang.to_string());
     }
 
     if !dictionary.is_empty() {
@@ -77,20 +194,20 @@ pub async fn transcribe(
     let _ = std::fs::remove_file(&temp_audio);
 
     if !output.status.success() {
+        let _ = std::fs::remove_file(&temp_json);
         let stderr = String::from_utf8_lossy(&output.stderr);
         anyhow::bail!("whisper-cpp failed: {}", stderr);
     }
 
-    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    if text.is_empty() {
-        log::warn!("[Whisperi] Local transcription result: empty (no voice detected)");
-    } else {
-        log::info!("[Whisperi] Local transcription result: {} chars", text.len());
+    if timestamps {
+        let json = std::fs::read_to_string(&temp_json).context("whisper-cpp wrote no JSON output");
+        let _ = std::fs::remove_file(&temp_json);
+        return Transcript::from_whisper_json(&json?);
     }
-    Ok(text)
+    Ok(Transcript::plain(String::from_utf8_lossy(&output.stdout).trim()))
 }
 
-fn num_cpus() -> usize {
+pub(super) fn num_cpus() -> usize {
     std::thread::available_parallelism()
         .map(|n| n.get())
         .unwrap_or(4)
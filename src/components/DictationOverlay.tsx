@@ -26,10 +26,9 @@ function DictationOverlayInner() {
   // After in-app update: reopen settings so the user sees the About tab
   useEffect(() => {
     if (!loaded) return;
-    const hasAnyKey =
-      settings.openaiApiKey || settings.anthropicApiKey || settings.geminiApiKey ||
-      settings.groqApiKey || settings.mistralApiKey || settings.qwenApiKey ||
-      settings.openrouterApiKey;
+    const hasAnyKey = Object.entries(settings.apiKeys).some(
+      ([provider, stored]) => stored && !provider.startsWith("custom"),
+    );
     if (!hasAnyKey) {
       showSettings();
       return;
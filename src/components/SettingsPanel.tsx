@@ -20,13 +20,17 @@ import {
   Plus,
   Trash2,
 } from "lucide-react";
-import { setSetting } from "@/services/tauriApi";
+import { setSetting, purgeRecordings } from "@/services/tauriApi";
 import { useSettings } from "@/hooks/useSettings";
 import { Button } from "@/components/ui/button";
 import { Input } from "@/components/ui/input";
 import { Toggle } from "@/components/ui/toggle";
 import { Badge } from "@/components/ui/badge";
 import ApiKeyInput from "@/components/ui/ApiKeyInput";
+import CustomEndpointFields from "@/components/ui/CustomEndpointFields";
+import LocalServerFields from "@/components/ui/LocalServerFields";
+import ModelSelect from "@/components/ui/ModelSelect";
+import FallbackList from "@/components/ui/FallbackList";
 import LanguageSelector from "@/components/ui/LanguageSelector";
 import { SettingsSection, SettingsRow } from "@/components/ui/SettingsSection";
 import { ProviderTabs, type ProviderTabItem } from "@/components/ui/ProviderTabs";
@@ -40,6 +44,7 @@ import {
 import {
   listAudioDevices,
   type AudioDevice,
+  type ProviderPurpose,
   clearTranscriptions,
 } from "@/services/tauriApi";
 import modelRegistry from "@/models/modelRegistryData.json";
@@ -240,6 +245,17 @@ function GeneralSection({ settings, update }: SectionProps) {
             </option>
           ))}
         </select>
+        <SettingsRow label="Resampling quality" description="Filter used to convert the microphone to 16 kHz. Higher removes more aliasing at a small CPU cost.">
+          <select
+            value={settings.resampleQuality}
+            onChange={(e) => update("resampleQuality", e.target.value)}
+            className="w-32 h-9 px-2 text-sm bg-surface-1 border border-border rounded-lg text-foreground"
+          >
+            <option value="fast">Fast</option>
+            <option value="balanced">Balanced</option>
+            <option value="high">High</option>
+          </select>
+        </SettingsRow>
       </SettingsSection>
 
       <SettingsSection title="Behavior">
@@ -261,43 +277,57 @@ function GeneralSection({ settings, update }: SectionProps) {
             onChange={(v) => update("autoPaste", v)}
           />
         </SettingsRow>
+        {settings.autoPaste && (
+          <SettingsRow label="Type as it streams" description="Type enhanced text word by word while the AI is still writing">
+            <Toggle
+              checked={settings.incrementalPaste}
+              onChange={(v) => update("incrementalPaste", v)}
+            />
+          </SettingsRow>
+        )}
         <SettingsRow label="Sound effects" description="Play a sound when recording starts and stops">
           <Toggle
             checked={settings.soundEnabled}
             onChange={(v) => update("soundEnabled", v)}
           />
         </SettingsRow>
+        <SettingsRow label="Stop on silence" description="Stop recording automatically after you stop speaking">
+          <select
+            value={settings.silenceTimeout}
+            onChange={(e) => update("silenceTimeout", Number(e.target.value))}
+            className="w-32 h-9 px-2 text-sm bg-surface-1 border border-border rounded-lg text-foreground"
+          >
+            <option value={0}>Off</option>
+            <option value={2}>2 seconds</option>
+            <option value={3}>3 seconds</option>
+            <option value={5}>5 seconds</option>
+            <option value={10}>10 seconds</option>
+          </select>
+        </SettingsRow>
       </SettingsSection>
     </>
   );
 }
 
-const API_KEY_MAP: Record<string, keyof import("@/hooks/useSettings").Settings> = {
-  openai: "openaiApiKey",
-  anthropic: "anthropicApiKey",
-  gemini: "geminiApiKey",
-  groq: "groqApiKey",
-  mistral: "mistralApiKey",
-  qwen: "qwenApiKey",
-  openrouter: "openrouterApiKey",
-};
-
-function getApiKey(settings: import("@/hooks/useSettings").Settings, provider: string): string {
-  const key = API_KEY_MAP[provider];
-  return key ? (settings[key] as string) || "" : "";
-}
-
-function getApiKeyField(provider: string): keyof import("@/hooks/useSettings").Settings {
-  return API_KEY_MAP[provider] ?? "openaiApiKey";
+/** Props wiring an ApiKeyInput to the stored-key flags in settings. */
+function apiKeyProps({ settings, update }: SectionProps, provider: string, purpose: ProviderPurpose) {
+  return {
+    provider,
+    hasKey: !!settings.apiKeys[provider],
+    onChange: (hasKey: boolean) => update("apiKeys", { ...settings.apiKeys, [provider]: hasKey }),
+    // Custom endpoint keys are stored as customTranscription / customReasoning
+    check: { provider: provider.startsWith("custom") ? "custom" : provider, purpose },
+  };
 }
 
 function getTranscriptionProviders(settings: import("@/hooks/useSettings").Settings): ProviderTabItem[] {
   return [
-    { id: "openai", name: "OpenAI", hasKey: !!settings.openaiApiKey },
-    { id: "groq", name: "Groq", recommended: true, hasKey: !!settings.groqApiKey },
-    { id: "mistral", name: "Mistral", hasKey: !!settings.mistralApiKey },
-    { id: "qwen", name: "Qwen", hasKey: !!settings.qwenApiKey },
-    { id: "openrouter", name: "OpenRouter", hasKey: !!settings.openrouterApiKey },
+    { id: "openai", name: "OpenAI", hasKey: !!settings.apiKeys.openai },
+    { id: "groq", name: "Groq", recommended: true, hasKey: !!settings.apiKeys.groq },
+    { id: "mistral", name: "Mistral", hasKey: !!settings.apiKeys.mistral },
+    { id: "qwen", name: "Qwen", hasKey: !!settings.apiKeys.qwen },
+    { id: "openrouter", name: "OpenRouter", hasKey: !!settings.apiKeys.openrouter },
+    { id: "custom", name: "Custom", hasKey: !!settings.customTranscription.baseUrl },
   ];
 }
 
@@ -322,70 +352,105 @@ function TranscriptionSection({ settings, update }: SectionProps) {
           }}
         />
         <div className="space-y-3">
-          <SettingsRow label="Model">
-            {settings.cloudTranscriptionProvider === "openrouter" ? (
-              <input
-                type="text"
-                value={settings.cloudTranscriptionModel}
-                onChange={(e) => update("cloudTranscriptionModel", e.target.value)}
-                placeholder="e.g. openai/gpt-audio-mini"
-                className="w-72 h-9 px-2 text-sm bg-surface-1 border border-border rounded-lg text-foreground placeholder:text-muted-foreground"
-              />
-            ) : (
-              <select
-                value={settings.cloudTranscriptionModel}
-                onChange={(e) => update("cloudTranscriptionModel", e.target.value)}
-                className="w-72 h-9 px-2 text-sm bg-surface-1 border border-border rounded-lg text-foreground"
-              >
-                {modelRegistry.transcriptionProviders
+          {settings.cloudTranscriptionProvider === "custom" ? (
+            <CustomEndpointFields
+              endpoint={settings.customTranscription}
+              onChange={(endpoint) => update("customTranscription", endpoint)}
+              modelPlaceholder="e.g. Systran/faster-whisper-large-v3"
+            />
+          ) : (
+            <>
+              <SettingsRow label="Model">
+                <ModelSelect
+                  provider={settings.cloudTranscriptionProvider}
+                  purpose="transcription"
+                  value={settings.cloudTranscriptionModel}
+                  onChange={(model) => update("cloudTranscriptionModel", model)}
+                  known={
+                    modelRegistry.transcriptionProviders.find((p) => p.id === settings.cloudTranscriptionProvider)
+                      ?.models ?? []
+                  }
+                  enabled={!!settings.apiKeys[settings.cloudTranscriptionProvider]}
+                  freeform={settings.cloudTranscriptionProvider === "openrouter"}
+                  placeholder="e.g. openai/gpt-audio-mini"
+                />
+              </SettingsRow>
+              {settings.cloudTranscriptionProvider === "openrouter" ? (
+                <p className="text-xs text-muted-foreground -mt-1 text-right">
+                  Enter any model from{" "}
+                  <button
+                    type="button"
+                    onClick={() => import("@tauri-apps/plugin-opener").then((m) => m.openUrl("https://openrouter.ai/models"))}
+                    className="text-primary hover:underline cursor-pointer"
+                  >openrouter.ai/models</button>
+                  {" "}in <code className="text-primary/80">provider/model-name</code> format (must be audio-capable)
+                </p>
+              ) : (() => {
+                const selectedModel = modelRegistry.transcriptionProviders
                   .find((p) => p.id === settings.cloudTranscriptionProvider)
-                  ?.models.map((m) => (
-                    <option key={m.id} value={m.id}>
-                      {m.name}{m.params ? ` (${m.params})` : ""}
-                    </option>
-                  ))}
-              </select>
-            )}
-          </SettingsRow>
-          {settings.cloudTranscriptionProvider === "openrouter" ? (
-            <p className="text-xs text-muted-foreground -mt-1 text-right">
-              Enter any model from{" "}
-              <button
-                type="button"
-                onClick={() => import("@tauri-apps/plugin-opener").then((m) => m.openUrl("https://openrouter.ai/models"))}
-                className="text-primary hover:underline cursor-pointer"
-              >openrouter.ai/models</button>
-              {" "}in <code className="text-primary/80">provider/model-name</code> format (must be audio-capable)
-            </p>
-          ) : (() => {
-            const selectedModel = modelRegistry.transcriptionProviders
-              .find((p) => p.id === settings.cloudTranscriptionProvider)
-              ?.models.find((m) => m.id === settings.cloudTranscriptionModel);
-            return selectedModel?.description ? (
-              <p className="text-xs text-muted-foreground -mt-1 text-right">{selectedModel.description}</p>
-            ) : null;
-          })()}
-          <ApiKeyInput
-            apiKey={getApiKey(settings, settings.cloudTranscriptionProvider)}
-            setApiKey={(key) => update(getApiKeyField(settings.cloudTranscriptionProvider), key)}
-            placeholder="sk-..."
-            label={`${settings.cloudTranscriptionProvider} API Key`}
-            helpText={`Enter your ${settings.cloudTranscriptionProvider} API key`}
-          />
+                  ?.models.find((m) => m.id === settings.cloudTranscriptionModel);
+                return selectedModel?.description ? (
+                  <p className="text-xs text-muted-foreground -mt-1 text-right">{selectedModel.description}</p>
+                ) : null;
+              })()}
+            </>
+          )}
+          {settings.cloudTranscriptionProvider === "custom" ? (
+            <ApiKeyInput
+              {...apiKeyProps({ settings, update }, "customTranscription", "transcription")}
+              placeholder="optional"
+              label="Custom endpoint API Key"
+              helpText="Leave empty if the server needs no key"
+            />
+          ) : (
+            <ApiKeyInput
+              key={settings.cloudTranscriptionProvider}
+              {...apiKeyProps({ settings, update }, settings.cloudTranscriptionProvider, "transcription")}
+              placeholder="sk-..."
+              label={`${settings.cloudTranscriptionProvider} API Key`}
+              helpText={`Enter your ${settings.cloudTranscriptionProvider} API key`}
+            />
+          )}
         </div>
       </SettingsSection>
+      <SettingsSection title="Fallbacks" description="Tried in order when the provider is down, rate limited or rejects the key">
+        <FallbackList
+          choices={settings.transcriptionFallbacks}
+          onChange={(choices) => update("transcriptionFallbacks", choices)}
+          options={[...getTranscriptionProviders(settings), { id: "local", name: "Local model" }].filter(
+            (p) => p.id !== (settings.useLocalWhisper ? "local" : settings.cloudTranscriptionProvider),
+          )}
+          exhaustedLabel="the dictation fails"
+        />
+      </SettingsSection>
+      <SettingsSection title="Live Transcription" description="Show text while you are still speaking">
+        <SettingsRow label="Stream while recording" description="Transcribe in short chunks during recording so stopping returns faster">
+          <Toggle
+            checked={settings.streamingTranscription}
+            onChange={(v) => update("streamingTranscription", v)}
+          />
+        </SettingsRow>
+        <SettingsRow label="Timestamps and confidence" description="Save timed segments with a confidence score in the history (local Whisper, OpenAI Whisper, Groq)">
+          <Toggle
+            checked={settings.transcriptTimestamps}
+            onChange={(v) => update("transcriptTimestamps", v)}
+          />
+        </SettingsRow>
+      </SettingsSection>
     </>
   );
 }
 
 function getReasoningProviders(settings: import("@/hooks/useSettings").Settings): ProviderTabItem[] {
   return [
-    { id: "openai", name: "OpenAI", hasKey: !!settings.openaiApiKey },
-    { id: "anthropic", name: "Anthropic", hasKey: !!settings.anthropicApiKey },
-    { id: "gemini", name: "Gemini", hasKey: !!settings.geminiApiKey },
-    { id: "groq", name: "Groq", recommended: true, hasKey: !!settings.groqApiKey },
-    { id: "qwen", name: "Qwen", hasKey: !!settings.qwenApiKey },
-    { id: "openrouter", name: "OpenRouter", hasKey: !!settings.openrouterApiKey },
+    { id: "openai", name: "OpenAI", hasKey: !!settings.apiKeys.openai },
+    { id: "anthropic", name: "Anthropic", hasKey: !!settings.apiKeys.anthropic },
+    { id: "gemini", name: "Gemini", hasKey: !!settings.apiKeys.gemini },
+    { id: "groq", name: "Groq", recommended: true, hasKey: !!settings.apiKeys.groq },
+    { id: "qwen", name: "Qwen", hasKey: !!settings.apiKeys.qwen },
+    { id: "openrouter", name: "OpenRouter", hasKey: !!settings.apiKeys.openrouter },
+    { id: "local", name: "Local", hasKey: !!settings.localReasoning.baseUrl },
+    { id: "custom", name: "Custom", hasKey: !!settings.customReasoning.baseUrl },
   ];
 }
 
@@ -411,6 +476,9 @@ function AIModelsSection({ settings, update }: SectionProps) {
               // Auto-select the first model for the new provider
               if (id === "openrouter") {
                 update("reasoningModel", "openai/gpt-4o");
+              } else if (id === "local") {
+                // Installed models are listed by the server; pick one there
+                update("reasoningModel", "");
               } else {
                 const provider = modelRegistry.cloudProviders.find((p) => p.id === id);
                 if (provider?.models[0]) {
@@ -420,59 +488,83 @@ function AIModelsSection({ settings, update }: SectionProps) {
             }}
           />
           <div className="space-y-3">
-            <SettingsRow label="Model">
-              {settings.reasoningProvider === "openrouter" ? (
-                <input
-                  type="text"
-                  value={settings.reasoningModel}
-                  onChange={(e) => update("reasoningModel", e.target.value)}
-                  placeholder="e.g. openai/gpt-4o"
-                  className="w-72 h-9 px-2 text-sm bg-surface-1 border border-border rounded-lg text-foreground placeholder:text-muted-foreground"
-                />
-              ) : (
-                <select
-                  value={settings.reasoningModel}
-                  onChange={(e) => update("reasoningModel", e.target.value)}
-                  className="w-72 h-9 px-2 text-sm bg-surface-1 border border-border rounded-lg text-foreground"
-                >
-                  {modelRegistry.cloudProviders
+            {settings.reasoningProvider === "custom" ? (
+              <CustomEndpointFields
+                endpoint={settings.customReasoning}
+                onChange={(endpoint) => update("customReasoning", endpoint)}
+                modelPlaceholder="e.g. Qwen/Qwen3-32B"
+              />
+            ) : settings.reasoningProvider === "local" ? (
+              <LocalServerFields
+                server={settings.localReasoning}
+                onChange={(server) => update("localReasoning", server)}
+                model={settings.reasoningModel}
+                onModelChange={(model) => update("reasoningModel", model)}
+              />
+            ) : (
+              <>
+                <SettingsRow label="Model">
+                  <ModelSelect
+                    provider={settings.reasoningProvider}
+                    purpose="reasoning"
+                    value={settings.reasoningModel}
+                    onChange={(model) => update("reasoningModel", model)}
+                    known={modelRegistry.cloudProviders.find((p) => p.id === settings.reasoningProvider)?.models ?? []}
+                    enabled={!!settings.apiKeys[settings.reasoningProvider]}
+                    freeform={settings.reasoningProvider === "openrouter"}
+                    placeholder="e.g. openai/gpt-4o"
+                  />
+                </SettingsRow>
+                {settings.reasoningProvider === "openrouter" ? (
+                  <p className="text-xs text-muted-foreground -mt-1 text-right">
+                    Enter any model from{" "}
+                    <button
+                      type="button"
+                      onClick={() => import("@tauri-apps/plugin-opener").then((m) => m.openUrl("https://openrouter.ai/models"))}
+                      className="text-primary hover:underline cursor-pointer"
+                    >openrouter.ai/models</button>
+                    {" "}in <code className="text-primary/80">provider/model-name</code> format
+                  </p>
+                ) : (() => {
+                  const selectedModel = modelRegistry.cloudProviders
                     .find((p) => p.id === settings.reasoningProvider)
-                    ?.models.map((m) => (
-                      <option key={m.id} value={m.id}>
-                        {m.name}{m.params ? ` (${m.params})` : ""}
-                      </option>
-                    ))}
-                </select>
-              )}
-            </SettingsRow>
-            {settings.reasoningProvider === "openrouter" ? (
-              <p className="text-xs text-muted-foreground -mt-1 text-right">
-                Enter any model from{" "}
-                <button
-                  type="button"
-                  onClick={() => import("@tauri-apps/plugin-opener").then((m) => m.openUrl("https://openrouter.ai/models"))}
-                  className="text-primary hover:underline cursor-pointer"
-                >openrouter.ai/models</button>
-                {" "}in <code className="text-primary/80">provider/model-name</code> format
-              </p>
-            ) : (() => {
-              const selectedModel = modelRegistry.cloudProviders
-                .find((p) => p.id === settings.reasoningProvider)
-                ?.models.find((m) => m.id === settings.reasoningModel);
-              return selectedModel?.description ? (
-                <p className="text-xs text-muted-foreground -mt-1 text-right">{selectedModel.description}</p>
-              ) : null;
-            })()}
-            <ApiKeyInput
-              apiKey={getApiKey(settings, settings.reasoningProvider)}
-              setApiKey={(key) => update(getApiKeyField(settings.reasoningProvider), key)}
-              label={`${settings.reasoningProvider} API Key`}
-              helpText={`Enter your ${settings.reasoningProvider} API key`}
-            />
+                    ?.models.find((m) => m.id === settings.reasoningModel);
+                  return selectedModel?.description ? (
+                    <p className="text-xs text-muted-foreground -mt-1 text-right">{selectedModel.description}</p>
+                  ) : null;
+                })()}
+              </>
+            )}
+            {settings.reasoningProvider === "local" ? null : settings.reasoningProvider === "custom" ? (
+              <ApiKeyInput
+                {...apiKeyProps({ settings, update }, "customReasoning", "reasoning")}
+                placeholder="optional"
+                label="Custom endpoint API Key"
+                helpText="Leave empty if the server needs no key"
+              />
+            ) : (
+              <ApiKeyInput
+                key={settings.reasoningProvider}
+                {...apiKeyProps({ settings, update }, settings.reasoningProvider, "reasoning")}
+                label={`${settings.reasoningProvider} API Key`}
+                helpText={`Enter your ${settings.reasoningProvider} API key`}
+              />
+            )}
           </div>
         </SettingsSection>
       )}
 
+      {settings.useReasoningModel && (
+        <SettingsSection title="Fallbacks" description="Tried in order when the AI provider fails">
+          <FallbackList
+            choices={settings.reasoningFallbacks}
+            onChange={(choices) => update("reasoningFallbacks", choices)}
+            options={getReasoningProviders(settings).filter((p) => p.id !== settings.reasoningProvider)}
+            exhaustedLabel="paste the transcription without enhancement"
+          />
+        </SettingsSection>
+      )}
+
       <SettingsSection title="System Prompt" description="Cleanup instructions sent to the AI model. Core behavior rules are applied automatically.">
         <div className="flex flex-col flex-1 min-h-0">
           {/* Prompt tabs */}
@@ -638,6 +730,15 @@ function DeveloperSection({ settings, update, toast }: SectionProps & { toast: (
     }
   };
 
+  const handlePurgeRecordings = async (all: boolean) => {
+    try {
+      const count = await purgeRecordings(all);
+      toast({ title: `Deleted ${count} recording${count === 1 ? "" : "s"}`, variant: "success" });
+    } catch (e) {
+      toast({ title: "Failed to delete recordings", description: String(e), variant: "destructive" });
+    }
+  };
+
   return (
     <>
       <SettingsSection title="Debug Mode" description="When enabled, the output includes labeled sections for both the raw transcription and the AI-enhanced result, so you can compare them side by side.">
@@ -654,6 +755,47 @@ function DeveloperSection({ settings, update, toast }: SectionProps & { toast: (
           <Trash2 className="w-3 h-3" /> Clear transcription history
         </Button>
       </SettingsSection>
+
+      <SettingsSection title="Recordings" description="Keep the audio of dictations so a failed one can be played back or exported instead of spoken again">
+        <SettingsRow label="Keep audio">
+          <select
+            value={settings.keepAudio}
+            onChange={(e) => update("keepAudio", e.target.value)}
+            className="w-32 h-9 px-2 text-sm bg-surface-1 border border-border rounded-lg text-foreground"
+          >
+            <option value="off">Never</option>
+            <option value="failures">Failures only</option>
+            <option value="all">Always</option>
+          </select>
+        </SettingsRow>
+        {settings.keepAudio !== "off" && (
+          <>
+            <SettingsRow label="Delete after" description="Days to keep a recording (0 = forever)">
+              <Input
+                type="number"
+                min={0}
+                value={settings.audioRetentionDays}
+                onChange={(e) => update("audioRetentionDays", Math.max(0, Number(e.target.value)))}
+                onBlur={() => handlePurgeRecordings(false)}
+                className="w-20 h-8 text-sm"
+              />
+            </SettingsRow>
+            <SettingsRow label="Size limit" description="Megabytes of recordings to keep, oldest go first (0 = no limit)">
+              <Input
+                type="number"
+                min={0}
+                value={settings.audioRetentionMb}
+                onChange={(e) => update("audioRetentionMb", Math.max(0, Number(e.target.value)))}
+                onBlur={() => handlePurgeRecordings(false)}
+                className="w-20 h-8 text-sm"
+              />
+            </SettingsRow>
+          </>
+        )}
+        <Button variant="outline" size="sm" onClick={() => handlePurgeRecordings(true)} className="text-destructive hover:bg-destructive/10 hover:border-destructive/30">
+          <Trash2 className="w-3 h-3" /> Delete all recordings
+        </Button>
+      </SettingsSection>
     </>
   );
 }
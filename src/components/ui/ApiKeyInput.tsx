@@ -1,10 +1,22 @@
-import React from "react";
-import { Check } from "lucide-react";
+import React, { useState } from "react";
+import { Check, Trash2 } from "lucide-react";
+import {
+  setApiKey,
+  deleteApiKey,
+  testProviderCredentials,
+  type CredentialCheck,
+  type ProviderPurpose,
+} from "@/services/tauriApi";
 import { Input } from "./input";
 
 interface ApiKeyInputProps {
-  apiKey: string;
-  setApiKey: (key: string) => void;
+  /** Provider id, or "customTranscription" / "customReasoning". */
+  provider: string;
+  hasKey: boolean;
+  /** Called after the key was saved or removed. */
+  onChange: (hasKey: boolean) => void;
+  /** Provider id and purpose for the "Test" button; omitted hides it. */
+  check?: { provider: string; purpose: ProviderPurpose };
   className?: string;
   placeholder?: string;
   label?: string;
@@ -12,14 +24,57 @@ interface ApiKeyInputProps {
 }
 
 export default function ApiKeyInput({
-  apiKey,
-  setApiKey,
+  provider,
+  hasKey,
+  onChange,
+  check,
   className = "",
   placeholder = "sk-...",
   label = "API Key",
   helpText = "Get your API key from platform.openai.com",
 }: ApiKeyInputProps) {
-  const hasKey = apiKey.length > 0;
+  // The stored key is never sent back to the UI; this only holds a new one
+  const [draft, setDraft] = useState("");
+  const [error, setError] = useState<string | null>(null);
+  const [result, setResult] = useState<CredentialCheck | null>(null);
+  const [testing, setTesting] = useState(false);
+
+  const save = async () => {
+    if (!draft.trim()) return;
+    try {
+      await setApiKey(provider, draft);
+      setDraft("");
+      setError(null);
+      setResult(null);
+      onChange(true);
+    } catch (e) {
+      setError(String(e));
+    }
+  };
+
+  const remove = async () => {
+    try {
+      await deleteApiKey(provider);
+      setError(null);
+      setResult(null);
+      onChange(false);
+    } catch (e) {
+      setError(String(e));
+    }
+  };
+
+  const test = async () => {
+    if (!check) return;
+    setTesting(true);
+    try {
+      setResult(await testProviderCredentials(check.provider, check.purpose, undefined, true));
+      setError(null);
+    } catch (e) {
+      setError(String(e));
+    } finally {
+      setTesting(false);
+    }
+  };
 
   return (
     <div className={className}>
@@ -27,18 +82,49 @@ export default function ApiKeyInput({
       <div className="relative">
         <Input
           type="password"
-          placeholder={placeholder}
-          value={apiKey}
-          onChange={(e) => setApiKey(e.target.value)}
-          className={`h-8 text-sm ${hasKey ? "pr-8" : ""}`}
+          placeholder={hasKey ? "Saved in secure storage — type to replace" : placeholder}
+          value={draft}
+          onChange={(e) => setDraft(e.target.value)}
+          onBlur={save}
+          onKeyDown={(e) => e.key === "Enter" && save()}
+          className={`h-8 text-sm ${hasKey ? "pr-14" : ""}`}
         />
         {hasKey && (
-          <div className="absolute right-2.5 top-1/2 -translate-y-1/2">
+          <div className="absolute right-2.5 top-1/2 -translate-y-1/2 flex items-center gap-2">
             <Check className="w-3.5 h-3.5 text-success" />
+            <button
+              type="button"
+              onClick={remove}
+              title="Remove key"
+              className="text-muted-foreground hover:text-destructive"
+            >
+              <Trash2 className="w-3.5 h-3.5" />
+            </button>
           </div>
         )}
       </div>
-      {helpText && <p className="text-[12px] text-muted-foreground mt-1">{helpText}</p>}
+      {error ? (
+        <p className="text-[12px] text-destructive mt-1">{error}</p>
+      ) : result ? (
+        <p className={`text-[12px] mt-1 ${result.ok ? "text-success" : "text-destructive"}`}>
+          {result.ok
+            ? `Key works (${result.latencyMs} ms, ${result.modelCount} models)` +
+              (result.unavailable.length > 0 ? ` — not available: ${result.unavailable.join(", ")}` : "")
+            : result.error?.message}
+        </p>
+      ) : (
+        helpText && <p className="text-[12px] text-muted-foreground mt-1">{helpText}</p>
+      )}
+      {check && (
+        <button
+          type="button"
+          onClick={test}
+          disabled={testing}
+          className="text-[12px] text-muted-foreground hover:text-foreground underline mt-1 disabled:opacity-50"
+        >
+          {testing ? "Testing…" : "Test connection"}
+        </button>
+      )}
     </div>
   );
 }
@@ -0,0 +1,102 @@
+import { useEffect, useState } from "react";
+import type { CustomEndpoint } from "@/services/tauriApi";
+import { SettingsRow } from "./SettingsSection";
+
+interface CustomEndpointFieldsProps {
+  endpoint: CustomEndpoint;
+  onChange: (endpoint: CustomEndpoint) => void;
+  urlPlaceholder?: string;
+  modelPlaceholder?: string;
+}
+
+const inputClass =
+  "w-72 h-9 px-2 text-sm bg-surface-1 border border-border rounded-lg text-foreground placeholder:text-muted-foreground";
+
+/** "Name: value" per line, as edited in the extra-headers box. */
+function formatHeaders(headers: Record<string, string>): string {
+  return Object.entries(headers)
+    .map(([name, value]) => `${name}: ${value}`)
+    .join("\n");
+}
+
+function parseHeaders(text: string): Record<string, string> {
+  const headers: Record<string, string> = {};
+  for (const line of text.split("\n")) {
+    const idx = line.indexOf(":");
+    if (idx <= 0) continue;
+    const name = line.slice(0, idx).trim();
+    if (name) headers[name] = line.slice(idx + 1).trim();
+  }
+  return headers;
+}
+
+export default function CustomEndpointFields({
+  endpoint,
+  onChange,
+  urlPlaceholder = "http://localhost:8000/v1",
+  modelPlaceholder = "model name",
+}: CustomEndpointFieldsProps) {
+  const set = <K extends keyof CustomEndpoint>(key: K, value: CustomEndpoint[K]) =>
+    onChange({ ...endpoint, [key]: value });
+
+  // Edit headers as free text and only commit parsed pairs on blur
+  const [headersText, setHeadersText] = useState(formatHeaders(endpoint.extraHeaders));
+  useEffect(() => {
+    setHeadersText(formatHeaders(endpoint.extraHeaders));
+  }, [endpoint.extraHeaders]);
+
+  return (
+    <>
+      <SettingsRow label="Base URL" description="OpenAI-compatible API root, including /v1">
+        <input
+          type="text"
+          value={endpoint.baseUrl}
+          onChange={(e) => set("baseUrl", e.target.value)}
+          placeholder={urlPlaceholder}
+          className={inputClass}
+        />
+      </SettingsRow>
+      <SettingsRow label="Model">
+        <input
+          type="text"
+          value={endpoint.model}
+          onChange={(e) => set("model", e.target.value)}
+          placeholder={modelPlaceholder}
+          className={inputClass}
+        />
+      </SettingsRow>
+      <SettingsRow label="Authentication" description="How the API key is sent">
+        <select
+          value={endpoint.authScheme}
+          onChange={(e) => set("authScheme", e.target.value as CustomEndpoint["authScheme"])}
+          className="w-72 h-9 px-2 text-sm bg-surface-1 border border-border rounded-lg text-foreground"
+        >
+          <option value="bearer">Authorization: Bearer</option>
+          <option value="header">Custom header</option>
+          <option value="none">None</option>
+        </select>
+      </SettingsRow>
+      {endpoint.authScheme === "header" && (
+        <SettingsRow label="Header name">
+          <input
+            type="text"
+            value={endpoint.authHeader}
+            onChange={(e) => set("authHeader", e.target.value)}
+            placeholder="api-key"
+            className={inputClass}
+          />
+        </SettingsRow>
+      )}
+      <SettingsRow label="Extra headers" description="One per line, as Name: value">
+        <textarea
+          value={headersText}
+          onChange={(e) => setHeadersText(e.target.value)}
+          onBlur={() => set("extraHeaders", parseHeaders(headersText))}
+          placeholder="X-Team: speech"
+          rows={2}
+          className="w-72 px-2 py-1.5 text-sm bg-surface-1 border border-border rounded-lg text-foreground placeholder:text-muted-foreground resize-y"
+        />
+      </SettingsRow>
+    </>
+  );
+}
@@ -0,0 +1,88 @@
+import { ArrowDown, ArrowUp, X } from "lucide-react";
+import type { ProviderChoice } from "@/services/tauriApi";
+
+interface FallbackListProps {
+  choices: ProviderChoice[];
+  onChange: (choices: ProviderChoice[]) => void;
+  /** Providers that can be added, excluding the primary. */
+  options: { id: string; name: string }[];
+  /** What happens once every provider has failed. */
+  exhaustedLabel: string;
+}
+
+const selectClass = "h-8 px-2 text-sm bg-surface-1 border border-border rounded-lg text-foreground";
+
+export default function FallbackList({ choices, onChange, options, exhaustedLabel }: FallbackListProps) {
+  const nameOf = (id: string) => options.find((o) => o.id === id)?.name ?? id;
+  const available = options.filter((o) => !choices.some((c) => c.provider === o.id));
+
+  const move = (index: number, offset: number) => {
+    const next = [...choices];
+    [next[index], next[index + offset]] = [next[index + offset], next[index]];
+    onChange(next);
+  };
+
+  return (
+    <div className="space-y-1.5">
+      {choices.map((choice, i) => (
+        <div key={choice.provider} className="flex items-center gap-2">
+          <span className="w-5 text-xs text-muted-foreground text-right">{i + 1}.</span>
+          <span className="w-28 text-sm text-foreground truncate">{nameOf(choice.provider)}</span>
+          <input
+            type="text"
+            value={choice.model}
+            onChange={(e) => onChange(choices.map((c, j) => (j === i ? { ...c, model: e.target.value } : c)))}
+            placeholder="default model"
+            className="flex-1 h-8 px-2 text-sm bg-surface-1 border border-border rounded-lg text-foreground placeholder:text-muted-foreground"
+          />
+          <button
+            type="button"
+            onClick={() => move(i, -1)}
+            disabled={i === 0}
+            title="Try earlier"
+            className="text-muted-foreground hover:text-foreground disabled:opacity-30"
+          >
+            <ArrowUp className="w-4 h-4" />
+          </button>
+          <button
+            type="button"
+            onClick={() => move(i, 1)}
+            disabled={i === choices.length - 1}
+            title="Try later"
+            className="text-muted-foreground hover:text-foreground disabled:opacity-30"
+          >
+            <ArrowDown className="w-4 h-4" />
+          </button>
+          <button
+            type="button"
+            onClick={() => onChange(choices.filter((_, j) => j !== i))}
+            title="Remove"
+            className="text-muted-foreground hover:text-destructive"
+          >
+            <X className="w-4 h-4" />
+          </button>
+        </div>
+      ))}
+      <div className="flex items-center justify-between gap-2">
+        <p className="text-xs text-muted-foreground">
+          {choices.length > 0 ? "Then: " : "If the provider fails: "}
+          {exhaustedLabel}
+        </p>
+        {available.length > 0 && (
+          <select
+            value=""
+            onChange={(e) => onChange([...choices, { provider: e.target.value, model: "" }])}
+            className={selectClass}
+          >
+            <option value="">Add fallback…</option>
+            {available.map((o) => (
+              <option key={o.id} value={o.id}>
+                {o.name}
+              </option>
+            ))}
+          </select>
+        )}
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,113 @@
+import { useCallback, useEffect, useState } from "react";
+import { RefreshCw } from "lucide-react";
+import { listLocalModels, type LocalServer } from "@/services/tauriApi";
+import { SettingsRow } from "./SettingsSection";
+
+interface LocalServerFieldsProps {
+  server: LocalServer;
+  onChange: (server: LocalServer) => void;
+  model: string;
+  onModelChange: (model: string) => void;
+}
+
+const inputClass =
+  "w-72 h-9 px-2 text-sm bg-surface-1 border border-border rounded-lg text-foreground placeholder:text-muted-foreground";
+
+const DEFAULT_URLS: Record<LocalServer["api"], string> = {
+  ollama: "http://127.0.0.1:11434",
+  openai: "http://127.0.0.1:8080",
+};
+
+export default function LocalServerFields({ server, onChange, model, onModelChange }: LocalServerFieldsProps) {
+  const [models, setModels] = useState<string[]>([]);
+  const [error, setError] = useState<string | null>(null);
+  const [loading, setLoading] = useState(false);
+
+  const refresh = useCallback(async () => {
+    setLoading(true);
+    try {
+      setModels(await listLocalModels(server.baseUrl, server.api));
+      setError(null);
+    } catch (e) {
+      setModels([]);
+      setError(String(e));
+    } finally {
+      setLoading(false);
+    }
+  }, [server.baseUrl, server.api]);
+
+  useEffect(() => {
+    refresh();
+  }, [server.api]); // eslint-disable-line react-hooks/exhaustive-deps
+
+  const setApi = (api: LocalServer["api"]) => {
+    // Follow the default port when the URL is still the other server's default
+    const baseUrl = server.baseUrl === DEFAULT_URLS[server.api] ? DEFAULT_URLS[api] : server.baseUrl;
+    onChange({ baseUrl, api });
+  };
+
+  return (
+    <>
+      <SettingsRow label="Server" description="Ollama, or llama.cpp / LM Studio with an OpenAI-compatible API">
+        <select
+          value={server.api}
+          onChange={(e) => setApi(e.target.value as LocalServer["api"])}
+          className="w-72 h-9 px-2 text-sm bg-surface-1 border border-border rounded-lg text-foreground"
+        >
+          <option value="ollama">Ollama</option>
+          <option value="openai">OpenAI-compatible (llama.cpp)</option>
+        </select>
+      </SettingsRow>
+      <SettingsRow label="Server URL">
+        <input
+          type="text"
+          value={server.baseUrl}
+          onChange={(e) => onChange({ ...server, baseUrl: e.target.value })}
+          onBlur={refresh}
+          placeholder={DEFAULT_URLS[server.api]}
+          className={inputClass}
+        />
+      </SettingsRow>
+      <SettingsRow label="Model">
+        <div className="flex items-center gap-2">
+          {models.length > 0 ? (
+            <select
+              value={model}
+              onChange={(e) => onModelChange(e.target.value)}
+              className="w-64 h-9 px-2 text-sm bg-surface-1 border border-border rounded-lg text-foreground"
+            >
+              {!models.includes(model) && <option value={model}>{model || "Select a model"}</option>}
+              {models.map((m) => (
+                <option key={m} value={m}>
+                  {m}
+                </option>
+              ))}
+            </select>
+          ) : (
+            <input
+              type="text"
+              value={model}
+              onChange={(e) => onModelChange(e.target.value)}
+              placeholder="e.g. qwen3:8b"
+              className="w-64 h-9 px-2 text-sm bg-surface-1 border border-border rounded-lg text-foreground placeholder:text-muted-foreground"
+            />
+          )}
+          <button
+            type="button"
+            onClick={refresh}
+            disabled={loading}
+            title="Refresh installed models"
+            className="h-9 w-6 flex items-center justify-center text-muted-foreground hover:text-foreground disabled:opacity-50"
+          >
+            <RefreshCw className={`w-4 h-4 ${loading ? "animate-spin" : ""}`} />
+          </button>
+        </div>
+      </SettingsRow>
+      {error && (
+        <p className="text-xs text-destructive -mt-1 text-right">
+          Server not reachable. Start it, or enter the model name by hand.
+        </p>
+      )}
+    </>
+  );
+}
@@ -0,0 +1,109 @@
+import { useCallback, useEffect, useState } from "react";
+import { RefreshCw } from "lucide-react";
+import { listProviderModels, type ProviderPurpose } from "@/services/tauriApi";
+
+interface ModelSelectProps {
+  provider: string;
+  purpose: ProviderPurpose;
+  value: string;
+  onChange: (model: string) => void;
+  /** Curated models from the registry, listed first with their names. */
+  known: { id: string; name: string; params?: string }[];
+  /** Only query the provider once it has a key. */
+  enabled: boolean;
+  /** Free text with suggestions, for providers with very long lists (OpenRouter). */
+  freeform?: boolean;
+  placeholder?: string;
+}
+
+const fieldClass =
+  "w-64 h-9 px-2 text-sm bg-surface-1 border border-border rounded-lg text-foreground placeholder:text-muted-foreground";
+
+export default function ModelSelect({
+  provider,
+  purpose,
+  value,
+  onChange,
+  known,
+  enabled,
+  freeform = false,
+  placeholder,
+}: ModelSelectProps) {
+  const [live, setLive] = useState<string[]>([]);
+  const [loading, setLoading] = useState(false);
+
+  const load = useCallback(
+    async (refresh: boolean) => {
+      if (!enabled) return;
+      setLoading(true);
+      try {
+        setLive((await listProviderModels(provider, purpose, refresh)).models);
+      } catch (e) {
+        // The curated list still works; the key check reports why
+        console.warn(`Listing ${provider} models failed:`, e);
+        setLive([]);
+      } finally {
+        setLoading(false);
+      }
+    },
+    [provider, purpose, enabled]
+  );
+
+  useEffect(() => {
+    setLive([]);
+    load(false);
+  }, [load]);
+
+  const extra = live.filter((id) => !known.some((m) => m.id === id));
+  const listed = known.some((m) => m.id === value) || live.includes(value);
+
+  return (
+    <div className="flex items-center gap-2">
+      {freeform ? (
+        <>
+          <input
+            type="text"
+            list={`${purpose}-${provider}-models`}
+            value={value}
+            onChange={(e) => onChange(e.target.value)}
+            placeholder={placeholder}
+            className={fieldClass}
+          />
+          <datalist id={`${purpose}-${provider}-models`}>
+            {live.map((id) => (
+              <option key={id} value={id} />
+            ))}
+          </datalist>
+        </>
+      ) : (
+        <select value={value} onChange={(e) => onChange(e.target.value)} className={fieldClass}>
+          {!listed && <option value={value}>{value || "Select a model"}</option>}
+          {known.map((m) => (
+            <option key={m.id} value={m.id}>
+              {m.name}
+              {m.params ? ` (${m.params})` : ""}
+            </option>
+          ))}
+          {extra.length > 0 && (
+            <optgroup label="Also offered by the provider">
+              {extra.map((id) => (
+                <option key={id} value={id}>
+                  {id}
+                </option>
+              ))}
+            </optgroup>
+          )}
+        </select>
+      )}
+      <button
+        type="button"
+        onClick={() => load(true)}
+        disabled={!enabled || loading}
+        title="Refresh models from the provider"
+        className="h-9 w-6 flex items-center justify-center text-muted-foreground hover:text-foreground disabled:opacity-50"
+      >
+        <RefreshCw className={`w-4 h-4 ${loading ? "animate-spin" : ""}`} />
+      </button>
+    </div>
+  );
+}
@@ -1,4 +1,4 @@
-import { Brain, Wrench } from "lucide-react";
+import { Brain, HardDrive, Wrench } from "lucide-react";
 
 interface ProviderIconProps {
   provider: string;
@@ -19,6 +19,9 @@ export function ProviderIcon({ provider, className = "w-5 h-5" }: ProviderIconPr
   if (provider === "custom") {
     return <Wrench className={className} />;
   }
+  if (provider === "local") {
+    return <HardDrive className={className} />;
+  }
 
   const label = PROVIDER_LABELS[provider];
   if (label) {
@@ -2,43 +2,17 @@ import { useState, useCallback, useRef, useEffect } from "react";
 import {
   startRecording as apiStartRecording,
   stopRecording as apiStopRecording,
-  transcribeLocal,
-  transcribeCloud,
-  processReasoning,
-  pasteText,
+  dictate,
   onAudioLevel,
   onRecordingError,
-  getApiKey,
-  getAgentName,
-  getAgentAliases,
-  getCustomDictionary,
+  onRecordingAutoStop,
+  onTranscriptPartial,
+  onEnhancementDelta,
+  onProviderRetry,
   getSetting,
-  saveTranscription,
 } from "@/services/tauriApi";
-import { getSystemPrompt, getChatSystemPrompt, getUserPrompt, detectChatMode } from "@/config/prompts";
 import { playStartSound, playStopSound } from "@/utils/sounds";
 
-/** Strip <think>...</think> blocks from reasoning model output. */
-function stripThinkTags(text: string): string {
-  return text.replace(/<think>[\s\S]*?<\/think>/g, "").trim();
-}
-
-/** Check if transcription is empty or just dictionary words echoed back (Whisper hallucination on silence). */
-function isEmptyTranscription(text: string, dictionary: string[]): boolean {
-  const trimmed = text.trim();
-  if (!trimmed) return true;
-  if (dictionary.length === 0) return false;
-
-  // Normalize: lowercase, strip punctuation, split into words
-  const normalize = (s: string) =>
-    s.toLowerCase().replace(/[^\p{L}\p{N}\s]/gu, "").split(/\s+/).filter(Boolean);
-  const textWords = normalize(trimmed);
-  if (textWords.length === 0) return true;
-
-  const dictWords = new Set(dictionary.flatMap((entry) => normalize(entry)));
-  return textWords.every((w) => dictWords.has(w));
-}
-
 type RecordingPhase = "idle" | "recording" | "processing";
 
 interface UseAudioRecordingOptions {
@@ -50,8 +24,10 @@ export function useAudioRecording({ onToast }: UseAudioRecordingOptions = {}) {
   const [audioLevel, setAudioLevel] = useState(0);
   const [transcript, setTranscript] = useState("");
   const unlistenRef = useRef<(() => void)[]>([]);
+  // Latest stop(), so the auto-stop listener doesn't need to resubscribe
+  const stopRef = useRef<() => Promise<void>>(async () => {});
 
-  // Subscribe to audio-level and recording-error events
+  // Subscribe to audio-level, recording-error, auto-stop, provider-retry, live transcript and enhancement events
   useEffect(() => {
     let cancelled = false;
 
@@ -69,11 +45,40 @@ export function useAudioRecording({ onToast }: UseAudioRecordingOptions = {}) {
           });
         }
       });
+      const unlistenPartial = await onTranscriptPartial((partial) => {
+        if (!cancelled) setTranscript(partial.text);
+      });
+      const unlistenDelta = await onEnhancementDelta((delta) => {
+        if (!cancelled) setTranscript(delta.text);
+      });
+      const unlistenAutoStop = await onRecordingAutoStop(() => {
+        if (!cancelled) stopRef.current();
+      });
+      const unlistenRetry = await onProviderRetry((retry) => {
+        if (!cancelled) {
+          onToast?.({
+            title: "Retrying",
+            description: retry.message,
+            variant: "default",
+          });
+        }
+      });
       if (!cancelled) {
-        unlistenRef.current = [unlistenLevel, unlistenError];
+        unlistenRef.current = [
+          unlistenLevel,
+          unlistenError,
+          unlistenPartial,
+          unlistenDelta,
+          unlistenAutoStop,
+          unlistenRetry,
+        ];
       } else {
         unlistenLevel();
         unlistenError();
+        unlistenPartial();
+        unlistenDelta();
+        unlistenAutoStop();
+        unlistenRetry();
       }
     }
 
@@ -108,153 +113,19 @@ export function useAudioRecording({ onToast }: UseAudioRecordingOptions = {}) {
     if (soundEnabled !== false) playStopSound();
 
     try {
-      const audioData = await apiStopRecording();
+      // The backend owns the rest of the pipeline: stop → transcribe → enhance → save → paste
+      const outcome = await dictate();
       setAudioLevel(0);
-
-      // Load settings for transcription
-      const [
-        useLocal,
-        whisperModel,
-        cloudProvider,
-        cloudModel,
-        language,
-        dictionary,
-        useReasoning,
-        reasoningModel,
-        reasoningProvider,
-        autoPaste,
-        useCustomPrompt,
-        customSystemPrompt,
-        agentName,
-        agentAliases,
-        debugMode,
-      ] = await Promise.all([
-        getSetting<boolean>("useLocalWhisper"),
-        getSetting<string>("whisperModel"),
-        getSetting<string>("cloudTranscriptionProvider"),
-        getSetting<string>("cloudTranscriptionModel"),
-        getSetting<string>("preferredLanguage"),
-        getCustomDictionary(),
-        getSetting<boolean>("useReasoningModel"),
-        getSetting<string>("reasoningModel"),
-        getSetting<string>("reasoningProvider"),
-        getSetting<boolean>("autoPaste"),
-        getSetting<boolean>("useCustomPrompt"),
-        getSetting<string>("customSystemPrompt"),
-        getAgentName(),
-        getAgentAliases(),
-        getSetting<boolean>("debugMode"),
-      ]);
-
-      // Include agent name + aliases in transcription dictionary so STT recognizes them
-      const extraWords = [agentName, ...agentAliases]
-        .filter((w): w is string => !!w?.trim())
-        .filter((w) => !dictionary.includes(w));
-      const transcriptionDict = extraWords.length > 0
-        ? [...dictionary, ...extraWords]
-        : dictionary;
-
-      // Transcribe
-      let rawText: string;
-      if (useLocal) {
-        rawText = await transcribeLocal(
-          audioData,
-          whisperModel ?? "base",
-          language ?? undefined,
-          transcriptionDict
-        );
+      if (outcome.text) {
+        console.log("[Whisperi] Dictation:", outcome.text);
+        setTranscript(outcome.text);
       } else {
-        const provider = cloudProvider ?? "openai";
-        const apiKey = await getApiKey(provider);
-        if (!apiKey) {
-          console.warn(`[Whisperi] No API key for transcription provider: ${provider}`);
-          onToast?.({
-            title: "API Key Missing",
-            description: `No API key configured for ${provider}. Set it in Settings.`,
-            variant: "destructive",
-          });
-          setPhase("idle");
-          return;
-        }
-        const model = cloudModel ?? "gpt-4o-mini-transcribe";
-        console.log(`[Whisperi] Transcribing with ${provider}/${model}...`);
-        rawText = await transcribeCloud(
-          audioData,
-          provider,
-          apiKey,
-          model,
-          language ?? undefined,
-          transcriptionDict
-        );
-      }
-      console.log("[Whisperi] Transcription:", rawText);
-
-      // Skip when transcription is empty or just dictionary words echoed back (silence hallucination)
-      if (isEmptyTranscription(rawText, transcriptionDict)) {
         console.log("[Whisperi] Empty transcription (silence or dictionary echo), skipping.");
-        setPhase("idle");
-        return;
       }
-
-      // AI reasoning (post-processing)
-      let finalText = rawText;
-      let rawAiResponse: string | null = null;
-      if (useReasoning && reasoningModel && reasoningProvider) {
-        try {
-          const rApiKey = await getApiKey(reasoningProvider);
-          if (rApiKey) {
-            console.log(`[Whisperi] Enhancing with ${reasoningProvider}/${reasoningModel}...`);
-            const isChatMode = detectChatMode(rawText, agentName, agentAliases);
-            const systemPrompt = isChatMode
-              ? getChatSystemPrompt(agentName, dictionary, language ?? undefined)
-              : getSystemPrompt(agentName, dictionary, language ?? undefined,
-                  useCustomPrompt && customSystemPrompt ? customSystemPrompt : undefined);
-            const userPrompt = getUserPrompt(rawText);
-            rawAiResponse = await processReasoning(
-              userPrompt,
-              reasoningModel,
-              reasoningProvider,
-              systemPrompt,
-              rApiKey
-            );
-            finalText = stripThinkTags(rawAiResponse);
-            console.log("[Whisperi] Enhanced:", finalText);
-          } else {
-            console.warn(`[Whisperi] No API key for enhancement provider: ${reasoningProvider}`);
-          }
-        } catch (e) {
-          console.error("[Whisperi] Enhancement error:", e);
-          if (debugMode) {
-            finalText = `${rawText}\n\n[Enhancement Error]\n${e}`;
-          }
-        }
-      }
-
-      // In debug mode, output both raw and enhanced with labels
-      // Show the raw AI response (with <think> tags) so the user can inspect reasoning
-      const outputText = debugMode && finalText !== rawText
-        ? `[Transcription]\n${rawText}\n\n[Enhanced]\n${finalText}${rawAiResponse && rawAiResponse !== finalText ? `\n\n[Raw AI Response]\n${rawAiResponse}` : ""}`
-        : finalText;
-
-      setTranscript(outputText);
-
-      // Copy to clipboard and paste into focused app (if enabled)
-      if (autoPaste !== false) {
-        await pasteText(outputText);
-      }
-
-      // Save to database
-      await saveTranscription(
-        rawText,
-        finalText !== rawText ? finalText : null,
-        useReasoning ? "ai" : "none",
-        agentName,
-        null
-      );
-
       setPhase("idle");
     } catch (e) {
       console.error("[Whisperi] Transcription failed:", e);
+      setAudioLevel(0);
       onToast?.({
         title: "Transcription Failed",
         description: String(e),
@@ -263,6 +134,7 @@ export function useAudioRecording({ onToast }: UseAudioRecordingOptions = {}) {
       setPhase("idle");
     }
   }, [phase, onToast]);
+  stopRef.current = stop;
 
   const toggle = useCallback(async (deviceId?: string) => {
     if (phase === "idle") {
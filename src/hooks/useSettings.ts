@@ -3,29 +3,44 @@ import { emit, listen } from "@tauri-apps/api/event";
 import {
   getSetting,
   setSetting,
-  getApiKey,
-  setApiKey,
+  testApiKey,
+  API_KEY_PROVIDERS,
   getAgentName,
   setAgentName as setAgentNameApi,
   getCustomDictionary,
   setCustomDictionary as setCustomDictionaryApi,
   getAgentAliases,
   setAgentAliases as setAgentAliasesApi,
+  EMPTY_CUSTOM_ENDPOINT,
+  DEFAULT_LOCAL_SERVER,
+  type CustomEndpoint,
+  type LocalServer,
+  type ProviderChoice,
 } from "@/services/tauriApi";
 
 export interface Settings {
   // Transcription
   useLocalWhisper: boolean;
+  localEngine: string;
   whisperModel: string;
+  parakeetModel: string;
+  whisperServer: boolean;
   preferredLanguage: string;
   cloudTranscriptionProvider: string;
   cloudTranscriptionModel: string;
+  customTranscription: CustomEndpoint;
+  transcriptionFallbacks: ProviderChoice[];
+  streamingTranscription: boolean;
+  transcriptTimestamps: boolean;
   customDictionary: string[];
 
   // Reasoning
   useReasoningModel: boolean;
   reasoningModel: string;
   reasoningProvider: string;
+  customReasoning: CustomEndpoint;
+  localReasoning: LocalServer;
+  reasoningFallbacks: ProviderChoice[];
   useCustomPrompt: boolean;
   customSystemPrompt: string;
 
@@ -35,10 +50,18 @@ export interface Settings {
 
   // Output
   autoPaste: boolean;
+  incrementalPaste: boolean;
   soundEnabled: boolean;
+  silenceTimeout: number;
 
   // Microphone
   selectedMicDeviceId: string;
+  resampleQuality: string;
+  // Recordings
+  /** "off", "failures" or "all": which dictations keep their audio. */
+  keepAudio: string;
+  audioRetentionDays: number;
+  audioRetentionMb: number;
 
   // Agent
   agentName: string;
@@ -47,43 +70,47 @@ export interface Settings {
   // Developer
   debugMode: boolean;
 
-  // API keys
-  openaiApiKey: string;
-  anthropicApiKey: string;
-  geminiApiKey: string;
-  groqApiKey: string;
-  mistralApiKey: string;
-  qwenApiKey: string;
-  openrouterApiKey: string;
+  // Providers with a key in secure storage (the keys stay in the backend)
+  apiKeys: Record<string, boolean>;
 }
 
 const DEFAULTS: Settings = {
   useLocalWhisper: false,
+  localEngine: "whisper",
   whisperModel: "base",
+  parakeetModel: "parakeet-tdt-0.6b-v3",
+  whisperServer: false,
   preferredLanguage: "auto",
   cloudTranscriptionProvider: "openai",
   cloudTranscriptionModel: "gpt-4o-mini-transcribe",
+  customTranscription: EMPTY_CUSTOM_ENDPOINT,
+  transcriptionFallbacks: [],
+  streamingTranscription: false,
+  transcriptTimestamps: false,
   customDictionary: [],
   useReasoningModel: true,
   reasoningModel: "gpt-5-mini",
   reasoningProvider: "openai",
+  customReasoning: EMPTY_CUSTOM_ENDPOINT,
+  localReasoning: DEFAULT_LOCAL_SERVER,
+  reasoningFallbacks: [],
   useCustomPrompt: false,
   customSystemPrompt: "",
   autoPaste: true,
+  incrementalPaste: false,
   soundEnabled: true,
+  silenceTimeout: 0,
   dictationKey: "",
   activationMode: "tap",
   selectedMicDeviceId: "",
+  resampleQuality: "balanced",
+  keepAudio: "failures",
+  audioRetentionDays: 30,
+  audioRetentionMb: 500,
   agentName: "Whisperi",
   agentAliases: [],
   debugMode: false,
-  openaiApiKey: "",
-  anthropicApiKey: "",
-  geminiApiKey: "",
-  groqApiKey: "",
-  mistralApiKey: "",
-  qwenApiKey: "",
-  openrouterApiKey: "",
+  apiKeys: {},
 };
 
 export function useSettings() {
@@ -97,89 +124,119 @@ export function useSettings() {
     async function load() {
       const [
         useLocalWhisper,
+        localEngine,
         whisperModel,
+        parakeetModel,
+        whisperServer,
         preferredLanguage,
         cloudTranscriptionProvider,
         cloudTranscriptionModel,
+        customTranscription,
+        transcriptionFallbacks,
+        streamingTranscription,
+        transcriptTimestamps,
         useReasoningModel,
         reasoningModel,
         reasoningProvider,
+        customReasoning,
+        localReasoning,
+        reasoningFallbacks,
         useCustomPrompt,
         customSystemPrompt,
         autoPaste,
+        incrementalPaste,
         soundEnabled,
+        silenceTimeout,
         dictationKey,
         activationMode,
         selectedMicDeviceId,
+        resampleQuality,
+        keepAudio,
+        audioRetentionDays,
+        audioRetentionMb,
         debugMode,
         agentNameVal,
         agentAliases,
         customDictionary,
-        openaiApiKey,
-        anthropicApiKey,
-        geminiApiKey,
-        groqApiKey,
-        mistralApiKey,
-        qwenApiKey,
-        openrouterApiKey,
+        keyStatuses,
       ] = await Promise.all([
         getSetting<boolean>("useLocalWhisper"),
+        getSetting<string>("localEngine"),
         getSetting<string>("whisperModel"),
+        getSetting<string>("parakeetModel"),
+        getSetting<boolean>("whisperServer"),
         getSetting<string>("preferredLanguage"),
         getSetting<string>("cloudTranscriptionProvider"),
         getSetting<string>("cloudTranscriptionModel"),
+        getSetting<CustomEndpoint>("customTranscription"),
+        getSetting<ProviderChoice[]>("transcriptionFallbacks"),
+        getSetting<boolean>("streamingTranscription"),
+        getSetting<boolean>("transcriptTimestamps"),
         getSetting<boolean>("useReasoningModel"),
         getSetting<string>("reasoningModel"),
         getSetting<string>("reasoningProvider"),
+        getSetting<CustomEndpoint>("customReasoning"),
+        getSetting<LocalServer>("localReasoning"),
+        getSetting<ProviderChoice[]>("reasoningFallbacks"),
         getSetting<boolean>("useCustomPrompt"),
         getSetting<string>("customSystemPrompt"),
         getSetting<boolean>("autoPaste"),
+        getSetting<boolean>("incrementalPaste"),
         getSetting<boolean>("soundEnabled"),
+        getSetting<number>("silenceTimeout"),
         getSetting<string>("dictationKey"),
         getSetting<"tap" | "push">("activationMode"),
         getSetting<string>("selectedMicDeviceId"),
+        getSetting<string>("resampleQuality"),
+        getSetting<string>("keepAudio"),
+        getSetting<number>("audioRetentionDays"),
+        getSetting<number>("audioRetentionMb"),
         getSetting<boolean>("debugMode"),
         getAgentName(),
         getAgentAliases(),
         getCustomDictionary(),
-        getApiKey("openai"),
-        getApiKey("anthropic"),
-        getApiKey("gemini"),
-        getApiKey("groq"),
-        getApiKey("mistral"),
-        getApiKey("qwen"),
-        getApiKey("openrouter"),
+        Promise.all(API_KEY_PROVIDERS.map(testApiKey)),
       ]);
 
       if (cancelled) return;
 
       const resolved: Settings = {
         useLocalWhisper: useLocalWhisper ?? DEFAULTS.useLocalWhisper,
+        localEngine: localEngine ?? DEFAULTS.localEngine,
         whisperModel: whisperModel ?? DEFAULTS.whisperModel,
+        parakeetModel: parakeetModel ?? DEFAULTS.parakeetModel,
+        whisperServer: whisperServer ?? DEFAULTS.whisperServer,
         preferredLanguage: preferredLanguage ?? DEFAULTS.preferredLanguage,
         cloudTranscriptionProvider: cloudTranscriptionProvider ?? DEFAULTS.cloudTranscriptionProvider,
         cloudTranscriptionModel: cloudTranscriptionModel ?? DEFAULTS.cloudTranscriptionModel,
+        customTranscription: customTranscription ?? DEFAULTS.customTranscription,
+        transcriptionFallbacks: transcriptionFallbacks ?? DEFAULTS.transcriptionFallbacks,
+        streamingTranscription: streamingTranscription ?? DEFAULTS.streamingTranscription,
+        transcriptTimestamps: transcriptTimestamps ?? DEFAULTS.transcriptTimestamps,
         useReasoningModel: useReasoningModel ?? DEFAULTS.useReasoningModel,
         reasoningModel: reasoningModel ?? DEFAULTS.reasoningModel,
         reasoningProvider: reasoningProvider ?? DEFAULTS.reasoningProvider,
+        customReasoning: customReasoning ?? DEFAULTS.customReasoning,
+        localReasoning: localReasoning ?? DEFAULTS.localReasoning,
+        reasoningFallbacks: reasoningFallbacks ?? DEFAULTS.reasoningFallbacks,
         useCustomPrompt: useCustomPrompt ?? DEFAULTS.useCustomPrompt,
         customSystemPrompt: customSystemPrompt ?? DEFAULTS.customSystemPrompt,
         autoPaste: autoPaste ?? DEFAULTS.autoPaste,
+        incrementalPaste: incrementalPaste ?? DEFAULTS.incrementalPaste,
         soundEnabled: soundEnabled ?? DEFAULTS.soundEnabled,
+        silenceTimeout: silenceTimeout ?? DEFAULTS.silenceTimeout,
         dictationKey: dictationKey ?? DEFAULTS.dictationKey,
         activationMode: activationMode ?? DEFAULTS.activationMode,
         selectedMicDeviceId: selectedMicDeviceId ?? DEFAULTS.selectedMicDeviceId,
+        resampleQuality: resampleQuality ?? DEFAULTS.resampleQuality,
+        keepAudio: keepAudio ?? DEFAULTS.keepAudio,
+        audioRetentionDays: audioRetentionDays ?? DEFAULTS.audioRetentionDays,
+        audioRetentionMb: audioRetentionMb ?? DEFAULTS.audioRetentionMb,
         debugMode: debugMode ?? DEFAULTS.debugMode,
         agentName: agentNameVal,
         agentAliases,
         customDictionary,
-        openaiApiKey,
-        anthropicApiKey,
-        geminiApiKey,
-        groqApiKey,
-        mistralApiKey,
-        qwenApiKey,
-        openrouterApiKey,
+        apiKeys: Object.fromEntries(API_KEY_PROVIDERS.map((p, i) => [p, keyStatuses[i].stored])),
       };
 
       // Persist defaults to store for keys that were missing, so the
@@ -187,19 +244,35 @@ export function useSettings() {
       // always sees the same values the UI shows.
       const keysToCheck: { stored: unknown; key: keyof Settings }[] = [
         { stored: useLocalWhisper, key: "useLocalWhisper" },
+        { stored: localEngine, key: "localEngine" },
         { stored: whisperModel, key: "whisperModel" },
+        { stored: parakeetModel, key: "parakeetModel" },
+        { stored: whisperServer, key: "whisperServer" },
         { stored: preferredLanguage, key: "preferredLanguage" },
         { stored: cloudTranscriptionProvider, key: "cloudTranscriptionProvider" },
         { stored: cloudTranscriptionModel, key: "cloudTranscriptionModel" },
+        { stored: customTranscription, key: "customTranscription" },
+        { stored: transcriptionFallbacks, key: "transcriptionFallbacks" },
+        { stored: streamingTranscription, key: "streamingTranscription" },
+        { stored: transcriptTimestamps, key: "transcriptTimestamps" },
         { stored: useReasoningModel, key: "useReasoningModel" },
         { stored: reasoningModel, key: "reasoningModel" },
         { stored: reasoningProvider, key: "reasoningProvider" },
+        { stored: customReasoning, key: "customReasoning" },
+        { stored: localReasoning, key: "localReasoning" },
+        { stored: reasoningFallbacks, key: "reasoningFallbacks" },
         { stored: useCustomPrompt, key: "useCustomPrompt" },
         { stored: customSystemPrompt, key: "customSystemPrompt" },
         { stored: autoPaste, key: "autoPaste" },
+        { stored: incrementalPaste, key: "incrementalPaste" },
         { stored: soundEnabled, key: "soundEnabled" },
+        { stored: silenceTimeout, key: "silenceTimeout" },
         { stored: debugMode, key: "debugMode" },
         { stored: activationMode, key: "activationMode" },
+        { stored: resampleQuality, key: "resampleQuality" },
+        { stored: keepAudio, key: "keepAudio" },
+        { stored: audioRetentionDays, key: "audioRetentionDays" },
+        { stored: audioRetentionMb, key: "audioRetentionMb" },
       ];
       for (const { stored, key } of keysToCheck) {
         if (stored == null) {
@@ -245,9 +318,8 @@ export function useSettings() {
         setAgentAliasesApi(value as string[]);
       } else if (key === "customDictionary") {
         setCustomDictionaryApi(value as string[]);
-      } else if (key.endsWith("ApiKey")) {
-        const provider = key.replace("ApiKey", "");
-        setApiKey(provider, value as string);
+      } else if (key === "apiKeys") {
+        // Keys are saved through setApiKey; only the stored flags live here
       } else {
         setSetting(key, value);
       }
@@ -40,8 +40,16 @@ export async function onRecordingError(
   });
 }
 
+/** Fired when the speaker has been silent for the configured `silenceTimeout`. */
+export async function onRecordingAutoStop(callback: () => void): Promise<UnlistenFn> {
+  return listen("recording-auto-stop", () => {
+    callback();
+  });
+}
+
 // Transcription
-export interface WhisperModelStatus {
+/** A local model of either engine and whether it has been downloaded. */
+export interface ModelStatus {
   id: string;
   name: string;
   description: string;
@@ -51,24 +59,66 @@ export interface WhisperModelStatus {
   recommended: boolean;
 }
 
+export type LocalEngine = "whisper" | "parakeet";
+
+/** User-defined OpenAI-compatible endpoint, used when the provider is "custom". */
+export interface CustomEndpoint {
+  baseUrl: string;
+  authScheme: "bearer" | "header" | "none";
+  /** Header name for the "header" auth scheme, e.g. "api-key". */
+  authHeader: string;
+  extraHeaders: Record<string, string>;
+  model: string;
+}
+
+export const EMPTY_CUSTOM_ENDPOINT: CustomEndpoint = {
+  baseUrl: "",
+  authScheme: "bearer",
+  authHeader: "",
+  extraHeaders: {},
+  model: "",
+};
+
+/** Ollama / llama.cpp server, used when the reasoning provider is "local". */
+export interface LocalServer {
+  baseUrl: string;
+  /** "ollama" for the native API, "openai" for OpenAI-compatible servers. */
+  api: "ollama" | "openai";
+}
+
+export const DEFAULT_LOCAL_SERVER: LocalServer = {
+  baseUrl: "http://127.0.0.1:11434",
+  api: "ollama",
+};
+
+/**
+ * Entry in a fallback list. An empty model means the provider's default; in
+ * transcription fallbacks "local" stands for the configured local engine.
+ */
+export interface ProviderChoice {
+  provider: string;
+  model: string;
+}
+
 export async function transcribeLocal(
   audioData: number[],
   model: string,
   language?: string,
   dictionary?: string[],
+  engine?: LocalEngine,
 ): Promise<string> {
   return invoke("transcribe_local", {
     audioData,
     model,
     language,
     dictionary: dictionary ?? [],
+    engine,
   });
 }
 
 export async function transcribeCloud(
   audioData: number[],
   provider: string,
-  apiKey: string,
   model: string,
   language?: string,
   dictionary?: string[],
@@ -76,14 +126,13 @@ export async function transcribeCloud(
   return invoke("transcribe_cloud", {
     audioData,
     provider,
-    apiKey,
     model,
     language,
     dictionary: dictionary ?? [],
   });
 }
 
-export async function listWhisperModels(): Promise<WhisperModelStatus[]> {
+export async function listWhisperModels(): Promise<ModelStatus[]> {
   return invoke("list_whisper_models");
 }
 
@@ -99,6 +148,23 @@ export async function getWhisperStatus(): Promise<boolean> {
   return invoke("get_whisper_status");
 }
 
+export async function listParakeetModels(): Promise<ModelStatus[]> {
+  return invoke("list_parakeet_models");
+}
+
+export async function downloadParakeetModel(modelId: string): Promise<void> {
+  return invoke("download_parakeet_model", { modelId });
+}
+
+export async function deleteParakeetModel(modelId: string): Promise<void> {
+  return invoke("delete_parakeet_model", { modelId });
+}
+
+/** Cancel a whisper or Parakeet download; resolves false if none was running. */
+export async function cancelModelDownload(modelId: string): Promise<boolean> {
+  return invoke("cancel_model_download", { modelId });
+}
+
 export interface ModelDownloadProgress {
   model_id: string;
   downloaded: number;
@@ -115,12 +181,16 @@ export async function onModelDownloadProgress(
 }
 
 // Reasoning
+/** Models installed on a local server; omitted arguments use the saved settings. */
+export async function listLocalModels(baseUrl?: string, api?: LocalServer["api"]): Promise<string[]> {
+  return invoke("list_local_models", { baseUrl, api });
+}
+
 export async function processReasoning(
   text: string,
   model: string,
   provider: string,
   systemPrompt: string,
-  apiKey: string,
   maxTokens?: number,
 ): Promise<string> {
   return invoke("process_reasoning", {
@@ -128,11 +198,111 @@ export async function processReasoning(
     model,
     provider,
     systemPrompt,
-    apiKey,
     maxTokens,
   });
 }
 
+// Dictation pipeline
+export type DictationStage =
+  | "recording"
+  | "transcribing"
+  | "enhancing"
+  | "pasted"
+  | "done"
+  | "failed";
+
+/** Structured provider failure; `message` is ready to show to the user. */
+export interface ProviderErrorInfo {
+  kind:
+    | "unknownProvider"
+    | "missingApiKey"
+    | "config"
+    | "unauthorized"
+    | "rateLimited"
+    | "quotaExceeded"
+    | "server"
+    | "timeout"
+    | "api"
+    | "network"
+    | "invalidResponse";
+  message: string;
+  retryable: boolean;
+  retryAfterSecs?: number;
+}
+
+export interface DictationStageEvent {
+  stage: DictationStage;
+  text?: string;
+  error?: string;
+  provider_error?: ProviderErrorInfo;
+}
+
+export interface DictationOutcome {
+  transcription_id: number | null;
+  raw_text: string;
+  text: string;
+  pasted: boolean;
+}
+
+/** Stop the active recording and run transcribe → enhance → save → paste in the backend. */
+export async function dictate(): Promise<DictationOutcome> {
+  return invoke("dictate");
+}
+
+export async function onDictationStage(
+  callback: (event: DictationStageEvent) => void,
+): Promise<UnlistenFn> {
+  return listen<DictationStageEvent>("dictation-stage", (event) => {
+    callback(event.payload);
+  });
+}
+
+export interface ProviderRetryEvent {
+  provider: string;
+  attempt: number;
+  delayMs: number;
+  /** e.g. "Groq is rate limiting requests, retrying in 4s" */
+  message: string;
+}
+
+/** Fired before the backend retries a failed provider call. */
+export async function onProviderRetry(
+  callback: (event: ProviderRetryEvent) => void,
+): Promise<UnlistenFn> {
+  return listen<ProviderRetryEvent>("provider-retry", (event) => {
+    callback(event.payload);
+  });
+}
+
+export interface TranscriptPartialEvent {
+  text: string;
+  is_final: boolean;
+}
+
+export async function onTranscriptPartial(
+  callback: (event: TranscriptPartialEvent) => void,
+): Promise<UnlistenFn> {
+  return listen<TranscriptPartialEvent>("transcript-partial", (event) => {
+    callback(event.payload);
+  });
+}
+
+export interface EnhancementDeltaEvent {
+  provider: string;
+  /** Newly visible text. */
+  delta: string;
+  /** Everything streamed so far, without `<think>` blocks. */
+  text: string;
+}
+
+export async function onEnhancementDelta(
+  callback: (event: EnhancementDeltaEvent) => void,
+): Promise<UnlistenFn> {
+  return listen<EnhancementDeltaEvent>("enhancement-delta", (event) => {
+    callback(event.payload);
+  });
+}
+
 // Database
 export interface Transcription {
   id: number;
@@ -143,6 +313,45 @@ export interface Transcription {
   processing_method: string;
   agent_name: string | null;
   error: string | null;
+  /** Provider that produced the text, after any fallbacks. */
+  transcription_provider: string | null;
+  reasoning_provider: string | null;
+  /** Language the transcription engine detected. */
+  language: string | null;
+  /** Timed segments of `original_text`; empty unless timestamps were enabled. */
+  segments: TranscriptSegment[];
+  /** Compressed size of the kept recording in bytes; null when none was kept. */
+  audio_size: number | null;
+  /** Dictation this row revises; null for a dictation. */
+  parent_id: number | null;
+  /** Length of the recording after silence trimming. */
+  audio_duration_ms: number | null;
+  input_device: string | null;
+  transcription_model: string | null;
+  /** Language asked of the engine ("auto" to detect); `language` is the detected one. */
+  requested_language: string | null;
+  reasoning_model: string | null;
+  /** Stage latencies; transcription and enhancement include fallbacks. */
+  transcription_ms: number | null;
+  enhancement_ms: number | null;
+  paste_ms: number | null;
+  total_ms: number | null;
+  prompt_tokens: number | null;
+  completion_tokens: number | null;
+  /** Application that had focus when the text was delivered. */
+  target_app: string | null;
+  /** Null when auto-paste is off. */
+  paste_succeeded: boolean | null;
+}
+
+export interface TranscriptSegment {
+  /** Seconds into the recording. */
+  start: number;
+  end: number;
+  text: string;
+  /** 0–1; low values mark words worth double-checking. */
+  confidence?: number;
+  avg_logprob?: number;
 }
 
 export async function saveTranscription(
@@ -168,6 +377,68 @@ export async function getTranscriptions(
   return invoke("get_transcriptions", { limit, offset });
 }
 
+/** A dictation followed by its revisions, oldest first. */
+export async function getRevisions(id: number): Promise<Transcription[]> {
+  return invoke("get_revisions", { id });
+}
+
+/** Narrows a history search; unset fields don't filter. Dates are UTC. */
+export interface SearchFilters {
+  /** `2025-06-01` or `2025-06-01 18:00:00`. */
+  from?: string;
+  /** A bare date includes the whole day. */
+  to?: string;
+  processingMethod?: "ai" | "none";
+  agentName?: string;
+  errorsOnly?: boolean;
+  /** Transcription or enhancement provider id. */
+  provider?: string;
+  limit?: number;
+  offset?: number;
+}
+
+export interface SnippetPart {
+  text: string;
+  /** Whether this part matched a search word, for highlighting. */
+  matched: boolean;
+}
+
+export interface SearchHit extends Transcription {
+  /** Best-matching passage; empty when the query was empty. */
+  snippet: SnippetPart[];
+}
+
+/**
+ * Full-text search over the original and enhanced text of the history. Every
+ * word must match, the last one as a prefix; results come best match first.
+ */
+export async function searchTranscriptions(query: string, filters?: SearchFilters): Promise<SearchHit[]> {
+  return invoke("search_transcriptions", { query, filters });
+}
+
+/**
+ * Transcribe the kept recording of a history row again with another engine
+ * ("local", "whisper", "parakeet" or a cloud provider id). Resolves to the
+ * saved revision.
+ */
+export async function retranscribe(
+  id: number,
+  provider: string,
+  model?: string,
+  language?: string,
+): Promise<Transcription> {
+  return invoke("retranscribe", { id, provider, model, language });
+}
+
+/** Enhance a history row's text again, optionally with another prompt or agent name. */
+export async function reenhance(
+  id: number,
+  prompt?: string,
+  agentName?: string,
+): Promise<Transcription> {
+  return invoke("reenhance", { id, prompt, agentName });
+}
+
 export async function deleteTranscription(id: number): Promise<void> {
   return invoke("delete_transcription", { id });
 }
@@ -176,6 +447,37 @@ export async function clearTranscriptions(): Promise<void> {
   return invoke("clear_transcriptions");
 }
 
+// Recordings
+/** WAV bytes of the recording kept for a history row. */
+export async function getRecording(id: number): Promise<ArrayBuffer> {
+  return invoke("get_recording", { id });
+}
+
+/** Play the recording kept for a history row. */
+export async function playRecording(id: number): Promise<HTMLAudioElement> {
+  const url = URL.createObjectURL(new Blob([await getRecording(id)], { type: "audio/wav" }));
+  const audio = new Audio(url);
+  audio.addEventListener("ended", () => URL.revokeObjectURL(url));
+  await audio.play();
+  return audio;
+}
+
+export async function exportRecording(id: number, path: string): Promise<void> {
+  return invoke("export_recording", { id, path });
+}
+
+export async function deleteRecording(id: number): Promise<void> {
+  return invoke("delete_recording", { id });
+}
+
+/**
+ * Delete every kept recording, or with `all` false only those the retention
+ * settings no longer cover. Resolves to the number deleted.
+ */
+export async function purgeRecordings(all: boolean): Promise<number> {
+  return invoke("purge_recordings", { all });
+}
+
 // Clipboard
 export async function pasteText(text: string): Promise<void> {
   return invoke("paste_text", { text });
@@ -199,10 +501,78 @@ export async function getAllSettings(): Promise<Record<string, unknown>> {
 }
 
 // Models
-export async function getModelRegistry(): Promise<unknown> {
+export interface RegistryProvider {
+  id: string;
+  name: string;
+  baseUrl?: string;
+  models: { id: string; name: string; description: string; params?: string }[];
+  capabilities: { prompt: boolean; language: boolean; streaming: boolean; timestamps: boolean };
+}
+
+export interface ModelRegistry {
+  whisperModels: Record<string, {
+    name: string;
+    description: string;
+    size: string;
+    sizeMb: number;
+    fileName: string;
+    downloadUrl: string;
+    recommended: boolean;
+    supportedLanguages: string[];
+  }>;
+  parakeetModels: Record<string, {
+    name: string;
+    description: string;
+    size: string;
+    sizeMb: number;
+    supportedLanguages: string[];
+    recommended: boolean;
+    downloadUrl: string;
+    extractDir: string;
+  }>;
+  transcriptionProviders: RegistryProvider[];
+  cloudProviders: RegistryProvider[];
+}
+
+export async function getModelRegistry(): Promise<ModelRegistry> {
   return invoke("get_model_registry");
 }
 
+export interface ProviderSummary {
+  id: string;
+  capabilities: RegistryProvider["capabilities"];
+  requiresApiKey: boolean;
+}
+
+/** Providers the backend has clients for, by id. */
+export async function listProviders(): Promise<{ transcription: ProviderSummary[]; reasoning: ProviderSummary[] }> {
+  return invoke("list_providers");
+}
+
+export interface ProviderModels {
+  provider: string;
+  purpose: ProviderPurpose;
+  /** Model ids, sorted. */
+  models: string[];
+  /** Unix seconds. */
+  fetchedAt: number;
+  cached: boolean;
+  /** The provider couldn't be reached, so an expired list was returned. */
+  stale: boolean;
+}
+
+/**
+ * Models the provider currently offers for `purpose`, queried with the stored
+ * key. Cached in the database for a day unless `refresh` is set.
+ */
+export async function listProviderModels(
+  provider: string,
+  purpose: ProviderPurpose,
+  refresh = false
+): Promise<ProviderModels> {
+  return invoke("list_provider_models", { provider, purpose, refresh });
+}
+
 // App
 export async function quitApp(): Promise<void> {
   return invoke("quit_app");
@@ -226,26 +596,69 @@ export async function setAgentName(name: string): Promise<void> {
   return setSetting("agentName", name);
 }
 
-// API keys (stored in tauri-plugin-store settings.json)
-const API_KEY_MAP: Record<string, string> = {
-  openai: "openaiApiKey",
-  anthropic: "anthropicApiKey",
-  gemini: "geminiApiKey",
-  groq: "groqApiKey",
-  mistral: "mistralApiKey",
-  qwen: "qwenApiKey",
-  openrouter: "openrouterApiKey",
-};
+// API keys (kept in the OS keyring by the backend; they are never read back)
+export const API_KEY_PROVIDERS = [
+  "openai",
+  "anthropic",
+  "gemini",
+  "groq",
+  "mistral",
+  "qwen",
+  "openrouter",
+  "customTranscription",
+  "customReasoning",
+];
 
-export async function getApiKey(provider: string): Promise<string> {
-  const key = API_KEY_MAP[provider] ?? `${provider}ApiKey`;
-  const value = await getSetting<string>(key);
-  return value || "";
+export interface ApiKeyStatus {
+  stored: boolean;
+  /** "keyring", or "file" when no OS credential store is available. */
+  store: "keyring" | "file";
 }
 
+/** Save a provider's key in secure storage; an empty key deletes it. */
 export async function setApiKey(provider: string, apiKey: string): Promise<void> {
-  const key = API_KEY_MAP[provider] ?? `${provider}ApiKey`;
-  return setSetting(key, apiKey);
+  return invoke("set_api_key", { provider, apiKey });
+}
+
+export async function deleteApiKey(provider: string): Promise<void> {
+  return invoke("delete_api_key", { provider });
+}
+
+export async function testApiKey(provider: string): Promise<ApiKeyStatus> {
+  return invoke("test_api_key", { provider });
+}
+
+export type ProviderPurpose = "transcription" | "reasoning";
+
+export interface CredentialCheck {
+  provider: string;
+  purpose: ProviderPurpose;
+  /** Whether the provider accepted the key. */
+  ok: boolean;
+  latencyMs: number;
+  /** Requested models the key can use. */
+  accessible: string[];
+  /** Requested models the provider doesn't list for this key. */
+  unavailable: string[];
+  modelCount: number;
+  error?: ProviderErrorInfo;
+  /** Unix seconds. */
+  checkedAt: number;
+  cached: boolean;
+}
+
+/**
+ * Check the stored key with a cheap authenticated request (the provider's
+ * model list). `models` defaults to the registry's models for the provider;
+ * results are cached for a few minutes unless `refresh` is set.
+ */
+export async function testProviderCredentials(
+  provider: string,
+  purpose: ProviderPurpose,
+  models?: string[],
+  refresh = false
+): Promise<CredentialCheck> {
+  return invoke("test_provider_credentials", { provider, purpose, models, refresh });
 }
 
 // Custom dictionary